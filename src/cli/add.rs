@@ -2,6 +2,8 @@ use crate::{
     consts,
     environment::{get_up_to_date_prefix, verify_prefix_location_unchanged, LockFileUsage},
     project::{manifest::PyPiRequirement, DependencyType, Project, SpecType},
+    pypi_marker_env::cpython_marker_environment,
+    utils::requirements_file::{FileRequirement, RequirementsFiles, SkippedLine},
 };
 use clap::Parser;
 use itertools::{Either, Itertools};
@@ -10,6 +12,7 @@ use miette::{IntoDiagnostic, WrapErr};
 use rattler_conda_types::{
     version_spec::{LogicalOperator, RangeOperator},
     MatchSpec, NamelessMatchSpec, PackageName, Platform, Version, VersionBumpType, VersionSpec,
+    VersionWithSource,
 };
 use rattler_repodata_gateway::sparse::SparseRepoData;
 use rattler_solve::{resolvo, SolverImpl};
@@ -54,7 +57,8 @@ pub struct Args {
     /// - `pixi add --pypi boto3`
     /// - `pixi add --pypi "boto3==version"
     ///
-    #[arg(required = true)]
+    /// The requirements of a pip requirements file can be added with `--pypi -r requirements.txt`.
+    #[arg(required_unless_present = "requirement")]
     pub specs: Vec<String>,
 
     /// The path to 'pixi.toml'
@@ -85,6 +89,16 @@ pub struct Args {
     #[arg(long, short)]
     pub platform: Vec<Platform>,
 
+    /// Add the requirements from a pip requirements file, can be used more than once. Requires
+    /// `--pypi`.
+    #[arg(long, short = 'r', requires = "pypi")]
+    pub requirement: Vec<PathBuf>,
+
+    /// Constrain the versions of the requirements with a pip constraints file, can be used more
+    /// than once.
+    #[arg(long, short = 'c', requires = "requirement")]
+    pub constraint: Vec<PathBuf>,
+
     /// Resolution scheme to use
     #[arg(skip)]
     pub sdist_resolution: SDistResolution,
@@ -126,6 +140,9 @@ pub async fn execute(args: Args) -> miette::Result<()> {
         .collect::<Vec<Platform>>();
     project.manifest.add_platforms(platforms_to_add.iter())?;
 
+    // The specs that are added, used to report them to the user.
+    let mut added = args.specs.clone();
+
     match dependency_type {
         DependencyType::CondaDependency(spec_type) => {
             let specs = args
@@ -156,20 +173,35 @@ pub async fn execute(args: Args) -> miette::Result<()> {
                 .collect::<miette::Result<Vec<_>>>()?;
 
             // Move those requirements into our custom PyPiRequirement
-            let specs = pep508_requirements
+            let mut specs = pep508_requirements
                 .into_iter()
                 .map(|req| {
                     let name = rip::types::PackageName::from_str(req.name.as_str())?;
                     let requirement = PyPiRequirement::from(req);
-                    Ok((name, requirement))
+                    Ok((name, requirement, spec_platforms.clone()))
                 })
                 .collect::<Result<Vec<_>, rip::types::ParsePackageNameError>>()
                 .into_diagnostic()?;
 
+            // Add the requirements from the requirements files
+            if !args.requirement.is_empty() {
+                let file_specs = requirements_file_specs(
+                    &project,
+                    &args.requirement,
+                    &args.constraint,
+                    spec_platforms,
+                )?;
+                added.extend(
+                    file_specs
+                        .iter()
+                        .map(|(name, requirement, _)| requirement.as_pep508(name).to_string()),
+                );
+                specs.extend(file_specs);
+            }
+
             add_pypi_specs_to_project(
                 &mut project,
                 specs,
-                spec_platforms,
                 args.no_lockfile_update,
                 args.no_install,
                 args.sdist_resolution,
@@ -178,7 +210,7 @@ pub async fn execute(args: Args) -> miette::Result<()> {
         }
     }?;
 
-    for package in args.specs {
+    for package in added {
         eprintln!(
             "{}Added {}",
             console::style(console::Emoji("✔ ", "")).green(),
@@ -208,15 +240,16 @@ pub async fn execute(args: Args) -> miette::Result<()> {
     Ok(())
 }
 
+/// Adds the pypi `specs` to the project. Each spec is added for the given platforms, or for all
+/// platforms if no platforms are given.
 pub async fn add_pypi_specs_to_project(
     project: &mut Project,
-    specs: Vec<(rip::types::PackageName, PyPiRequirement)>,
-    specs_platforms: &Vec<Platform>,
+    specs: Vec<(rip::types::PackageName, PyPiRequirement, Vec<Platform>)>,
     no_update_lockfile: bool,
     no_install: bool,
     sdist_resolution: SDistResolution,
 ) -> miette::Result<()> {
-    for (name, spec) in &specs {
+    for (name, spec, specs_platforms) in &specs {
         // TODO: Get best version
         // Add the dependency to the project
         if specs_platforms.is_empty() {
@@ -341,6 +374,164 @@ pub async fn add_conda_specs_to_project(
     Ok(())
 }
 
+/// Reads the pip requirements and constraints files and converts the requirements into pypi
+/// dependencies. The versions of the requirements are restricted by the constraints on the same
+/// package. Lines that can not be represented in the manifest are reported as a warning.
+fn requirements_file_specs(
+    project: &Project,
+    requirement_files: &[PathBuf],
+    constraint_files: &[PathBuf],
+    specs_platforms: &[Platform],
+) -> miette::Result<Vec<(rip::types::PackageName, PyPiRequirement, Vec<Platform>)>> {
+    let files = RequirementsFiles::from_paths(requirement_files, constraint_files)?;
+    let mut skipped = files.skipped;
+    let skip = |file_requirement: &FileRequirement, reason: &str| SkippedLine {
+        path: file_requirement.path.clone(),
+        line: file_requirement.line,
+        contents: file_requirement.requirement.to_string(),
+        reason: reason.to_string(),
+    };
+
+    // Collect the version constraints per package
+    let mut constraints = HashMap::<rip::types::PackageName, Vec<_>>::new();
+    for constraint in files.constraints.iter() {
+        let Ok(name) = rip::types::PackageName::from_str(&constraint.requirement.name) else {
+            skipped.push(skip(constraint, "invalid package name"));
+            continue;
+        };
+        if constraint.requirement.marker.is_some() {
+            skipped.push(skip(
+                constraint,
+                "environment markers on constraints are not supported",
+            ));
+            continue;
+        }
+        if let Some(pep508_rs::VersionOrUrl::VersionSpecifier(specifiers)) =
+            &constraint.requirement.version_or_url
+        {
+            constraints
+                .entry(name)
+                .or_default()
+                .push((constraint, specifiers.clone()));
+        }
+    }
+
+    let mut specs = Vec::new();
+    for file_requirement in files.requirements.iter() {
+        let Ok(name) = rip::types::PackageName::from_str(&file_requirement.requirement.name) else {
+            skipped.push(skip(file_requirement, "invalid package name"));
+            continue;
+        };
+        let platforms =
+            match requirement_platforms(project, &file_requirement.requirement, specs_platforms) {
+                Ok(platforms) => platforms,
+                Err(reason) => {
+                    skipped.push(skip(file_requirement, &reason));
+                    continue;
+                }
+            };
+
+        let mut requirement = PyPiRequirement::from(file_requirement.requirement.clone());
+        if let Some(constraints) = constraints.get(&name) {
+            requirement.version = Some(
+                requirement
+                    .version
+                    .iter()
+                    .flat_map(|specifiers| specifiers.iter())
+                    .chain(
+                        constraints
+                            .iter()
+                            .flat_map(|(_, specifiers)| specifiers.iter()),
+                    )
+                    .cloned()
+                    .collect(),
+            );
+        }
+        specs.push((name, requirement, platforms));
+    }
+
+    // Constraints only restrict the packages that are added
+    for (name, constraints) in constraints.iter() {
+        if !specs.iter().any(|(spec_name, _, _)| spec_name == name) {
+            for (constraint, _) in constraints {
+                skipped.push(skip(
+                    *constraint,
+                    "the package is not one of the requirements, only constraints on requirements are supported",
+                ));
+            }
+        }
+    }
+
+    if !skipped.is_empty() {
+        tracing::warn!(
+            "The following lines of the requirements files were not imported:\n{}",
+            skipped.iter().map(|line| format!("  {line}")).join("\n")
+        );
+    }
+
+    Ok(specs)
+}
+
+/// Returns the platforms a requirement from a requirements file should be added for. Requirements
+/// without environment markers are added for `specs_platforms`. The environment markers of other
+/// requirements are evaluated for each platform of the project, the requirement is only added for
+/// the platforms on which they hold. Returns the reason if the requirement can not be added.
+fn requirement_platforms(
+    project: &Project,
+    requirement: &pep508_rs::Requirement,
+    specs_platforms: &[Platform],
+) -> Result<Vec<Platform>, String> {
+    if requirement.marker.is_none() {
+        return Ok(specs_platforms.to_vec());
+    }
+
+    let candidates = if specs_platforms.is_empty() {
+        project
+            .platforms()
+            .into_iter()
+            .sorted_by_key(|platform| platform.as_str())
+            .collect_vec()
+    } else {
+        specs_platforms.to_vec()
+    };
+
+    // The python version of the environment is not known, so markers that depend on the python
+    // version can not be evaluated. These are detected by evaluating the markers for a range of
+    // python versions.
+    let python_versions = ["2.7", "3.6", "3.8", "3.10", "3.12", "3.13"]
+        .map(|version| VersionWithSource::from_str(version).expect("a valid python version"));
+
+    let mut platforms = Vec::new();
+    for platform in candidates.iter().copied() {
+        let matches = python_versions
+            .iter()
+            .map(|version| {
+                cpython_marker_environment(platform, version)
+                    .map(|environment| requirement.evaluate_markers(&environment, Vec::new()))
+            })
+            .collect::<miette::Result<Vec<_>>>()
+            .map_err(|err| err.to_string())?;
+        if !matches.iter().all_equal() {
+            return Err(String::from(
+                "environment markers that depend on the python version are not supported",
+            ));
+        }
+        if matches[0] {
+            platforms.push(platform);
+        }
+    }
+
+    if platforms.is_empty() {
+        Err(String::from(
+            "the environment markers do not match any platform of the project",
+        ))
+    } else if specs_platforms.is_empty() && platforms.len() == candidates.len() {
+        Ok(Vec::new())
+    } else {
+        Ok(platforms)
+    }
+}
+
 /// Given several specs determines the highest installable version for them.
 pub fn determine_best_version(
     project: &Project,
@@ -446,4 +637,46 @@ mod tests {
             .unwrap()
             .to_string(), @">=1.2.0,<1.4");
     }
+
+    #[test]
+    fn test_requirement_platforms() {
+        let manifest = crate::project::manifest::Manifest::from_str(
+            std::path::Path::new(""),
+            r#"
+        [project]
+        name = "foo"
+        channels = []
+        platforms = ["linux-64", "osx-arm64", "win-64"]
+        "#,
+        )
+        .unwrap();
+        let project = Project::from_manifest(manifest);
+        let platforms = |requirement: &str, specs_platforms: &[Platform]| {
+            requirement_platforms(
+                &project,
+                &pep508_rs::Requirement::from_str(requirement).unwrap(),
+                specs_platforms,
+            )
+        };
+
+        assert_eq!(platforms("requests", &[]), Ok(vec![]));
+        assert_eq!(
+            platforms("requests", &[Platform::Linux64]),
+            Ok(vec![Platform::Linux64])
+        );
+        assert_eq!(
+            platforms("pywin32; sys_platform == 'win32'", &[]),
+            Ok(vec![Platform::Win64])
+        );
+        assert_eq!(
+            platforms("uvloop; platform_system != 'Windows'", &[]),
+            Ok(vec![Platform::Linux64, Platform::OsxArm64])
+        );
+        assert_eq!(
+            platforms("six; os_name == 'nt' or os_name == 'posix'", &[]),
+            Ok(vec![])
+        );
+        assert!(platforms("tomli; python_version < '3.11'", &[]).is_err());
+        assert!(platforms("pywin32; sys_platform == 'win32'", &[Platform::Linux64]).is_err());
+    }
 }
@@ -0,0 +1,53 @@
+use crate::{project::manifest::Manifest, Project};
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Format the project manifest: sort the dependency tables and normalize the dependency specs.
+///
+/// Comments and the layout of the rest of the manifest are preserved.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The path to 'pixi.toml'
+    #[arg(long)]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Don't write the formatted manifest, exit with a non-zero exit code if the manifest is not
+    /// formatted.
+    #[arg(long)]
+    pub check: bool,
+}
+
+pub fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+
+    let manifests = std::iter::once(&project.manifest).chain(project.workspace_members());
+    let unformatted: Vec<Manifest> = manifests
+        .filter(|manifest| !manifest.is_formatted())
+        .cloned()
+        .collect();
+
+    if args.check {
+        for manifest in unformatted.iter() {
+            eprintln!(
+                "{} is not formatted",
+                console::style(manifest.path.display()).bold()
+            );
+        }
+        if !unformatted.is_empty() {
+            miette::bail!("the project manifest is not formatted, run `pixi fmt` to format it");
+        }
+        return Ok(());
+    }
+
+    for mut manifest in unformatted {
+        manifest.format();
+        manifest.save()?;
+        eprintln!(
+            "{}Formatted {}",
+            console::style(console::Emoji("✔ ", "")).green(),
+            console::style(manifest.path.display()).bold()
+        );
+    }
+
+    Ok(())
+}
@@ -1,8 +1,10 @@
-use crate::{config::get_default_author, consts};
+use crate::{
+    config::get_default_author, consts, utils::conda_environment_file::CondaEnvironmentFile,
+};
 use clap::Parser;
 use miette::IntoDiagnostic;
 use minijinja::{context, Environment};
-use rattler_conda_types::Platform;
+use rattler_conda_types::{NamelessMatchSpec, Platform};
 use std::io::{Error, ErrorKind, Write};
 use std::path::Path;
 use std::{fs, path::PathBuf};
@@ -21,6 +23,11 @@ pub struct Args {
     /// Platforms that the project supports.
     #[arg(short, long = "platform", id = "platform")]
     pub platforms: Vec<String>,
+
+    /// Import the name, channels and dependencies of a conda environment file, e.g.
+    /// `environment.yml`.
+    #[arg(long, value_name = "ENV_FILE")]
+    pub import: Option<PathBuf>,
 }
 
 /// The default channels to use for a new project.
@@ -42,6 +49,16 @@ platforms = ["{{ platforms|join("\", \"") }}"]
 [tasks]
 
 [dependencies]
+{%- for dependency in dependencies %}
+{{ dependency }}
+{%- endfor %}
+{%- if pypi_dependencies %}
+
+[pypi-dependencies]
+{%- for dependency in pypi_dependencies %}
+{{ dependency }}
+{%- endfor %}
+{%- endif %}
 
 "#;
 
@@ -67,23 +84,42 @@ pub async fn execute(args: Args) -> miette::Result<()> {
         miette::bail!("{} already exists", consts::PROJECT_MANIFEST);
     }
 
+    // Read the environment file before anything is written, it might contain errors.
+    let imported = args
+        .import
+        .as_deref()
+        .map(CondaEnvironmentFile::from_path)
+        .transpose()?
+        .unwrap_or_default();
+
     // Fail silently if it already exists or cannot be created.
     fs::create_dir_all(&dir).ok();
 
     // Write pixi.toml
-    let name = dir
-        .file_name()
-        .ok_or_else(|| {
-            miette::miette!(
-                "Cannot get file or directory name from the path: {}",
-                dir.to_string_lossy()
-            )
-        })?
-        .to_string_lossy();
+    let name = match imported.name {
+        Some(name) => name,
+        None => dir
+            .file_name()
+            .ok_or_else(|| {
+                miette::miette!(
+                    "Cannot get file or directory name from the path: {}",
+                    dir.to_string_lossy()
+                )
+            })?
+            .to_string_lossy()
+            .into_owned(),
+    };
     let version = "0.1.0";
     let author = get_default_author();
+    let imported_channels = imported
+        .channels
+        .into_iter()
+        .filter(|channel| channel != "nodefaults")
+        .collect::<Vec<_>>();
     let channels = if let Some(channels) = args.channels {
         channels
+    } else if !imported_channels.is_empty() {
+        imported_channels
     } else {
         DEFAULT_CHANNELS
             .iter()
@@ -91,6 +127,28 @@ pub async fn execute(args: Args) -> miette::Result<()> {
             .map(ToOwned::to_owned)
             .collect()
     };
+    let dependencies = imported
+        .dependencies
+        .iter()
+        .map(|(name, spec)| {
+            format!(
+                "{} = {}",
+                toml_edit::Key::new(name.as_source()),
+                conda_spec_value(spec)
+            )
+        })
+        .collect::<Vec<_>>();
+    let pypi_dependencies = imported
+        .pypi_dependencies
+        .into_iter()
+        .map(|(name, requirement)| {
+            format!(
+                "{} = {}",
+                toml_edit::Key::new(name.as_source_str()),
+                toml_edit::Item::from(requirement)
+            )
+        })
+        .collect::<Vec<_>>();
 
     let platforms = if args.platforms.is_empty() {
         vec![Platform::current().to_string()]
@@ -107,7 +165,9 @@ pub async fn execute(args: Args) -> miette::Result<()> {
                 version,
                 author,
                 channels,
-                platforms
+                platforms,
+                dependencies,
+                pypi_dependencies
             },
         )
         .unwrap();
@@ -141,6 +201,27 @@ pub async fn execute(args: Args) -> miette::Result<()> {
     Ok(())
 }
 
+/// Returns the value of a conda dependency in the manifest. The spec is written as a string, unless
+/// it is pinned to a channel, which can only be expressed in a table.
+fn conda_spec_value(spec: &NamelessMatchSpec) -> toml_edit::Value {
+    let Some(channel) = &spec.channel else {
+        return spec.to_string().into();
+    };
+    let mut table = toml_edit::InlineTable::new();
+    table.insert(
+        "version",
+        spec.version
+            .as_ref()
+            .map_or_else(|| String::from("*"), ToString::to_string)
+            .into(),
+    );
+    if let Some(build) = &spec.build {
+        table.insert("build", build.to_string().into());
+    }
+    table.insert("channel", channel.name().into());
+    table.into()
+}
+
 // When the specific template is not in the file or the file does not exist.
 // Make the file and append the template to the file.
 fn create_or_append_file(path: &Path, template: &str) -> std::io::Result<()> {
@@ -1,4 +1,5 @@
 use crate::environment::get_up_to_date_prefix;
+use crate::project::manifest::ExcludeNewer;
 use crate::Project;
 use clap::Parser;
 use std::path::PathBuf;
@@ -12,10 +13,18 @@ pub struct Args {
 
     #[clap(flatten)]
     pub lock_file_usage: super::LockFileUsageArgs,
+
+    /// Don't select packages that were published after this date or timestamp, e.g. `2024-01-01`.
+    /// Overrides `exclude-newer` of the manifest.
+    #[arg(long)]
+    pub exclude_newer: Option<ExcludeNewer>,
 }
 
 pub async fn execute(args: Args) -> miette::Result<()> {
-    let project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+    let mut project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+    if let Some(exclude_newer) = args.exclude_newer {
+        project.set_exclude_newer(exclude_newer);
+    }
 
     get_up_to_date_prefix(
         &project,
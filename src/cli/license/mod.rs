@@ -0,0 +1,21 @@
+use clap::Parser;
+
+pub mod report;
+
+#[derive(Debug, Parser)]
+pub enum Command {
+    Report(report::Args),
+}
+
+/// Inspect the licenses of the packages of the project.
+#[derive(Debug, Parser)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    match args.command {
+        Command::Report(args) => report::execute(args).await,
+    }
+}
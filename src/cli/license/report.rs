@@ -0,0 +1,222 @@
+use crate::{
+    lock_file::{load_lock_file, locked_packages},
+    project::manifest::{LicensePolicy, LicenseStatus},
+    Project,
+};
+use clap::Parser;
+use comfy_table::{presets::NOTHING, Attribute, Cell, Color, ContentArrangement, Table};
+use itertools::Itertools;
+use miette::IntoDiagnostic;
+use rattler_conda_types::{Platform, RepoDataRecord};
+use rattler_lock::{PypiPackageData, PypiPackageEnvironmentData};
+use serde::Serialize;
+use serde_with::{serde_as, DisplayFromStr};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+/// The license under which pypi packages are reported, their license is not part of the
+/// lock-file.
+const PYPI_LICENSE: &str = "not recorded (pypi)";
+
+/// How conda packages without a license are reported.
+const NO_LICENSE: &str = "not recorded";
+
+/// Show the licenses of the locked packages of an environment and whether they are allowed by the
+/// license policy of the project.
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// The environment to report on, defaults to the default environment.
+    #[arg(long, short)]
+    pub environment: Option<String>,
+
+    /// Only report on the packages of this platform, defaults to all platforms of the environment.
+    #[arg(long, short)]
+    pub platform: Option<Platform>,
+
+    /// Output the report as JSON.
+    #[arg(long)]
+    pub json: bool,
+
+    /// The path to 'pixi.toml'
+    #[arg(long)]
+    pub manifest_path: Option<PathBuf>,
+}
+
+/// The packages that share a license, `None` for packages without a license.
+#[serde_as]
+#[derive(Debug, Serialize, PartialEq, Eq)]
+struct LicenseGroup {
+    license: Option<String>,
+    #[serde_as(as = "DisplayFromStr")]
+    status: LicenseStatus,
+    packages: BTreeSet<String>,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+    let environment = project.environment_from_name_or_default(args.environment.as_deref())?;
+    let environment_name = environment.name();
+
+    let lock_file = load_lock_file(&project).await?;
+    let (conda_packages, pypi_packages) = locked_packages(&lock_file, &environment)?;
+    let platforms = match args.platform {
+        Some(platform) => vec![platform],
+        None => environment.platforms().into_iter().sorted().collect(),
+    };
+    let empty_conda = Vec::new();
+    let empty_pypi = Vec::new();
+    let groups = license_groups(
+        project.license_policy(),
+        platforms
+            .iter()
+            .flat_map(|platform| conda_packages.get(platform).unwrap_or(&empty_conda).iter()),
+        platforms
+            .iter()
+            .flat_map(|platform| pypi_packages.get(platform).unwrap_or(&empty_pypi).iter()),
+    );
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&groups).into_diagnostic()?
+        );
+        return Ok(());
+    }
+
+    if groups.is_empty() {
+        eprintln!(
+            "{}No packages are locked for the environment `{environment_name}`, run `pixi lock` to update the lock-file",
+            console::style(console::Emoji("✘ ", "")).red(),
+        );
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(NOTHING)
+        .set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        Cell::new("License").add_attribute(Attribute::Bold),
+        Cell::new("Status").add_attribute(Attribute::Bold),
+        Cell::new("Count").add_attribute(Attribute::Bold),
+        Cell::new("Packages").add_attribute(Attribute::Bold),
+    ]);
+    for group in &groups {
+        let color = match group.status {
+            LicenseStatus::Allowed => Color::Green,
+            LicenseStatus::Unknown => Color::Yellow,
+            LicenseStatus::Denied { .. } | LicenseStatus::NotAllowed => Color::Red,
+        };
+        table.add_row(vec![
+            Cell::new(group.license.as_deref().unwrap_or(NO_LICENSE)),
+            Cell::new(&group.status).fg(color),
+            Cell::new(group.packages.len()),
+            Cell::new(group.packages.iter().join(", ")),
+        ]);
+    }
+    println!("{table}");
+
+    let violations = groups
+        .iter()
+        .filter(|group| group.status.is_violation())
+        .map(|group| group.packages.len())
+        .sum::<usize>();
+    if violations > 0 {
+        eprintln!(
+            "\n{}{violations} package(s) have a license that violates the license policy, run `pixi why <PACKAGE>` to see why they are required",
+            console::style(console::Emoji("✘ ", "")).red(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Groups the packages by their license, sorted by license. The packages without a license and the
+/// pypi packages come last.
+fn license_groups<'a>(
+    policy: &LicensePolicy,
+    conda_packages: impl IntoIterator<Item = &'a RepoDataRecord>,
+    pypi_packages: impl IntoIterator<Item = &'a (PypiPackageData, PypiPackageEnvironmentData)>,
+) -> Vec<LicenseGroup> {
+    let mut groups: BTreeMap<Option<String>, LicenseGroup> = BTreeMap::new();
+    for record in conda_packages {
+        let package = &record.package_record;
+        groups
+            .entry(package.license.clone())
+            .or_insert_with(|| LicenseGroup {
+                license: package.license.clone(),
+                status: policy.status(package.license.as_deref()),
+                packages: BTreeSet::new(),
+            })
+            .packages
+            .insert(package.name.as_normalized().to_string());
+    }
+    let without_license = groups.remove(&None);
+
+    let mut pypi_group: Option<LicenseGroup> = None;
+    for (data, _) in pypi_packages {
+        pypi_group
+            .get_or_insert_with(|| LicenseGroup {
+                license: Some(String::from(PYPI_LICENSE)),
+                status: LicenseStatus::Unknown,
+                packages: BTreeSet::new(),
+            })
+            .packages
+            .insert(data.name.clone());
+    }
+
+    groups
+        .into_values()
+        .chain(without_license)
+        .chain(pypi_group)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lock_file::fixtures;
+
+    fn record(name: &str, license: Option<&str>) -> RepoDataRecord {
+        let mut record = fixtures::record(name, "1.0", &[]);
+        record.package_record.license = license.map(ToString::to_string);
+        record
+    }
+
+    #[test]
+    fn test_license_groups() {
+        let policy = LicensePolicy {
+            allow: Vec::new(),
+            deny: vec![String::from("GPL-3.0-only")],
+        };
+        let records = [
+            record("numpy", Some("BSD-3-Clause")),
+            record("pandas", Some("BSD-3-Clause")),
+            record("readline", Some("GPL-3.0-only")),
+            record("blas", None),
+            record("mkl", Some("unknown")),
+        ];
+        let groups = license_groups(&policy, &records, []);
+
+        assert_eq!(
+            groups
+                .iter()
+                .map(|group| format!(
+                    "{}: {} [{}]",
+                    group.license.as_deref().unwrap_or(NO_LICENSE),
+                    group.status,
+                    group.packages.iter().join(", ")
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                "BSD-3-Clause: allowed [numpy, pandas]",
+                "GPL-3.0-only: denied (GPL-3.0-only) [readline]",
+                "unknown: unknown [mkl]",
+                "not recorded: unknown [blas]",
+            ]
+        );
+        assert!(groups[1].status.is_violation());
+    }
+}
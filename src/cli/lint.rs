@@ -0,0 +1,85 @@
+use crate::{project::manifest::LintContext, Project};
+use clap::Parser;
+use std::path::{Path, PathBuf};
+
+/// Check the project manifest for likely mistakes, like unused features or unpinned dependencies.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The path to 'pixi.toml'
+    #[arg(long)]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Exit with a non-zero exit code if any problems are found.
+    #[arg(long)]
+    pub check: bool,
+}
+
+pub fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+
+    // Tasks shadow the executables that are installed in the environments of the project.
+    let bin_dirs = project
+        .environments()
+        .iter()
+        .flat_map(|environment| environment_bin_dirs(&environment.dir()))
+        .collect::<Vec<_>>();
+    let is_executable = |name: &str| {
+        bin_dirs.iter().any(|dir| {
+            let path = dir.join(name);
+            is_executable::is_executable(&path)
+                || (cfg!(windows) && is_executable::is_executable(path.with_extension("exe")))
+        })
+    };
+
+    // Workspace members are only used through the environments of the workspace, so every
+    // manifest is linted in the context of those environments.
+    let environments = project.environments();
+    let context = LintContext {
+        used_features: environments
+            .iter()
+            .flat_map(|environment| environment.manifest().features.iter().cloned())
+            .collect(),
+        platforms: environments
+            .iter()
+            .flat_map(|environment| environment.platforms())
+            .collect(),
+    };
+
+    let manifests = std::iter::once(&project.manifest).chain(project.workspace_members());
+    let mut problems = 0;
+    for manifest in manifests {
+        let lints = manifest.lint_with_context(&context, is_executable);
+        for lint in lints.iter() {
+            eprintln!(
+                "{}{}: {lint}",
+                console::style("warning").yellow().bold(),
+                console::style(format!(" ({})", manifest.path.display())).dim(),
+            );
+        }
+        problems += lints.len();
+    }
+
+    if problems == 0 {
+        eprintln!(
+            "{}No problems found",
+            console::style(console::Emoji("✔ ", "")).green()
+        );
+    } else if args.check {
+        miette::bail!("found {problems} problem(s) in the project manifest");
+    }
+
+    Ok(())
+}
+
+/// Returns the directories of an environment prefix that contain executables.
+fn environment_bin_dirs(prefix: &Path) -> Vec<PathBuf> {
+    if cfg!(windows) {
+        vec![
+            prefix.to_path_buf(),
+            prefix.join("Library/bin"),
+            prefix.join("Scripts"),
+        ]
+    } else {
+        vec![prefix.join("bin")]
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{
+    consts,
+    lock_file::diff::{lock_file_changes, LockedVersion, PackageChange, PlatformChanges},
+    Project,
+};
+use clap::{Parser, ValueEnum};
+use comfy_table::{presets::NOTHING, Attribute, Cell, Color, ContentArrangement, Table};
+use miette::{IntoDiagnostic, WrapErr};
+use rattler_lock::LockFile;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+
+/// The formats the changes can be printed in.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffFormat {
+    /// A table per environment and platform.
+    Table,
+
+    /// Markdown tables, for example for the description of a pull request.
+    Markdown,
+
+    /// A JSON array with the changes per environment and platform.
+    Json,
+}
+
+/// Compare two lock-files and show which packages were added, removed, upgraded or downgraded.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The old lock-file, either a path or a git revision like `main` or `HEAD~1`.
+    pub old: String,
+
+    /// The new lock-file, either a path or a git revision. Defaults to the lock-file of the
+    /// project.
+    pub new: Option<String>,
+
+    /// The format of the output.
+    #[arg(long, value_enum, default_value = "table")]
+    pub format: DiffFormat,
+
+    /// The path to 'pixi.toml'
+    #[arg(long)]
+    pub manifest_path: Option<PathBuf>,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+
+    let old = load(&project, &args.old)?;
+    let new = match &args.new {
+        Some(source) => load(&project, source)?,
+        None => LockFile::from_path(&project.lock_file_path())
+            .into_diagnostic()
+            .wrap_err("failed to load the lock-file of the project")?,
+    };
+
+    let changes = lock_file_changes(&old, &new)?;
+    match args.format {
+        DiffFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&changes).into_diagnostic()?
+        ),
+        DiffFormat::Markdown => print!("{}", markdown(&changes)),
+        DiffFormat::Table if changes.is_empty() => eprintln!(
+            "{}The lock-files are the same",
+            console::style(console::Emoji("✔ ", "")).green(),
+        ),
+        DiffFormat::Table => print_tables(&changes),
+    }
+
+    Ok(())
+}
+
+/// Loads a lock-file from a path, or from a git revision of the repository of the project.
+fn load(project: &Project, source: &str) -> miette::Result<LockFile> {
+    let path = Path::new(source);
+    if path.is_file() {
+        return LockFile::from_path(path)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("failed to load the lock-file {}", path.display()));
+    }
+
+    let output = Command::new("git")
+        .arg("show")
+        .arg(format!("{source}:./{}", consts::PROJECT_LOCK_FILE))
+        .current_dir(project.root())
+        .output()
+        .into_diagnostic()
+        .wrap_err("failed to run git")?;
+    if !output.status.success() {
+        miette::bail!(
+            "`{source}` is neither a lock-file nor a git revision that contains one: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let contents = String::from_utf8(output.stdout).into_diagnostic()?;
+    LockFile::from_str(&contents)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("failed to parse the lock-file of revision `{source}`"))
+}
+
+/// Returns the old version, old build, new version and new build of a change.
+fn version_cells(change: &PackageChange) -> [String; 4] {
+    let version = |locked: &Option<LockedVersion>| {
+        locked
+            .as_ref()
+            .map(|locked| locked.version.clone())
+            .unwrap_or_default()
+    };
+    let build = |locked: &Option<LockedVersion>| {
+        locked
+            .as_ref()
+            .and_then(|locked| locked.build.clone())
+            .unwrap_or_default()
+    };
+    [
+        version(&change.before),
+        build(&change.before),
+        version(&change.after),
+        build(&change.after),
+    ]
+}
+
+fn print_tables(changes: &[PlatformChanges]) {
+    for platform_changes in changes {
+        println!(
+            "{}",
+            console::style(format!(
+                "{} ({})",
+                platform_changes.environment, platform_changes.platform
+            ))
+            .bold()
+        );
+
+        let mut table = Table::new();
+        table
+            .load_preset(NOTHING)
+            .set_content_arrangement(ContentArrangement::Dynamic);
+        table.set_header(vec![
+            Cell::new("Package").add_attribute(Attribute::Bold),
+            Cell::new("Kind").add_attribute(Attribute::Bold),
+            Cell::new("Change").add_attribute(Attribute::Bold),
+            Cell::new("Old version").add_attribute(Attribute::Bold),
+            Cell::new("Old build").add_attribute(Attribute::Bold),
+            Cell::new("New version").add_attribute(Attribute::Bold),
+            Cell::new("New build").add_attribute(Attribute::Bold),
+        ]);
+
+        for change in &platform_changes.changes {
+            let color = match change.after {
+                None => Color::Red,
+                Some(_) if change.before.is_none() => Color::Green,
+                Some(_) => Color::Yellow,
+            };
+            let [old_version, old_build, new_version, new_build] = version_cells(change);
+            table.add_row(vec![
+                Cell::new(&change.name).fg(color),
+                Cell::new(change.kind),
+                Cell::new(change.change).fg(color),
+                Cell::new(old_version),
+                Cell::new(old_build),
+                Cell::new(new_version),
+                Cell::new(new_build),
+            ]);
+        }
+
+        println!("{table}\n");
+    }
+}
+
+/// Formats the changes as Markdown tables, one per environment and platform.
+fn markdown(changes: &[PlatformChanges]) -> String {
+    if changes.is_empty() {
+        return String::from("The lock-files are the same.\n");
+    }
+
+    let mut result = String::new();
+    for platform_changes in changes {
+        result.push_str(&format!(
+            "### `{}` ({})\n\n",
+            platform_changes.environment, platform_changes.platform
+        ));
+        result.push_str("| Package | Kind | Change | Old version | New version |\n");
+        result.push_str("|---|---|---|---|---|\n");
+        for change in &platform_changes.changes {
+            let version = |version: &Option<LockedVersion>| {
+                version
+                    .as_ref()
+                    .map(|version| format!("`{version}`"))
+                    .unwrap_or_default()
+            };
+            result.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                change.name,
+                change.kind,
+                change.change,
+                version(&change.before),
+                version(&change.after),
+            ));
+        }
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lock_file::diff::{ChangeKind, PackageKind};
+    use rattler_conda_types::Platform;
+
+    #[test]
+    fn test_markdown() {
+        let changes = vec![PlatformChanges {
+            environment: String::from("default"),
+            platform: Platform::Linux64,
+            changes: vec![
+                PackageChange {
+                    name: String::from("numpy"),
+                    kind: PackageKind::Conda,
+                    change: ChangeKind::Upgraded,
+                    before: Some(LockedVersion {
+                        version: String::from("1.26.4"),
+                        build: Some(String::from("py312_0")),
+                    }),
+                    after: Some(LockedVersion {
+                        version: String::from("2.0.0"),
+                        build: Some(String::from("py312_0")),
+                    }),
+                },
+                PackageChange {
+                    name: String::from("requests"),
+                    kind: PackageKind::Pypi,
+                    change: ChangeKind::Added,
+                    before: None,
+                    after: Some(LockedVersion {
+                        version: String::from("2.31.0"),
+                        build: None,
+                    }),
+                },
+            ],
+        }];
+
+        assert_eq!(
+            markdown(&changes),
+            "### `default` (linux-64)\n\n\
+             | Package | Kind | Change | Old version | New version |\n\
+             |---|---|---|---|---|\n\
+             | numpy | conda | upgraded | `1.26.4 py312_0` | `2.0.0 py312_0` |\n\
+             | requests | pypi | added |  | `2.31.0` |\n\n"
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use clap::Parser;
+use miette::{IntoDiagnostic, WrapErr};
+use rattler_lock::LockFile;
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Merge two versions of the lock-file, meant to be used as a git merge driver.
+///
+/// Both versions are written to CURRENT as a single conflict and the merge is reported as
+/// conflicted, so git never records a lock-file that nobody solved. The next `pixi install` or
+/// `pixi lock` uses the packages of both versions as the preferred versions to solve every
+/// environment of the merged manifest again. To use it, add `pixi.lock merge=pixi` to
+/// `.gitattributes` and run `git config merge.pixi.driver "pixi lock merge %O %A %B"`.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The lock-file of the common ancestor (`%O`), not used.
+    pub ancestor: PathBuf,
+
+    /// Our version of the lock-file (`%A`), the conflicted lock-file is written here.
+    pub current: PathBuf,
+
+    /// Their version of the lock-file (`%B`).
+    pub other: PathBuf,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let ours = read(&args.current)?;
+    let theirs = read(&args.other)?;
+    if ours == theirs {
+        return Ok(());
+    }
+
+    // Unlike the line based conflicts of git, a conflict of the whole file keeps both sides
+    // complete lock-files.
+    std::fs::write(
+        &args.current,
+        format!("<<<<<<< ours\n{ours}=======\n{theirs}>>>>>>> theirs\n"),
+    )
+    .into_diagnostic()
+    .wrap_err("failed to write the conflicted lock-file")?;
+
+    // Both versions must be valid lock-files, otherwise the conflict can not be resolved by
+    // solving again.
+    for (contents, side) in [(&ours, "our"), (&theirs, "their")] {
+        LockFile::from_str(contents)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("failed to parse {side} version of the lock-file"))?;
+    }
+    miette::bail!(
+        "both versions of the lock-file changed, run `pixi lock` or `pixi install` after merging the manifest to solve the environments again"
+    )
+}
+
+/// Reads a version of the lock-file, making sure it ends with a newline.
+fn read(path: &Path) -> miette::Result<String> {
+    let mut contents = std::fs::read_to_string(path)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+    if !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    Ok(contents)
+}
@@ -0,0 +1,167 @@
+use crate::{
+    lock_file::{self, load_lock_file_with_conflicts, verify_environment_satisfiability},
+    project::manifest::ExcludeNewer,
+    utils::error::format_error_chain,
+    Project,
+};
+use clap::Parser;
+use itertools::Itertools;
+use std::path::PathBuf;
+
+pub mod diff;
+pub mod merge;
+
+#[derive(Debug, Parser)]
+pub enum Command {
+    Diff(diff::Args),
+    Merge(merge::Args),
+}
+
+/// Solve the environments of the project and update the lock-file, without installing them.
+#[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Only check if the lock-file is up-to-date. Exits with a non-zero exit code and reports the
+    /// changes if the lock-file would change.
+    #[arg(long)]
+    pub check: bool,
+
+    /// Don't select packages that were published after this date or timestamp, e.g. `2024-01-01`.
+    /// Overrides `exclude-newer` of the manifest.
+    #[arg(long)]
+    pub exclude_newer: Option<ExcludeNewer>,
+
+    /// The path to 'pixi.toml'
+    #[arg(long)]
+    pub manifest_path: Option<PathBuf>,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    match args.command {
+        Some(Command::Diff(args)) => diff::execute(args).await,
+        Some(Command::Merge(args)) => merge::execute(args).await,
+        None => lock(args).await,
+    }
+}
+
+/// Updates the lock-file, or only checks if it is up-to-date.
+async fn lock(args: Args) -> miette::Result<()> {
+    let mut project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+    if let Some(exclude_newer) = args.exclude_newer {
+        project.set_exclude_newer(exclude_newer);
+    }
+    let (lock_file, has_conflicts) = load_lock_file_with_conflicts(&project).await?;
+    if has_conflicts {
+        if args.check {
+            miette::bail!(
+                "the lock-file contains merge conflicts, run `pixi lock` to resolve them"
+            );
+        }
+        tracing::info!("the lock-file contains merge conflicts, solving all environments again");
+    }
+
+    let environments = project.environments();
+    let mut updated_environments = Vec::new();
+    for environment in &environments {
+        let name = environment.name();
+        // The packages of a conflicted lock-file are the union of both sides of the conflicts,
+        // which has to be solved again even if it happens to satisfy the project.
+        let satisfiability = if has_conflicts {
+            None
+        } else {
+            Some(verify_environment_satisfiability(
+                environment,
+                lock_file.environment(name.as_str()),
+            ))
+        };
+        match satisfiability {
+            None => {}
+            Some(Ok(())) => {
+                tracing::debug!("the lock-file is up to date for environment `{name}`");
+                let (locked_conda_packages, locked_pypi_packages) =
+                    lock_file::locked_packages(&lock_file, environment)?;
+                lock_file::check_license_policy(environment, &locked_conda_packages)?;
+                lock_file::warn_unchecked_pypi_licenses(environment, &locked_pypi_packages);
+                continue;
+            }
+            Some(Err(err)) => {
+                tracing::info!(
+                    "environment `{name}` is not up to date\nbecause {}",
+                    format_error_chain(&err)
+                );
+            }
+        }
+
+        // Solve the environment, preferring the versions of the packages that are locked.
+        let (locked_conda_packages, locked_pypi_packages) =
+            lock_file::locked_packages(&lock_file, environment)?;
+        let conda_packages = lock_file::update_lock_file_conda(
+            environment,
+            environment.platforms(),
+            &locked_conda_packages,
+            None,
+        )
+        .await?;
+        let pypi_packages = if environment.has_pypi_dependencies() {
+            lock_file::update_lock_file_for_pypi(
+                environment,
+                environment.platforms(),
+                &conda_packages,
+                &locked_pypi_packages,
+                None,
+                Default::default(),
+            )
+            .await?
+        } else {
+            Default::default()
+        };
+
+        // Report what changed.
+        let changes = lock_file::diff::environment_changes(
+            environment.platforms().into_iter().sorted(),
+            &locked_conda_packages,
+            &conda_packages,
+            &locked_pypi_packages,
+            &pypi_packages,
+        );
+        eprintln!("{}", console::style(format!("environment `{name}`")).bold());
+        if changes.is_empty() {
+            eprintln!("  the locked packages are unchanged, only the metadata is updated");
+        }
+        for (platform, changes) in changes {
+            eprintln!("  {}", console::style(platform).bold());
+            for change in changes {
+                eprintln!("    {change}");
+            }
+        }
+
+        lock_file::check_license_policy(environment, &conda_packages)?;
+        lock_file::warn_unchecked_pypi_licenses(environment, &pypi_packages);
+        updated_environments.push((environment, conda_packages, pypi_packages));
+    }
+
+    if updated_environments.is_empty() {
+        eprintln!(
+            "{}The lock-file is up-to-date",
+            console::style(console::Emoji("✔ ", "")).green(),
+        );
+        return Ok(());
+    }
+
+    if args.check {
+        miette::bail!(
+            "the lock-file is not up-to-date with the project, run `pixi lock` to update it"
+        );
+    }
+
+    lock_file::write_lock_file(&project, &lock_file, updated_environments)?;
+    eprintln!(
+        "{}Updated the lock-file",
+        console::style(console::Emoji("✔ ", "")).green(),
+    );
+
+    Ok(())
+}
@@ -10,20 +10,30 @@ use tracing_subscriber::{filter::LevelFilter, util::SubscriberInitExt, EnvFilter
 pub mod add;
 pub mod auth;
 pub mod completion;
+pub mod fmt;
 pub mod global;
 pub mod info;
 pub mod init;
 pub mod install;
+pub mod license;
+pub mod lint;
 pub mod list;
+pub mod lock;
 pub mod project;
 pub mod remove;
 pub mod run;
+pub mod sbom;
+pub mod schema;
 pub mod search;
 pub mod self_update;
 pub mod shell;
 pub mod shell_hook;
 pub mod task;
+pub mod tree;
+pub mod update;
 pub mod upload;
+pub mod verify;
+pub mod why;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -76,6 +86,16 @@ pub enum Command {
     Remove(remove::Args),
     SelfUpdate(self_update::Args),
     List(list::Args),
+    Schema(schema::Args),
+    Lint(lint::Args),
+    Fmt(fmt::Args),
+    Update(update::Args),
+    Lock(lock::Args),
+    Tree(tree::Args),
+    Why(why::Args),
+    Verify(verify::Args),
+    Sbom(sbom::Args),
+    License(license::Args),
 }
 
 #[derive(Parser, Debug, Default)]
@@ -192,6 +212,16 @@ pub async fn execute_command(command: Command) -> miette::Result<()> {
         Command::Remove(cmd) => remove::execute(cmd).await,
         Command::SelfUpdate(cmd) => self_update::execute(cmd).await,
         Command::List(cmd) => list::execute(cmd).await,
+        Command::Schema(cmd) => schema::execute(cmd),
+        Command::Lint(cmd) => lint::execute(cmd),
+        Command::Fmt(cmd) => fmt::execute(cmd),
+        Command::Update(cmd) => update::execute(cmd).await,
+        Command::Lock(cmd) => lock::execute(cmd).await,
+        Command::Tree(cmd) => tree::execute(cmd).await,
+        Command::Why(cmd) => why::execute(cmd).await,
+        Command::Verify(cmd) => verify::execute(cmd).await,
+        Command::Sbom(cmd) => sbom::execute(cmd).await,
+        Command::License(cmd) => license::execute(cmd).await,
     }
 }
 
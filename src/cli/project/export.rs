@@ -0,0 +1,294 @@
+use crate::{
+    lock_file::load_lock_file,
+    project::{manifest::EnvironmentName, Environment},
+    utils::conda_environment_file::CondaEnvironmentFile,
+    Project,
+};
+use clap::{Parser, ValueEnum};
+use itertools::Itertools;
+use miette::{IntoDiagnostic, WrapErr};
+use rattler_conda_types::{Channel, ChannelConfig, Platform, RepoDataRecord};
+use rattler_lock::{PypiPackageData, PypiPackageEnvironmentData};
+use std::path::PathBuf;
+
+/// The formats an environment can be exported to.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A conda `environment.yml` with the dependencies from the manifest.
+    EnvironmentYml,
+
+    /// A conda explicit spec file with the urls of the locked conda packages.
+    CondaExplicit,
+
+    /// A pip `requirements.txt` with the locked pypi packages and their hashes.
+    Requirements,
+}
+
+/// Export an environment of the project to a format that is understood by conda or pip.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The format to export to.
+    #[arg(value_enum)]
+    pub format: ExportFormat,
+
+    /// The environment to export, defaults to the default environment.
+    #[arg(long, short)]
+    pub environment: Option<String>,
+
+    /// The platform to export, defaults to the current platform.
+    #[arg(long, short)]
+    pub platform: Option<Platform>,
+
+    /// Write to this file instead of printing to the standard output.
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+
+    /// The path to 'pixi.toml'
+    #[arg(long)]
+    pub manifest_path: Option<PathBuf>,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+    let platform = args.platform.unwrap_or_else(Platform::current);
+    let environment = project.environment_from_name_or_default(args.environment.as_deref())?;
+    let environment_name = environment.name();
+    if !environment.platforms().contains(&platform) {
+        miette::bail!("the environment `{environment_name}` does not support `{platform}`");
+    }
+
+    let contents = match args.format {
+        ExportFormat::EnvironmentYml => environment_yml(&project, &environment, platform)?,
+        ExportFormat::CondaExplicit | ExportFormat::Requirements => {
+            let lock_file = load_lock_file(&project).await?;
+            let Some(locked_environment) = lock_file.environment(environment_name.as_str()) else {
+                miette::bail!(
+                    "the environment `{environment_name}` is not part of the lock-file, run `pixi install` to update the lock-file"
+                );
+            };
+            if locked_environment.packages(platform).is_none() {
+                miette::bail!(
+                    "the environment `{environment_name}` is not locked for `{platform}`, run `pixi install` to update the lock-file"
+                );
+            }
+
+            if args.format == ExportFormat::CondaExplicit {
+                let records = locked_environment
+                    .conda_repodata_records()
+                    .into_diagnostic()
+                    .wrap_err("failed to parse the contents of the lock-file")?
+                    .remove(&platform)
+                    .unwrap_or_default();
+                conda_explicit(platform, &records)
+            } else {
+                let packages = locked_environment
+                    .pypi_packages()
+                    .remove(&platform)
+                    .unwrap_or_default();
+                requirements(&packages)
+            }
+        }
+    };
+
+    match args.output {
+        Some(path) => std::fs::write(&path, contents)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("failed to write {}", path.display()))?,
+        None => print!("{contents}"),
+    }
+
+    Ok(())
+}
+
+/// Returns an `environment.yml` with the dependencies of the environment as they are written in
+/// the manifest.
+fn environment_yml(
+    project: &Project,
+    environment: &Environment<'_>,
+    platform: Platform,
+) -> miette::Result<String> {
+    let name = match environment.name() {
+        EnvironmentName::Default => project.name().to_string(),
+        EnvironmentName::Named(name) => format!("{}-{name}", project.name()),
+    };
+
+    CondaEnvironmentFile {
+        name: Some(name),
+        channels: environment
+            .channels()
+            .into_iter()
+            .map(environment_yml_channel)
+            .collect(),
+        dependencies: environment
+            .dependencies(None, Some(platform))
+            .into_specs()
+            .collect(),
+        pypi_dependencies: environment
+            .pypi_dependencies(Some(platform))
+            .into_iter()
+            .flat_map(|(name, requirements)| {
+                requirements
+                    .into_iter()
+                    .map(move |requirement| (name.clone(), requirement))
+            })
+            .collect(),
+    }
+    .to_yaml_string()
+}
+
+/// Returns how `channel` is written in an `environment.yml`. Channels on the default channel alias
+/// are referred to by their name, all other channels by their url.
+fn environment_yml_channel(channel: &Channel) -> String {
+    let channel_alias = ChannelConfig::default().channel_alias;
+    match &channel.name {
+        Some(name)
+            if channel
+                .base_url()
+                .as_str()
+                .starts_with(channel_alias.as_str()) =>
+        {
+            name.clone()
+        }
+        _ => channel.base_url().to_string(),
+    }
+}
+
+/// Returns a conda explicit spec file that lists the urls of the `records`, followed by their md5
+/// or sha256 hash.
+fn conda_explicit(platform: Platform, records: &[RepoDataRecord]) -> String {
+    let mut contents = format!(
+        "# This file may be used to create an environment using:\n\
+         # $ conda create --name <env> --file <this file>\n\
+         # platform: {platform}\n\
+         @EXPLICIT\n"
+    );
+    for record in records {
+        let hash = if let Some(md5) = &record.package_record.md5 {
+            format!("#{md5:x}")
+        } else if let Some(sha256) = &record.package_record.sha256 {
+            format!("#sha256:{sha256:x}")
+        } else {
+            String::new()
+        };
+        contents.push_str(&format!("{}{hash}\n", record.url));
+    }
+    contents
+}
+
+/// Returns a `requirements.txt` that pins the locked pypi `packages` to their exact version and
+/// hash.
+fn requirements(packages: &[(PypiPackageData, PypiPackageEnvironmentData)]) -> String {
+    packages
+        .iter()
+        .map(|(package, _)| {
+            let requirement = format!("{}=={}", package.name, package.version);
+            match package.hash.as_ref().and_then(|hash| hash.sha256()) {
+                Some(sha256) => format!("{requirement} \\\n    --hash=sha256:{sha256:x}\n"),
+                None => format!("{requirement}\n"),
+            }
+        })
+        .join("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lock_file::fixtures::record, project::manifest::Manifest};
+    use rattler_lock::PackageHashes;
+    use std::{path::Path, str::FromStr};
+    use url::Url;
+
+    #[test]
+    fn test_environment_yml() {
+        let manifest = Manifest::from_str(
+            Path::new(""),
+            r#"
+        [project]
+        name = "foo"
+        channels = ["conda-forge", "https://prefix.dev/internal"]
+        platforms = ["linux-64", "win-64"]
+
+        [dependencies]
+        python = ">=3.11"
+
+        [target.linux-64.dependencies]
+        gcc = "*"
+
+        [pypi-dependencies]
+        requests = ">=2.31"
+
+        [feature.test.dependencies]
+        pytest = "*"
+
+        [environments]
+        test = ["test"]
+        "#,
+        )
+        .unwrap();
+        let project = Project::from_manifest(manifest);
+
+        let environment = project.environment("test").unwrap();
+        let yaml = environment_yml(&project, &environment, Platform::Win64).unwrap();
+        let exported = CondaEnvironmentFile::from_str("environment.yml", &yaml).unwrap();
+        assert_eq!(exported.name.as_deref(), Some("foo-test"));
+        assert_eq!(
+            exported.channels,
+            vec!["conda-forge", "https://prefix.dev/internal/"]
+        );
+        assert_eq!(
+            exported
+                .dependencies
+                .iter()
+                .map(|(name, _)| name.as_source())
+                .sorted()
+                .collect::<Vec<_>>(),
+            vec!["pip", "pytest", "python"]
+        );
+        assert_eq!(exported.pypi_dependencies.len(), 1);
+    }
+
+    #[test]
+    fn test_conda_explicit() {
+        let mut record = record("python", "3.11.7", &[]);
+        record.package_record.md5 = rattler_digest::parse_digest_from_hex::<rattler_digest::Md5>(
+            "8e8e8b8e2b6a0c4ef8e6b5b4e4b0d1a2",
+        );
+        let url = record.url.clone();
+
+        assert_eq!(
+            conda_explicit(Platform::Linux64, &[record]),
+            format!(
+                "# This file may be used to create an environment using:\n\
+                 # $ conda create --name <env> --file <this file>\n\
+                 # platform: linux-64\n\
+                 @EXPLICIT\n\
+                 {url}#8e8e8b8e2b6a0c4ef8e6b5b4e4b0d1a2\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_requirements() {
+        let sha256 = rattler_digest::parse_digest_from_hex::<rattler_digest::Sha256>(
+            "58cd2187c01e70e6e26505bca751777aa9f2ee0b7f4300988b709f44e013003f",
+        );
+        let package = PypiPackageData {
+            name: String::from("requests"),
+            version: pep440_rs::Version::from_str("2.31.0").unwrap(),
+            requires_dist: Vec::new(),
+            requires_python: None,
+            url: Url::parse(
+                "https://files.pythonhosted.org/packages/requests-2.31.0-py3-none-any.whl",
+            )
+            .unwrap(),
+            hash: PackageHashes::from_hashes(None, sha256),
+        };
+
+        assert_eq!(
+            requirements(&[(package, PypiPackageEnvironmentData {
+                    extras: Default::default(),
+                })]),
+            "requests==2.31.0 \\\n    --hash=sha256:58cd2187c01e70e6e26505bca751777aa9f2ee0b7f4300988b709f44e013003f\n"
+        );
+    }
+}
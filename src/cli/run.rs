@@ -1,36 +1,45 @@
+use std::io::IsTerminal;
 use std::{collections::HashMap, path::PathBuf, string::String};
 
-use clap::Parser;
+use clap::{CommandFactory, Parser};
+use console::{style, Key, Term};
+use indexmap::IndexMap;
 use itertools::Itertools;
 use miette::{miette, Context, Diagnostic, IntoDiagnostic};
 use rattler_conda_types::Platform;
 
 use crate::environment::LockFileUsage;
 use crate::project::errors::UnsupportedPlatformError;
-use crate::task::{
-    ExecutableTask, FailedToParseShellScript, InvalidWorkingDirectory, TraversalError,
-};
+use crate::project::metadata::expand_activation_env;
+use crate::task::{ExecutableTask, TraversalError};
 use crate::{
-    environment::get_up_to_date_prefix, prefix::Prefix, progress::await_in_progress, Project,
+    dotenv, environment::get_up_to_date_prefix, prefix::Prefix, progress::await_in_progress,
+    Project,
 };
 use rattler_shell::{
     activation::{ActivationVariables, Activator, PathModificationBehavior},
     shell::ShellEnum,
 };
+use strsim::jaro;
 use thiserror::Error;
 use tracing::Level;
 
 /// Runs task in project.
 #[derive(Parser, Debug, Default)]
-#[clap(trailing_var_arg = true, arg_required_else_help = true)]
+#[clap(trailing_var_arg = true)]
 pub struct Args {
-    /// The task you want to run in the projects environment.
+    /// The task you want to run in the projects environment. When omitted in an interactive
+    /// terminal, a list of the available tasks is shown to pick from.
     pub task: Vec<String>,
 
     /// The path to 'pixi.toml'
     #[arg(long)]
     pub manifest_path: Option<PathBuf>,
 
+    /// Load environment variables from this dotenv file, can be specified multiple times.
+    #[arg(long = "env-file", value_name = "PATH")]
+    pub env_file: Vec<PathBuf>,
+
     #[clap(flatten)]
     pub lock_file_usage: super::LockFileUsageArgs,
 }
@@ -44,7 +53,22 @@ pub async fn execute(args: Args) -> miette::Result<()> {
     // `"test 1 == 0 || echo failed"` or `"echo foo && echo bar"` or `"echo 'Hello World'"`
     // This prevents shell interpretation of pixi run inputs.
     // Use as-is if 'task' already contains multiple elements.
-    let task_args = if args.task.len() == 1 {
+    let task_args = if args.task.is_empty() {
+        // Without a task we let the user pick one, but only if there is someone to ask. Otherwise
+        // the task is a required argument and clap reports the usage error.
+        if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+            Args::command()
+                .bin_name("pixi run")
+                .arg_required_else_help(true)
+                .try_get_matches_from(["pixi run"])
+                .expect_err("the task should be required")
+                .exit();
+        }
+        match pick_task(&project)? {
+            Some(task) => vec![task],
+            None => return Ok(()),
+        }
+    } else if args.task.len() == 1 {
         shlex::split(args.task[0].as_str())
             .ok_or(miette!("Could not split task, assuming non valid task"))?
     } else {
@@ -57,7 +81,7 @@ pub async fn execute(args: Args) -> miette::Result<()> {
         ExecutableTask::from_cmd_args(&project, task_args, Some(Platform::current()));
 
     // Get the environment to run the commands in.
-    let command_env = get_task_env(&project, args.lock_file_usage.into()).await?;
+    let command_env = get_task_env(&project, args.lock_file_usage.into(), &args.env_file).await?;
 
     // Traverse the task and its dependencies. Execute each task in order.
     match executable_task
@@ -78,16 +102,147 @@ pub async fn execute(args: Args) -> miette::Result<()> {
     }
 }
 
+/// Returns the task names that match the `query`, best matches first. Names that contain the query
+/// verbatim always match, other names are matched fuzzily using the same scorer as `pixi search`.
+fn filter_task_names<'n>(names: &'n [String], query: &str) -> Vec<&'n String> {
+    if query.is_empty() {
+        return names.iter().collect();
+    }
+
+    let query = query.to_lowercase();
+    names
+        .iter()
+        .filter_map(|name| {
+            let lowercase_name = name.to_lowercase();
+            let similarity = jaro(&query, &lowercase_name);
+            if lowercase_name.contains(&query) {
+                Some((name, similarity + 1.0))
+            } else if similarity > 0.6 {
+                Some((name, similarity))
+            } else {
+                None
+            }
+        })
+        .sorted_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .partial_cmp(a_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.cmp(b))
+        })
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Shows an interactive, fuzzy-filterable list of the tasks of the default environment for the
+/// current platform. Returns the name of the picked task or `None` if the user aborted.
+fn pick_task(project: &Project) -> miette::Result<Option<String>> {
+    let names = project
+        .default_environment()
+        .tasks(Some(Platform::current()))?
+        .into_keys()
+        .map(String::from)
+        .sorted()
+        .collect_vec();
+
+    if names.is_empty() {
+        miette::bail!(
+            "no tasks defined for {}, add one with `pixi task add`",
+            Platform::current()
+        );
+    }
+
+    let term = Term::stderr();
+    term.hide_cursor().into_diagnostic()?;
+    let result = run_picker(&term, &names);
+    term.show_cursor().into_diagnostic()?;
+
+    match result {
+        Ok(picked) => Ok(picked),
+        // Pressing CTRL+C while reading a key is reported as an interrupted read.
+        Err(err) if err.kind() == std::io::ErrorKind::Interrupted => Ok(None),
+        Err(err) => Err(err).into_diagnostic(),
+    }
+}
+
+/// Renders the picker and handles key presses until a task is picked or the picker is aborted.
+fn run_picker(term: &Term, names: &[String]) -> std::io::Result<Option<String>> {
+    const MAX_VISIBLE_ITEMS: usize = 10;
+
+    let mut query = String::new();
+    let mut selected = 0;
+    let mut rendered_lines = 0;
+
+    let picked = loop {
+        let matches = filter_task_names(names, &query);
+        selected = selected.min(matches.len().saturating_sub(1));
+
+        term.clear_last_lines(rendered_lines)?;
+        term.write_line(&format!(
+            "{} {} {}",
+            style("?").yellow().bold(),
+            style("Select a task to run ›").bold(),
+            query
+        ))?;
+        rendered_lines = 1;
+
+        let (_, columns) = term.size();
+        let first_visible = selected.saturating_sub(MAX_VISIBLE_ITEMS - 1);
+        for (idx, name) in matches
+            .iter()
+            .enumerate()
+            .skip(first_visible)
+            .take(MAX_VISIBLE_ITEMS)
+        {
+            let line = if idx == selected {
+                format!("{} {}", style("❯").cyan(), style(name).cyan().bold())
+            } else {
+                format!("  {name}")
+            };
+            term.write_line(&console::truncate_str(&line, columns as usize, "…"))?;
+            rendered_lines += 1;
+        }
+        if matches.is_empty() {
+            term.write_line(&style("  no matching tasks").dim().to_string())?;
+            rendered_lines += 1;
+        }
+
+        match term.read_key()? {
+            Key::Enter => {
+                if let Some(name) = matches.get(selected) {
+                    break Some(name.to_string());
+                }
+            }
+            Key::Escape | Key::CtrlC => break None,
+            Key::ArrowUp => selected = selected.saturating_sub(1),
+            Key::ArrowDown => {
+                if selected + 1 < matches.len() {
+                    selected += 1;
+                }
+            }
+            Key::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            Key::Char(c) if !c.is_control() => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    };
+
+    term.clear_last_lines(rendered_lines)?;
+    Ok(picked)
+}
+
 #[derive(Debug, Error, Diagnostic)]
 enum TaskExecutionError {
     #[error("the script exited with a non-zero exit code {0}")]
     NonZeroExitCode(i32),
 
     #[error(transparent)]
-    FailedToParseShellScript(#[from] FailedToParseShellScript),
-
-    #[error(transparent)]
-    InvalidWorkingDirectory(#[from] InvalidWorkingDirectory),
+    #[diagnostic(transparent)]
+    InvalidTask(#[from] crate::task::TaskExecutionError),
 
     #[error(transparent)]
     TraverseError(#[from] TraversalError),
@@ -161,9 +316,14 @@ async fn execute_task<'p>(
 /// activation scripts from the environment and stores the environment variables it added, it adds
 /// environment variables set by the project and merges all of that with the system environment
 /// variables.
+///
+/// Variables are applied in the following order, later sources overwrite earlier ones: the system
+/// environment, the activation, the env files of the project, the `env_files` and finally the
+/// variables set by pixi itself.
 pub async fn get_task_env(
     project: &Project,
     lock_file_usage: LockFileUsage,
+    env_files: &[PathBuf],
 ) -> miette::Result<HashMap<String, String>> {
     // Get the prefix which we can then activate.
     let prefix =
@@ -171,24 +331,67 @@ pub async fn get_task_env(
 
     // Get environment variables from the activation
     let activation_env = run_activation_async(project, prefix).await?;
+    let env: HashMap<String, String> = std::env::vars().chain(activation_env).collect();
+
+    // Get environment variables from the env files
+    let file_env = load_env_files(project, env_files, &env)?;
 
     // Get environment variables from the manifest
     let manifest_env = project.get_metadata_env();
 
     // Construct command environment by concatenating the environments
-    Ok(std::env::vars()
-        .chain(activation_env.into_iter())
+    Ok(env
+        .into_iter()
+        .chain(file_env.into_iter())
         .chain(manifest_env.into_iter())
         .collect())
 }
 
+/// Loads the variables from the env files defined in the manifest followed by the variables from
+/// `env_files`. Variables in later files overwrite those in earlier files. References to variables
+/// that are not defined in the files themselves are looked up in `env`.
+///
+/// Env files from the manifest that do not exist are skipped, the `env_files` must exist.
+pub fn load_env_files(
+    project: &Project,
+    env_files: &[PathBuf],
+    env: &HashMap<String, String>,
+) -> miette::Result<Vec<(String, String)>> {
+    let manifest_env_files = project.env_files().into_iter().map(|path| (path, false));
+    let env_files = env_files.iter().map(|path| (path.clone(), true));
+
+    let mut env = env.clone();
+    let mut result = Vec::new();
+    for (path, required) in manifest_env_files.chain(env_files) {
+        if !required && !path.is_file() {
+            tracing::debug!("skipping env file {} which does not exist", path.display());
+            continue;
+        }
+        for (name, value) in dotenv::load_env_file(&path, &env)? {
+            env.insert(name.clone(), value.clone());
+            result.push((name, value));
+        }
+    }
+
+    Ok(result)
+}
+
 /// Runs the activation script asynchronously. This function also adds a progress bar.
 pub async fn run_activation_async(
     project: &Project,
     prefix: Prefix,
 ) -> miette::Result<HashMap<String, String>> {
     let platform = Platform::current();
-    let additional_activation_scripts = project.activation_scripts(Some(platform));
+    let environment = project.default_environment();
+    let variables = project
+        .manifest_variables()
+        .with_environment(environment.name().as_str())
+        .with_platform(platform);
+    let additional_activation_scripts = environment
+        .activation_scripts(Some(platform))
+        .into_iter()
+        .map(|script| variables.expand(&script))
+        .collect::<Result<Vec<_>, _>>()?;
 
     // Make sure the scripts exists
     let (additional_activation_scripts, missing_scripts): (Vec<_>, _) =
@@ -214,18 +417,30 @@ pub async fn run_activation_async(
         }
     }
 
+    // The environment variables from the manifest can refer to the project metadata variables.
+    let activation_env = environment.activation_env(Some(platform));
+    let metadata_env = project.get_metadata_env();
+
     await_in_progress(
         "activating environment",
-        run_activation(prefix, additional_activation_scripts.into_iter().collect()),
+        run_activation(
+            prefix,
+            additional_activation_scripts.into_iter().collect(),
+            activation_env,
+            metadata_env,
+        ),
     )
     .await
     .wrap_err("failed to activate environment")
 }
 
-/// Runs and caches the activation script.
+/// Runs and caches the activation script. Afterwards the environment variables from the manifest
+/// are applied on top of the variables set by the activation scripts.
 async fn run_activation(
     prefix: Prefix,
     additional_activation_scripts: Vec<PathBuf>,
+    activation_env: IndexMap<String, String>,
+    metadata_env: HashMap<String, String>,
 ) -> miette::Result<HashMap<String, String>> {
     let activator_result = tokio::task::spawn_blocking(move || {
         // Run and cache the activation script
@@ -253,5 +468,40 @@ async fn run_activation(
     .into_diagnostic()?
     .into_diagnostic()?;
 
+    // Apply the environment variables from the manifest on top of the variables set by the
+    // activation, their values can refer to the variables set by the activation.
+    let mut activator_result = activator_result;
+    let activation_env = expand_activation_env(activation_env, &activator_result, &metadata_env);
+    activator_result.extend(activation_env);
+
     Ok(activator_result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::filter_task_names;
+
+    #[test]
+    fn test_filter_task_names() {
+        let task_names = ["build", "lint", "start", "test", "test-all"].map(String::from);
+        let names = |query| {
+            filter_task_names(&task_names, query)
+                .into_iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+        };
+
+        // Everything is shown without a query
+        assert_eq!(names(""), ["build", "lint", "start", "test", "test-all"]);
+
+        // Substring matches come first, the closest match first
+        assert_eq!(names("test")[..2], ["test", "test-all"]);
+
+        // Typos are matched fuzzily
+        assert_eq!(names("biuld"), ["build"]);
+
+        // Matching ignores case
+        assert_eq!(names("LINT"), ["lint"]);
+        assert!(names("xyz").is_empty());
+    }
+}
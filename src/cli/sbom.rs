@@ -0,0 +1,456 @@
+use crate::{
+    lock_file::{
+        amend_pypi_purls, diff::PackageKind, graph::normalize_pypi_name, load_lock_file,
+        locked_packages,
+    },
+    Project,
+};
+use chrono::SecondsFormat;
+use clap::{Parser, ValueEnum};
+use itertools::Itertools;
+use miette::IntoDiagnostic;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use rattler_conda_types::{Platform, RepoDataRecord};
+use rattler_digest::{compute_bytes_digest, Sha256};
+use rattler_lock::PypiPackageData;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+/// The formats of the software bill of materials.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SbomFormat {
+    /// CycloneDX 1.5 in JSON.
+    CyclonedxJson,
+
+    /// SPDX 2.3 in JSON.
+    SpdxJson,
+}
+
+/// Generate a software bill of materials (SBOM) of the locked packages of an environment.
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// The format of the bill of materials.
+    #[arg(long, value_enum, default_value = "cyclonedx-json")]
+    pub format: SbomFormat,
+
+    /// The environment to describe, defaults to the default environment.
+    #[arg(long, short)]
+    pub environment: Option<String>,
+
+    /// The platform to describe, defaults to the current platform.
+    #[arg(long, short)]
+    pub platform: Option<Platform>,
+
+    /// The file to write the bill of materials to, defaults to stdout.
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+
+    /// Do not look up the pypi names of conda packages that are missing from the lock-file, which
+    /// requires network access.
+    #[arg(long)]
+    pub no_pypi_mapping: bool,
+
+    /// The path to 'pixi.toml'
+    #[arg(long)]
+    pub manifest_path: Option<PathBuf>,
+}
+
+/// The license of a package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum License {
+    /// A valid SPDX license expression.
+    Expression(String),
+
+    /// A license that is not a valid SPDX expression, like `BSD` or `Proprietary`.
+    Name(String),
+}
+
+impl License {
+    fn parse(license: Option<&str>) -> Option<Self> {
+        let license = license?.trim();
+        if license.is_empty() {
+            return None;
+        }
+        Some(match spdx::Expression::parse(license) {
+            Ok(_) => License::Expression(license.to_string()),
+            Err(err) => {
+                tracing::info!("'{license}' is not a valid SPDX license expression: {err}");
+                License::Name(license.to_string())
+            }
+        })
+    }
+}
+
+/// A locked package as it is described in the bill of materials.
+#[derive(Debug, Clone)]
+struct SbomPackage {
+    name: String,
+    version: String,
+    kind: PackageKind,
+    purl: String,
+
+    /// Other purls of the package, like the pypi purl of a conda package that ships a python
+    /// package.
+    other_purls: Vec<String>,
+    sha256: Option<String>,
+    md5: Option<String>,
+    license: Option<License>,
+    url: String,
+}
+
+impl SbomPackage {
+    fn from_conda(record: &RepoDataRecord) -> Self {
+        let package = &record.package_record;
+        let package_type = if record.file_name.ends_with(".conda") {
+            "conda"
+        } else {
+            "tar.bz2"
+        };
+        let purl = format!(
+            "pkg:conda/{}@{}?build={}&channel={}&subdir={}&type={}",
+            package.name.as_normalized(),
+            encode(&package.version.to_string()),
+            encode(&package.build),
+            encode(&record.channel),
+            encode(&package.subdir),
+            encode(package_type),
+        );
+        Self {
+            name: package.name.as_normalized().to_string(),
+            version: package.version.to_string(),
+            kind: PackageKind::Conda,
+            purl,
+            other_purls: package.purls.iter().map(ToString::to_string).collect(),
+            sha256: package.sha256.as_ref().map(|hash| format!("{hash:x}")),
+            md5: package.md5.as_ref().map(|hash| format!("{hash:x}")),
+            license: License::parse(package.license.as_deref()),
+            url: record.url.to_string(),
+        }
+    }
+
+    fn from_pypi(data: &PypiPackageData) -> Self {
+        let hash = data.hash.as_ref();
+        Self {
+            name: data.name.clone(),
+            version: data.version.to_string(),
+            kind: PackageKind::Pypi,
+            purl: format!(
+                "pkg:pypi/{}@{}",
+                normalize_pypi_name(&data.name),
+                encode(&data.version.to_string())
+            ),
+            other_purls: Vec::new(),
+            sha256: hash
+                .and_then(|hash| hash.sha256())
+                .map(|hash| format!("{hash:x}")),
+            md5: hash
+                .and_then(|hash| hash.md5())
+                .map(|hash| format!("{hash:x}")),
+            // The license of pypi packages is not part of the lock-file.
+            license: None,
+            url: data.url.to_string(),
+        }
+    }
+}
+
+/// The characters that are percent-encoded in a component of a purl. Unlike form encoding, a space
+/// is encoded as `%20`.
+const PURL_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Percent-encodes a component of a purl.
+fn encode(value: &str) -> String {
+    utf8_percent_encode(value, PURL_ENCODE_SET).to_string()
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+    let platform = args.platform.unwrap_or_else(Platform::current);
+    let environment = project.environment_from_name_or_default(args.environment.as_deref())?;
+    let environment_name = environment.name();
+
+    let lock_file = load_lock_file(&project).await?;
+    let (mut conda_packages, mut pypi_packages) = locked_packages(&lock_file, &environment)?;
+    let mut conda_packages = conda_packages.remove(&platform).unwrap_or_default();
+    let pypi_packages = pypi_packages.remove(&platform).unwrap_or_default();
+    if conda_packages.is_empty() && pypi_packages.is_empty() {
+        miette::bail!(
+            "the environment `{environment_name}` is not locked for `{platform}`, run `pixi install` or `pixi lock` to update the lock-file"
+        );
+    }
+
+    // The pypi purls are normally already stored in the lock-file, but older lock-files might not
+    // contain them.
+    if !args.no_pypi_mapping {
+        if let Err(err) = amend_pypi_purls(&mut conda_packages).await {
+            tracing::warn!("failed to determine the pypi names of the conda packages: {err}");
+        }
+    }
+
+    let packages = conda_packages
+        .iter()
+        .map(SbomPackage::from_conda)
+        .chain(
+            pypi_packages
+                .iter()
+                .map(|(data, _)| SbomPackage::from_pypi(data)),
+        )
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .collect_vec();
+
+    let document = Document {
+        name: format!("{}-{environment_name}-{platform}", project.name()),
+        version: project.version().as_ref().map(ToString::to_string),
+        created: chrono::Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+    };
+    let sbom = match args.format {
+        SbomFormat::CyclonedxJson => cyclonedx(&document, &packages),
+        SbomFormat::SpdxJson => spdx(&document, &packages),
+    };
+    let sbom = serde_json::to_string_pretty(&sbom).into_diagnostic()?;
+
+    match args.output {
+        Some(path) => {
+            std::fs::write(&path, format!("{sbom}\n")).into_diagnostic()?;
+            eprintln!(
+                "{}Wrote the bill of materials of {} packages to {}",
+                console::style(console::Emoji("✔ ", "")).green(),
+                packages.len(),
+                path.display()
+            );
+        }
+        None => println!("{sbom}"),
+    }
+    Ok(())
+}
+
+/// Describes the bill of materials itself.
+struct Document {
+    name: String,
+    version: Option<String>,
+    created: String,
+}
+
+/// Creates a CycloneDX 1.5 bill of materials.
+fn cyclonedx(document: &Document, packages: &[SbomPackage]) -> Value {
+    let components = packages
+        .iter()
+        .map(|package| {
+            let hashes = package
+                .sha256
+                .iter()
+                .map(|hash| json!({ "alg": "SHA-256", "content": hash }))
+                .chain(
+                    package
+                        .md5
+                        .iter()
+                        .map(|hash| json!({ "alg": "MD5", "content": hash })),
+                )
+                .collect_vec();
+            let licenses = match &package.license {
+                Some(License::Expression(expression)) => json!([{ "expression": expression }]),
+                Some(License::Name(name)) => json!([{ "license": { "name": name } }]),
+                None => json!([]),
+            };
+            let properties = std::iter::once(json!({
+                "name": "pixi:package-kind",
+                "value": package.kind.to_string(),
+            }))
+            .chain(
+                package
+                    .other_purls
+                    .iter()
+                    .map(|purl| json!({ "name": "pixi:purl", "value": purl })),
+            )
+            .collect_vec();
+            json!({
+                "type": "library",
+                "bom-ref": package.purl,
+                "name": package.name,
+                "version": package.version,
+                "purl": package.purl,
+                "hashes": hashes,
+                "licenses": licenses,
+                "externalReferences": [{ "type": "distribution", "url": package.url }],
+                "properties": properties,
+            })
+        })
+        .collect_vec();
+
+    let mut component = json!({ "type": "application", "name": document.name });
+    if let Some(version) = &document.version {
+        component["version"] = json!(version);
+    }
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "timestamp": document.created,
+            "tools": [{ "vendor": "prefix.dev", "name": "pixi", "version": env!("CARGO_PKG_VERSION") }],
+            "component": component,
+        },
+        "components": components,
+    })
+}
+
+/// Creates an SPDX 2.3 bill of materials.
+fn spdx(document: &Document, packages: &[SbomPackage]) -> Value {
+    let spdx_id = |idx: usize, package: &SbomPackage| {
+        let name: String = package
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        format!("SPDXRef-Package-{}-{idx}-{name}", package.kind)
+    };
+
+    let spdx_packages = packages
+        .iter()
+        .enumerate()
+        .map(|(idx, package)| {
+            let checksums = package
+                .sha256
+                .iter()
+                .map(|hash| json!({ "algorithm": "SHA256", "checksumValue": hash }))
+                .chain(
+                    package
+                        .md5
+                        .iter()
+                        .map(|hash| json!({ "algorithm": "MD5", "checksumValue": hash })),
+                )
+                .collect_vec();
+            let external_refs = std::iter::once(&package.purl)
+                .chain(&package.other_purls)
+                .map(|purl| {
+                    json!({
+                        "referenceCategory": "PACKAGE-MANAGER",
+                        "referenceType": "purl",
+                        "referenceLocator": purl,
+                    })
+                })
+                .collect_vec();
+            let mut spdx_package = json!({
+                "name": package.name,
+                "SPDXID": spdx_id(idx, package),
+                "versionInfo": package.version,
+                "downloadLocation": package.url,
+                "filesAnalyzed": false,
+                "checksums": checksums,
+                "licenseConcluded": "NOASSERTION",
+                "licenseDeclared": "NOASSERTION",
+                "copyrightText": "NOASSERTION",
+                "externalRefs": external_refs,
+            });
+            match &package.license {
+                Some(License::Expression(expression)) => {
+                    spdx_package["licenseDeclared"] = json!(expression)
+                }
+                Some(License::Name(name)) => {
+                    spdx_package["licenseComments"] =
+                        json!(format!("The package declares the license '{name}'"))
+                }
+                None => {}
+            }
+            spdx_package
+        })
+        .collect_vec();
+
+    let relationships = packages
+        .iter()
+        .enumerate()
+        .map(|(idx, package)| {
+            json!({
+                "spdxElementId": "SPDXRef-DOCUMENT",
+                "relationshipType": "DESCRIBES",
+                "relatedSpdxElement": spdx_id(idx, package),
+            })
+        })
+        .collect_vec();
+
+    // The namespace must be unique for every document with different contents.
+    let digest = compute_bytes_digest::<Sha256>(
+        packages
+            .iter()
+            .map(|package| package.purl.as_str())
+            .join("\n"),
+    );
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": document.name,
+        "documentNamespace": format!("https://prefix.dev/spdx/{}-{digest:x}", document.name),
+        "creationInfo": {
+            "created": document.created,
+            "creators": [format!("Tool: pixi-{}", env!("CARGO_PKG_VERSION"))],
+        },
+        "packages": spdx_packages,
+        "relationships": relationships,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lock_file::fixtures::record;
+
+    fn numpy() -> SbomPackage {
+        let mut record = record("numpy", "1.26.4", &[]);
+        record.package_record.build = String::from("py312h8753938_0");
+        record.package_record.subdir = String::from("linux-64");
+        record.package_record.license = Some(String::from("BSD-3-Clause"));
+        SbomPackage::from_conda(&record)
+    }
+
+    #[test]
+    fn test_conda_purl() {
+        assert_eq!(
+            numpy().purl,
+            "pkg:conda/numpy@1.26.4?build=py312h8753938_0&channel=https%3A%2F%2Fconda.anaconda.org%2Fconda-forge%2F&subdir=linux-64&type=conda"
+        );
+        assert_eq!(encode("1.0 beta+local"), "1.0%20beta%2Blocal");
+    }
+
+    #[test]
+    fn test_license() {
+        assert_eq!(
+            License::parse(Some("BSD-3-Clause AND MIT")),
+            Some(License::Expression(String::from("BSD-3-Clause AND MIT")))
+        );
+        assert_eq!(
+            License::parse(Some("BSD")),
+            Some(License::Name(String::from("BSD")))
+        );
+        assert_eq!(License::parse(Some(" ")), None);
+        assert_eq!(License::parse(None), None);
+    }
+
+    #[test]
+    fn test_spdx() {
+        let document = Document {
+            name: String::from("project-default-linux-64"),
+            version: None,
+            created: String::from("2024-01-01T00:00:00Z"),
+        };
+        let sbom = spdx(&document, &[numpy()]);
+        let package = &sbom["packages"][0];
+        assert_eq!(package["SPDXID"], "SPDXRef-Package-conda-0-numpy");
+        assert_eq!(package["licenseDeclared"], "BSD-3-Clause");
+        assert_eq!(
+            sbom["relationships"][0]["relatedSpdxElement"],
+            "SPDXRef-Package-conda-0-numpy"
+        );
+
+        let sbom = cyclonedx(&document, &[numpy()]);
+        let component = &sbom["components"][0];
+        assert_eq!(component["licenses"][0]["expression"], "BSD-3-Clause");
+        assert_eq!(
+            component["externalReferences"][0]["url"],
+            "https://conda.anaconda.org/conda-forge/noarch/numpy-1.26.4-0.conda"
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use crate::utils::schema::project_manifest_schema;
+use clap::Parser;
+use miette::{IntoDiagnostic, WrapErr};
+use std::path::PathBuf;
+
+/// Print the JSON Schema of the project manifest.
+///
+/// The schema can be used by editors to provide completion and validation of `pixi.toml`, for
+/// instance through Taplo or the Even Better TOML extension for VS Code.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Write the schema to this file instead of printing it to the standard output.
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+pub fn execute(args: Args) -> miette::Result<()> {
+    let schema = serde_json::to_string_pretty(&project_manifest_schema()).into_diagnostic()?;
+
+    match args.output {
+        Some(path) => std::fs::write(&path, format!("{schema}\n"))
+            .into_diagnostic()
+            .wrap_err_with(|| format!("failed to write the schema to {}", path.display()))?,
+        None => println!("{schema}"),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_contains_manifest_tables() {
+        let schema = serde_json::to_value(project_manifest_schema()).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        for key in [
+            "project",
+            "dependencies",
+            "pypi-dependencies",
+            "system-requirements",
+            "target",
+            "tasks",
+            "feature",
+            "environments",
+        ] {
+            assert!(
+                properties.contains_key(key),
+                "missing `{key}` in the schema"
+            );
+        }
+        assert_eq!(schema["additionalProperties"], false);
+
+        let definitions = schema["definitions"].as_object().unwrap();
+        for definition in [
+            "ProjectMetadata",
+            "Feature",
+            "Target",
+            "Task",
+            "SystemRequirements",
+            "PyPiRequirement",
+            "PrioritizedChannel",
+            "Environment",
+        ] {
+            assert!(
+                definitions.contains_key(definition),
+                "missing `{definition}` in the schema definitions"
+            );
+        }
+    }
+}
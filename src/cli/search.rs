@@ -13,7 +13,10 @@ use regex::Regex;
 use strsim::jaro;
 use tokio::task::spawn_blocking;
 
-use crate::{progress::await_in_progress, repodata::fetch_sparse_repodata, Project};
+use crate::{
+    lock_file::excluded_by, progress::await_in_progress, project::manifest::ExcludePattern,
+    repodata::fetch_sparse_repodata, Project,
+};
 
 /// Search a package, output will list the latest version of package
 #[derive(Debug, Parser)]
@@ -102,6 +105,12 @@ pub async fn execute(args: Args) -> miette::Result<()> {
         )],
     };
 
+    // Packages that are excluded by the project are marked in the output.
+    let exclude = project
+        .as_ref()
+        .map(|p| p.default_environment().exclude())
+        .unwrap_or_default();
+
     let package_name_filter = args.package;
     let authenticated_client = AuthenticatedClient::default();
     let repo_data = fetch_sparse_repodata(
@@ -118,13 +127,20 @@ pub async fn execute(args: Args) -> miette::Result<()> {
 
         let limit = args.limit;
 
-        search_package_by_wildcard(package_name, &package_name_filter, repo_data, limit, stdout)
-            .await?;
+        search_package_by_wildcard(
+            package_name,
+            &package_name_filter,
+            repo_data,
+            limit,
+            &exclude,
+            stdout,
+        )
+        .await?;
     }
     // If package name filter doesn't contain * (wildcard), it will search and display specific package info (if any package is found)
     else {
         let package_name = PackageName::try_from(package_name_filter).into_diagnostic()?;
-        search_exact_package(package_name, repo_data, stdout).await?;
+        search_exact_package(package_name, repo_data, &exclude, stdout).await?;
     }
 
     Ok(())
@@ -133,6 +149,7 @@ pub async fn execute(args: Args) -> miette::Result<()> {
 async fn search_exact_package<W: Write>(
     package_name: PackageName,
     repo_data: Vec<SparseRepoData>,
+    exclude: &[&ExcludePattern],
     out: W,
 ) -> miette::Result<()> {
     let package_name_search = package_name.clone();
@@ -154,7 +171,7 @@ async fn search_exact_package<W: Write>(
 
     let package = packages.last();
     if let Some(package) = package {
-        if let Err(e) = print_package_info(package, out) {
+        if let Err(e) = print_package_info(package, exclude, out) {
             if e.kind() != std::io::ErrorKind::BrokenPipe {
                 return Err(e).into_diagnostic();
             }
@@ -164,7 +181,11 @@ async fn search_exact_package<W: Write>(
     Ok(())
 }
 
-fn print_package_info<W: Write>(package: &RepoDataRecord, mut out: W) -> io::Result<()> {
+fn print_package_info<W: Write>(
+    package: &RepoDataRecord,
+    exclude: &[&ExcludePattern],
+    mut out: W,
+) -> io::Result<()> {
     writeln!(out)?;
 
     let package = package.clone();
@@ -195,6 +216,15 @@ fn print_package_info<W: Write>(package: &RepoDataRecord, mut out: W) -> io::Res
         console::style(build)
     )?;
 
+    if let Some(pattern) = excluded_by(&package.package_record, exclude) {
+        writeln!(
+            out,
+            "{:19} {:19}",
+            console::style("Excluded"),
+            console::style(format!("yes, by `{pattern}`")).red()
+        )?;
+    }
+
     let size = match package.package_record.size {
         Some(size) => size.to_string(),
         None => String::from("Not found."),
@@ -273,6 +303,7 @@ async fn search_package_by_wildcard<W: Write>(
     package_name_filter: &str,
     repo_data: Vec<SparseRepoData>,
     limit: usize,
+    exclude: &[&ExcludePattern],
     out: W,
 ) -> miette::Result<()> {
     let wildcard_pattern = Regex::new(&format!("^{}$", &package_name_filter.replace('*', ".*")))
@@ -330,7 +361,7 @@ async fn search_package_by_wildcard<W: Write>(
         let _ = packages.split_off(limit);
     }
 
-    if let Err(e) = print_matching_packages(packages, out) {
+    if let Err(e) = print_matching_packages(packages, exclude, out) {
         if e.kind() != std::io::ErrorKind::BrokenPipe {
             return Err(e).into_diagnostic();
         }
@@ -339,7 +370,11 @@ async fn search_package_by_wildcard<W: Write>(
     Ok(())
 }
 
-fn print_matching_packages<W: Write>(packages: Vec<RepoDataRecord>, mut out: W) -> io::Result<()> {
+fn print_matching_packages<W: Write>(
+    packages: Vec<RepoDataRecord>,
+    exclude: &[&ExcludePattern],
+    mut out: W,
+) -> io::Result<()> {
     writeln!(
         out,
         "{:40} {:19} {:19}",
@@ -354,16 +389,22 @@ fn print_matching_packages<W: Write>(packages: Vec<RepoDataRecord>, mut out: W)
         // https://github.com/mamba-org/rattler/issues/146
         let channel = package.channel.split('/').collect::<Vec<_>>();
         let channel_name = channel[channel.len() - 2];
+        let excluded = if excluded_by(&package.package_record, exclude).is_some() {
+            console::style("(excluded)").red().to_string()
+        } else {
+            String::new()
+        };
 
         let package_name = package.package_record.name;
         let version = package.package_record.version.as_str();
 
         writeln!(
             out,
-            "{:40} {:19} {:19}",
+            "{:40} {:19} {:19} {}",
             console::style(package_name.as_source()).cyan().bright(),
             console::style(version),
             console::style(channel_name),
+            excluded,
         )?;
     }
 
@@ -17,7 +17,7 @@ use crate::environment::LockFileUsage;
 #[cfg(target_family = "windows")]
 use rattler_shell::shell::CmdExe;
 
-use super::run::run_activation_async;
+use super::run::{load_env_files, run_activation_async};
 
 /// Start a shell in the pixi environment of the project
 #[derive(Parser, Debug)]
@@ -26,6 +26,10 @@ pub struct Args {
     #[arg(long)]
     manifest_path: Option<PathBuf>,
 
+    /// Load environment variables from this dotenv file, can be specified multiple times.
+    #[arg(long = "env-file", value_name = "PATH")]
+    env_file: Vec<PathBuf>,
+
     #[clap(flatten)]
     lock_file_usage: LockFileUsageArgs,
 }
@@ -196,10 +200,11 @@ async fn start_nu_shell(
 /// Determine the environment variables that need to be set in an interactive shell to make it
 /// function as if the environment has been activated. This method runs the activation scripts from
 /// the environment and stores the environment variables it added, finally it adds environment
-/// variables from the project.
+/// variables from the env files and the project.
 pub async fn get_shell_env(
     project: &Project,
     lock_file_usage: LockFileUsage,
+    env_files: &[PathBuf],
 ) -> miette::Result<HashMap<String, String>> {
     // Get the prefix which we can then activate.
     let prefix =
@@ -208,6 +213,12 @@ pub async fn get_shell_env(
     // Get environment variables from the activation
     let activation_env = run_activation_async(project, prefix).await?;
 
+    // Get environment variables from the env files, these can refer to the activation variables.
+    let lookup_env = std::env::vars()
+        .chain(activation_env.clone())
+        .collect::<HashMap<_, _>>();
+    let file_env = load_env_files(project, env_files, &lookup_env)?;
+
     // Get environment variables from the manifest
     let manifest_env = project.get_metadata_env();
 
@@ -218,6 +229,7 @@ pub async fn get_shell_env(
     // Construct command environment by concatenating the environments
     Ok(activation_env
         .into_iter()
+        .chain(file_env.into_iter())
         .chain(manifest_env.into_iter())
         .chain(shell_env.into_iter())
         .collect())
@@ -227,7 +239,7 @@ pub async fn execute(args: Args) -> miette::Result<()> {
     let project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
 
     // Get the environment variables we need to set activate the project in the shell.
-    let env = get_shell_env(&project, args.lock_file_usage.into()).await?;
+    let env = get_shell_env(&project, args.lock_file_usage.into(), &args.env_file).await?;
     tracing::debug!("Pixi environment activation:\n{:?}", env);
 
     // Start the shell as the last part of the activation script based on the default shell.
@@ -3,12 +3,14 @@ use miette::IntoDiagnostic;
 use rattler_conda_types::Platform;
 use rattler_shell::{
     activation::{ActivationVariables, Activator, PathModificationBehavior},
-    shell::ShellEnum,
+    shell::{ShellEnum, ShellScript},
 };
+use std::collections::HashMap;
 
 use crate::{
     environment::{get_up_to_date_prefix, LockFileUsage},
     prefix::Prefix,
+    project::metadata::expand_activation_env,
     Project,
 };
 
@@ -36,7 +38,8 @@ async fn generate_activation_script(shell: Option<ShellEnum>) -> miette::Result<
     let platform = Platform::current();
     let prefix = Prefix::new(project.default_environment().dir())?;
     let shell = shell.unwrap_or_default();
-    let activator = Activator::from_path(prefix.root(), shell, platform).into_diagnostic()?;
+    let activator =
+        Activator::from_path(prefix.root(), shell.clone(), platform).into_diagnostic()?;
 
     let path = std::env::var("PATH")
         .ok()
@@ -52,7 +55,20 @@ async fn generate_activation_script(shell: Option<ShellEnum>) -> miette::Result<
         })
         .into_diagnostic()?;
 
-    Ok(result.script.clone())
+    // Set the environment variables from the manifest after the activation of the prefix.
+    // The variables set by the activation script are not known here, so the values can only
+    // refer to the other variables.
+    let activation_env = expand_activation_env(
+        project.default_environment().activation_env(Some(platform)),
+        &HashMap::new(),
+        &project.get_metadata_env(),
+    );
+    let mut env_script = ShellScript::new(shell, platform);
+    for (name, value) in activation_env {
+        env_script.set_env_var(&name, &value);
+    }
+
+    Ok(format!("{}{}", result.script, env_script.contents))
 }
 
 /// Prints the activation script to the stdout.
@@ -0,0 +1,206 @@
+use crate::{
+    lock_file::{
+        graph::{DependencyGraph, PackageKind},
+        load_lock_file, locked_packages,
+    },
+    Project,
+};
+use clap::Parser;
+use itertools::Itertools;
+use rattler_conda_types::Platform;
+use std::{collections::HashSet, path::PathBuf};
+
+/// Show the dependency tree of the locked packages. Highlighted packages are explicit
+/// dependencies.
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Only show the tree of this package.
+    pub package: Option<String>,
+
+    /// Show the packages that depend on the package instead of its dependencies.
+    #[arg(long, short, requires = "package")]
+    pub invert: bool,
+
+    /// The environment to show, defaults to the default environment.
+    #[arg(long, short)]
+    pub environment: Option<String>,
+
+    /// The platform to show, defaults to the current platform.
+    #[arg(long, short)]
+    pub platform: Option<Platform>,
+
+    /// The path to 'pixi.toml'
+    #[arg(long)]
+    pub manifest_path: Option<PathBuf>,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+    let platform = args.platform.unwrap_or_else(Platform::current);
+    let environment = project.environment_from_name_or_default(args.environment.as_deref())?;
+    let environment_name = environment.name();
+
+    let lock_file = load_lock_file(&project).await?;
+    let (mut conda_packages, mut pypi_packages) = locked_packages(&lock_file, &environment)?;
+    let graph = DependencyGraph::new(
+        &conda_packages.remove(&platform).unwrap_or_default(),
+        &pypi_packages.remove(&platform).unwrap_or_default(),
+        &environment.virtual_packages(platform),
+    );
+    if graph.is_empty() {
+        miette::bail!(
+            "the environment `{environment_name}` is not locked for `{platform}`, run `pixi install` or `pixi lock` to update the lock-file"
+        );
+    }
+
+    let explicit = graph.explicit_packages(&environment, platform);
+    let roots = match &args.package {
+        Some(name) => vec![graph
+            .find(name)
+            .ok_or_else(|| miette::miette!("the package `{name}` is not part of the environment `{environment_name}` for `{platform}`"))?],
+        None => explicit
+            .iter()
+            .copied()
+            .sorted_by(|a, b| graph.package(*a).name.cmp(&graph.package(*b).name))
+            .collect(),
+    };
+
+    print!("{}", render_tree(&graph, &roots, &explicit, args.invert));
+    Ok(())
+}
+
+/// Renders the dependencies, or with `invert` the dependents, of the `roots` as a tree. The
+/// subtree of a package is only shown the first time the package appears, later appearances are
+/// marked with `(*)`. Dependencies that lead back to a package on the current path are marked with
+/// `(cycle)`.
+fn render_tree(
+    graph: &DependencyGraph,
+    roots: &[usize],
+    explicit: &HashSet<usize>,
+    invert: bool,
+) -> String {
+    let mut renderer = TreeRenderer {
+        graph,
+        explicit,
+        invert,
+        expanded: HashSet::new(),
+        output: String::new(),
+    };
+    for &root in roots {
+        renderer.line("", root, "");
+        renderer.expanded.insert(root);
+        renderer.children(root, "", &mut vec![root]);
+    }
+    renderer.output
+}
+
+struct TreeRenderer<'a> {
+    graph: &'a DependencyGraph,
+    explicit: &'a HashSet<usize>,
+    invert: bool,
+    expanded: HashSet<usize>,
+    output: String,
+}
+
+impl<'a> TreeRenderer<'a> {
+    fn label(&self, idx: usize) -> String {
+        let package = self.graph.package(idx);
+        let name = if self.explicit.contains(&idx) {
+            console::style(&package.name).green().bold().to_string()
+        } else {
+            package.name.clone()
+        };
+        match package.kind {
+            PackageKind::Conda => format!("{name} {}", package.version),
+            PackageKind::Pypi => format!("{name} {} (pypi)", package.version),
+            PackageKind::Virtual => format!("{name} {} (virtual)", package.version),
+        }
+    }
+
+    fn line(&mut self, prefix: &str, idx: usize, suffix: &str) {
+        let label = self.label(idx);
+        self.output.push_str(&format!("{prefix}{label}{suffix}\n"));
+    }
+
+    fn children(&mut self, idx: usize, prefix: &str, path: &mut Vec<usize>) {
+        let edges = if self.invert {
+            self.graph.dependents(idx)
+        } else {
+            self.graph.dependencies(idx)
+        };
+        let children = edges
+            .iter()
+            .map(|dep| dep.package)
+            .sorted_by(|a, b| {
+                self.graph
+                    .package(*a)
+                    .name
+                    .cmp(&self.graph.package(*b).name)
+            })
+            .collect_vec();
+
+        for (i, &child) in children.iter().enumerate() {
+            let is_last = i + 1 == children.len();
+            let connector = if is_last { "└── " } else { "├── " };
+            let line_prefix = format!("{prefix}{connector}");
+            let has_children = if self.invert {
+                !self.graph.dependents(child).is_empty()
+            } else {
+                !self.graph.dependencies(child).is_empty()
+            };
+
+            if path.contains(&child) {
+                self.line(&line_prefix, child, " (cycle)");
+            } else if has_children && !self.expanded.insert(child) {
+                self.line(&line_prefix, child, " (*)");
+            } else {
+                self.line(&line_prefix, child, "");
+                let child_prefix = format!("{prefix}{}", if is_last { "    " } else { "│   " });
+                path.push(child);
+                self.children(child, &child_prefix, path);
+                path.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lock_file::fixtures::record;
+
+    #[test]
+    fn test_render_tree() {
+        let graph = DependencyGraph::new(
+            &[
+                record("pandas", "1.0", &["numpy", "python"]),
+                record("numpy", "1.0", &["python"]),
+                record("python", "1.0", &["pip"]),
+                record("pip", "1.0", &["python"]),
+            ],
+            &[],
+            &[],
+        );
+        let pandas = graph.find("pandas").unwrap();
+        let python = graph.find("python").unwrap();
+
+        assert_eq!(
+            console::strip_ansi_codes(&render_tree(&graph, &[pandas], &HashSet::new(), false)),
+            "pandas 1.0\n\
+             ├── numpy 1.0\n\
+             │   └── python 1.0\n\
+             │       └── pip 1.0\n\
+             │           └── python 1.0 (cycle)\n\
+             └── python 1.0 (*)\n"
+        );
+        assert_eq!(
+            console::strip_ansi_codes(&render_tree(&graph, &[python], &HashSet::new(), true)),
+            "python 1.0\n\
+             ├── numpy 1.0\n\
+             │   └── pandas 1.0\n\
+             ├── pandas 1.0\n\
+             └── pip 1.0\n\
+             \u{20}   └── python 1.0 (cycle)\n"
+        );
+    }
+}
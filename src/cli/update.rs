@@ -0,0 +1,173 @@
+use crate::{
+    lock_file::{self, diff, load_lock_file},
+    project::manifest::ExcludeNewer,
+    Project,
+};
+use clap::Parser;
+use itertools::Itertools;
+use rattler_conda_types::Platform;
+use std::path::PathBuf;
+
+/// Update the locked versions of packages in the lock-file.
+///
+/// Without packages, the environment is solved again from scratch. With packages, only the given
+/// packages are unlocked, all other packages keep their locked version unless the new versions of
+/// the given packages require them to change.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// The conda or pypi packages to update, all packages are updated if none are given.
+    pub packages: Vec<String>,
+
+    /// The environment to update, defaults to the default environment.
+    #[arg(long, short)]
+    pub environment: Option<String>,
+
+    /// The platforms to update, defaults to all platforms of the environment.
+    #[arg(long = "platform", short)]
+    pub platforms: Vec<Platform>,
+
+    /// Don't select packages that were published after this date or timestamp, e.g. `2024-01-01`.
+    /// Overrides `exclude-newer` of the manifest.
+    #[arg(long)]
+    pub exclude_newer: Option<ExcludeNewer>,
+
+    /// The path to 'pixi.toml'
+    #[arg(long)]
+    pub manifest_path: Option<PathBuf>,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let mut project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+    if let Some(exclude_newer) = args.exclude_newer {
+        project.set_exclude_newer(exclude_newer);
+    }
+    let environment = project.environment_from_name_or_default(args.environment.as_deref())?;
+    let environment_name = environment.name();
+
+    let supported_platforms = environment.platforms();
+    let platforms = if args.platforms.is_empty() {
+        supported_platforms.into_iter().sorted().collect_vec()
+    } else {
+        if let Some(platform) = args
+            .platforms
+            .iter()
+            .find(|platform| !supported_platforms.contains(platform))
+        {
+            miette::bail!("the environment `{environment_name}` does not support `{platform}`");
+        }
+        args.platforms.iter().copied().unique().collect_vec()
+    };
+
+    // Load the currently locked packages.
+    let lock_file = load_lock_file(&project).await?;
+    let (locked_conda_packages, locked_pypi_packages) =
+        lock_file::locked_packages(&lock_file, &environment)?;
+
+    // Make sure the packages that should be updated are actually locked.
+    let packages = args
+        .packages
+        .iter()
+        .map(|name| name.to_lowercase())
+        .collect_vec();
+    for name in &packages {
+        let is_locked = platforms.iter().any(|platform| {
+            let conda = locked_conda_packages.get(platform).into_iter().flatten();
+            let pypi = locked_pypi_packages.get(platform).into_iter().flatten();
+            conda
+                .map(|record| record.package_record.name.as_normalized().to_string())
+                .chain(pypi.map(|(data, _)| data.name.to_lowercase()))
+                .any(|locked| &locked == name)
+        });
+        if !is_locked {
+            miette::bail!(
+                "the package `{name}` is not locked in the environment `{environment_name}`"
+            );
+        }
+    }
+
+    // Unlock the packages that should be updated, the solvers keep the versions of the
+    // remaining packages where possible.
+    let mut unlocked_conda_packages = locked_conda_packages.clone();
+    let mut unlocked_pypi_packages = locked_pypi_packages.clone();
+    for platform in &platforms {
+        if let Some(records) = unlocked_conda_packages.get_mut(platform) {
+            if packages.is_empty() {
+                records.clear();
+            } else {
+                records.retain(|record| {
+                    !packages.contains(&record.package_record.name.as_normalized().to_string())
+                });
+            }
+        }
+        if let Some(pypi_packages) = unlocked_pypi_packages.get_mut(platform) {
+            if packages.is_empty() {
+                pypi_packages.clear();
+            } else {
+                pypi_packages.retain(|(data, _)| !packages.contains(&data.name.to_lowercase()));
+            }
+        }
+    }
+
+    // Solve the selected platforms again.
+    let mut conda_packages = locked_conda_packages.clone();
+    conda_packages.extend(
+        lock_file::update_lock_file_conda(
+            &environment,
+            platforms.iter().copied(),
+            &unlocked_conda_packages,
+            None,
+        )
+        .await?,
+    );
+    let mut pypi_packages = locked_pypi_packages.clone();
+    if environment.has_pypi_dependencies() {
+        pypi_packages.extend(
+            lock_file::update_lock_file_for_pypi(
+                &environment,
+                platforms.iter().copied(),
+                &conda_packages,
+                &unlocked_pypi_packages,
+                None,
+                Default::default(),
+            )
+            .await?,
+        );
+    }
+
+    // Report the changed versions.
+    let changes = diff::environment_changes(
+        platforms.iter().copied(),
+        &locked_conda_packages,
+        &conda_packages,
+        &locked_pypi_packages,
+        &pypi_packages,
+    );
+    for (platform, changes) in &changes {
+        eprintln!("{}", console::style(platform).bold());
+        for change in changes {
+            eprintln!("  {change}");
+        }
+    }
+
+    lock_file::write_lock_file(
+        &project,
+        &lock_file,
+        vec![(&environment, conda_packages, pypi_packages)],
+    )?;
+
+    if !changes.is_empty() {
+        eprintln!(
+            "{}Updated the lock-file of environment `{}`",
+            console::style(console::Emoji("✔ ", "")).green(),
+            environment_name
+        );
+    } else {
+        eprintln!(
+            "{}The lock-file of environment `{}` is already up-to-date",
+            console::style(console::Emoji("✔ ", "")).green(),
+            environment_name
+        );
+    }
+
+    Ok(())
+}
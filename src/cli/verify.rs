@@ -0,0 +1,483 @@
+use crate::{
+    install_pypi::PIXI_PYPI_INSTALLER,
+    lock_file::{diff::PackageKind, graph::normalize_pypi_name, load_lock_file, locked_packages},
+    prefix::Prefix,
+    project::Environment,
+    pypi_tags::is_python_record,
+    Project,
+};
+use clap::Parser;
+use itertools::Itertools;
+use miette::{Context, IntoDiagnostic};
+use rattler::install::PythonInfo;
+use rattler_conda_types::{prefix_record::PathType, Platform, PrefixRecord, RepoDataRecord};
+use rattler_digest::{compute_file_digest, Sha256, Sha256Hash};
+use rattler_lock::{PypiPackageData, PypiPackageEnvironmentData};
+use rip::{artifacts::wheel::InstallPaths, python_env::find_distributions_in_venv};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+/// Verify that the installed environments match the lock-file. Exits with a non-zero exit code if
+/// anything differs.
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// The environment to verify, defaults to all installed environments.
+    #[arg(long, short)]
+    pub environment: Option<String>,
+
+    /// Output the differences as JSON.
+    #[arg(long)]
+    pub json: bool,
+
+    /// The path to 'pixi.toml'
+    #[arg(long)]
+    pub manifest_path: Option<PathBuf>,
+}
+
+/// A difference between an installed package and the lock-file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Drift {
+    pub environment: String,
+    pub package: String,
+    pub kind: PackageKind,
+    #[serde(flatten)]
+    pub problem: Problem,
+}
+
+/// What differs between an installed package and the lock-file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "problem", rename_all = "kebab-case")]
+pub enum Problem {
+    /// The package is locked but not installed.
+    NotInstalled,
+
+    /// The package is installed but not locked.
+    NotLocked,
+
+    /// The installed version differs from the locked version.
+    VersionMismatch { locked: String, installed: String },
+
+    /// The hash of the installed package differs from the locked hash. The installed hash is
+    /// `None` if it was not recorded during the installation.
+    HashMismatch {
+        algorithm: &'static str,
+        locked: String,
+        installed: Option<String>,
+    },
+
+    /// A file of the package was removed from the environment.
+    MissingFile { path: PathBuf },
+
+    /// The content of a file of the package was modified.
+    ModifiedFile {
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::NotInstalled => write!(f, "is locked but not installed"),
+            Problem::NotLocked => write!(f, "is installed but not locked"),
+            Problem::VersionMismatch { locked, installed } => {
+                write!(f, "version {installed} is installed but {locked} is locked")
+            }
+            Problem::HashMismatch {
+                algorithm,
+                locked,
+                installed: Some(installed),
+            } => write!(
+                f,
+                "{algorithm} {installed} is installed but {locked} is locked"
+            ),
+            Problem::HashMismatch {
+                algorithm,
+                locked,
+                installed: None,
+            } => write!(
+                f,
+                "the {algorithm} of the installed package is unknown but {locked} is locked"
+            ),
+            Problem::MissingFile { path } => write!(f, "{} is missing", path.display()),
+            Problem::ModifiedFile { path, .. } => write!(f, "{} was modified", path.display()),
+        }
+    }
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+    let environments = match &args.environment {
+        Some(name) => {
+            let environment = project.environment_from_name_or_default(Some(name.as_str()))?;
+            if !environment.dir().is_dir() {
+                miette::bail!(
+                    "the environment `{name}` is not installed, run `pixi install` to install it"
+                );
+            }
+            vec![environment]
+        }
+        None => project
+            .environments()
+            .into_iter()
+            .filter(|environment| environment.dir().is_dir())
+            .collect(),
+    };
+    if environments.is_empty() {
+        miette::bail!("no environments are installed, run `pixi install` to install them");
+    }
+
+    let lock_file = load_lock_file(&project).await?;
+    let platform = Platform::current();
+    let mut drift = Vec::new();
+    for environment in &environments {
+        let (mut conda_packages, mut pypi_packages) = locked_packages(&lock_file, environment)?;
+        drift.extend(
+            verify_environment(
+                environment,
+                platform,
+                conda_packages.remove(&platform).unwrap_or_default(),
+                pypi_packages.remove(&platform).unwrap_or_default(),
+            )
+            .await
+            .with_context(|| {
+                format!("failed to verify the environment `{}`", environment.name())
+            })?,
+        );
+    }
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&drift).into_diagnostic()?
+        );
+    } else {
+        print_drift(&environments, &drift);
+    }
+
+    if !drift.is_empty() {
+        miette::bail!(
+            "{} installed package(s) differ from the lock-file, run `pixi install` to restore the environment",
+            drift.iter().map(|drift| (&drift.environment, &drift.package)).unique().count()
+        );
+    }
+    Ok(())
+}
+
+/// Prints the drift of every verified environment, or that it matches the lock-file.
+fn print_drift(environments: &[Environment<'_>], drift: &[Drift]) {
+    for environment in environments {
+        let name = environment.name().as_str();
+        let environment_drift = drift
+            .iter()
+            .filter(|drift| drift.environment == name)
+            .collect_vec();
+        if environment_drift.is_empty() {
+            eprintln!(
+                "{}The environment `{}` matches the lock-file",
+                console::style(console::Emoji("✔ ", "")).green(),
+                environment.name()
+            );
+            continue;
+        }
+
+        eprintln!(
+            "{}The environment `{}` differs from the lock-file:",
+            console::style(console::Emoji("✘ ", "")).red(),
+            environment.name()
+        );
+        for drift in environment_drift {
+            println!(
+                "    {} ({}) {}",
+                console::style(&drift.package).bold(),
+                drift.kind,
+                drift.problem
+            );
+        }
+    }
+}
+
+/// Compares the installed conda and pypi packages of an environment with the locked packages.
+async fn verify_environment(
+    environment: &Environment<'_>,
+    platform: Platform,
+    conda_packages: Vec<RepoDataRecord>,
+    pypi_packages: Vec<(PypiPackageData, PypiPackageEnvironmentData)>,
+) -> miette::Result<Vec<Drift>> {
+    let name = environment.name().as_str().to_string();
+    let prefix = Prefix::new(environment.dir())?;
+    let installed = prefix.find_installed_packages(None).await?;
+
+    // Hashing the files of all packages takes a while, so do it on a separate thread.
+    tokio::task::spawn_blocking(move || {
+        let drift: Vec<Drift> = conda_problems(prefix.root(), &conda_packages, &installed)
+            .into_iter()
+            .map(|(package, problem)| Drift {
+                environment: name.clone(),
+                package,
+                kind: PackageKind::Conda,
+                problem,
+            })
+            .chain(
+                pypi_problems(prefix.root(), platform, &pypi_packages, &installed)?
+                    .into_iter()
+                    .map(|(package, problem)| Drift {
+                        environment: name.clone(),
+                        package,
+                        kind: PackageKind::Pypi,
+                        problem,
+                    }),
+            )
+            .collect();
+        Ok(drift)
+    })
+    .await
+    .into_diagnostic()?
+}
+
+/// Compares the installed conda packages with the locked conda packages. This compares the hashes
+/// of the packages and the content of the files that they installed.
+fn conda_problems(
+    prefix_root: &Path,
+    locked: &[RepoDataRecord],
+    installed: &[PrefixRecord],
+) -> Vec<(String, Problem)> {
+    let mut installed: HashMap<_, _> = installed
+        .iter()
+        .map(|record| {
+            (
+                record.repodata_record.package_record.name.as_normalized(),
+                record,
+            )
+        })
+        .collect();
+
+    let mut problems = Vec::new();
+    for locked in locked {
+        let locked = &locked.package_record;
+        let name = locked.name.as_normalized();
+        let Some(installed) = installed.remove(name) else {
+            problems.push((name.to_string(), Problem::NotInstalled));
+            continue;
+        };
+        let record = &installed.repodata_record.package_record;
+
+        if record.version != locked.version || record.build != locked.build {
+            problems.push((
+                name.to_string(),
+                Problem::VersionMismatch {
+                    locked: format!("{} {}", locked.version, locked.build),
+                    installed: format!("{} {}", record.version, record.build),
+                },
+            ));
+            continue;
+        }
+        if let (Some(expected), Some(actual)) = (&locked.sha256, &record.sha256) {
+            if expected != actual {
+                problems.push((
+                    name.to_string(),
+                    Problem::HashMismatch {
+                        algorithm: "sha256",
+                        locked: format!("{expected:x}"),
+                        installed: Some(format!("{actual:x}")),
+                    },
+                ));
+            }
+        }
+        if let (Some(expected), Some(actual)) = (&locked.md5, &record.md5) {
+            if expected != actual {
+                problems.push((
+                    name.to_string(),
+                    Problem::HashMismatch {
+                        algorithm: "md5",
+                        locked: format!("{expected:x}"),
+                        installed: Some(format!("{actual:x}")),
+                    },
+                ));
+            }
+        }
+
+        for entry in &installed.paths_data.paths {
+            let problem = match entry.path_type {
+                // Compiled python files are regenerated by python when they are missing.
+                PathType::Directory | PathType::PycFile => None,
+                PathType::SoftLink => prefix_root
+                    .join(&entry.relative_path)
+                    .symlink_metadata()
+                    .is_err()
+                    .then(|| Problem::MissingFile {
+                        path: entry.relative_path.clone(),
+                    }),
+                // Files that contained the prefix have a different hash after installation.
+                PathType::HardLink => verify_file(
+                    prefix_root,
+                    &entry.relative_path,
+                    entry.sha256_in_prefix.or(entry.sha256),
+                ),
+                _ => verify_file(prefix_root, &entry.relative_path, None),
+            };
+            problems.extend(problem.map(|problem| (name.to_string(), problem)));
+        }
+    }
+
+    problems.extend(
+        installed
+            .into_keys()
+            .map(|name| (name.to_string(), Problem::NotLocked)),
+    );
+    problems.sort_by(|a, b| a.0.cmp(&b.0));
+    problems
+}
+
+/// Checks that a file of a package exists and, if `expected` is given, that its content has the
+/// expected hash.
+fn verify_file(
+    prefix_root: &Path,
+    relative_path: &Path,
+    expected: Option<Sha256Hash>,
+) -> Option<Problem> {
+    let path = prefix_root.join(relative_path);
+    if !path.is_file() {
+        return Some(Problem::MissingFile {
+            path: relative_path.to_path_buf(),
+        });
+    }
+    let expected = expected?;
+    let actual = match compute_file_digest::<Sha256>(&path) {
+        Ok(actual) => actual,
+        Err(err) => {
+            tracing::warn!("failed to compute the hash of {}: {err}", path.display());
+            return None;
+        }
+    };
+    (actual != expected).then(|| Problem::ModifiedFile {
+        path: relative_path.to_path_buf(),
+        expected: format!("{expected:x}"),
+        actual: format!("{actual:x}"),
+    })
+}
+
+/// Compares the python distributions that pixi installed with the locked pypi packages. pixi
+/// writes the hash of the installed artifact to a `HASH` file in the `.dist-info` directory, which
+/// is compared with the locked hash.
+fn pypi_problems(
+    prefix_root: &Path,
+    platform: Platform,
+    locked: &[(PypiPackageData, PypiPackageEnvironmentData)],
+    installed_conda: &[PrefixRecord],
+) -> miette::Result<Vec<(String, Problem)>> {
+    let Some(python_record) = installed_conda
+        .iter()
+        .find(|record| is_python_record(&record.repodata_record))
+    else {
+        // Without python no distributions can be installed.
+        return Ok(locked
+            .iter()
+            .map(|(data, _)| (data.name.clone(), Problem::NotInstalled))
+            .collect());
+    };
+    let python_info =
+        PythonInfo::from_python_record(&python_record.repodata_record.package_record, platform)
+            .into_diagnostic()?;
+    let install_paths = InstallPaths::for_venv(
+        (
+            python_info.short_version.0 as u32,
+            python_info.short_version.1 as u32,
+            0,
+        ),
+        platform.is_windows(),
+    );
+    let mut installed: HashMap<_, _> = find_distributions_in_venv(prefix_root, &install_paths)
+        .into_diagnostic()
+        .context("failed to locate the installed python packages")?
+        .into_iter()
+        .filter(|dist| dist.installer.as_deref() == Some(PIXI_PYPI_INSTALLER))
+        .map(|dist| (normalize_pypi_name(dist.name.as_str()), dist))
+        .collect();
+
+    let mut problems = Vec::new();
+    for (data, _) in locked {
+        let Some(dist) = installed.remove(&normalize_pypi_name(&data.name)) else {
+            problems.push((data.name.clone(), Problem::NotInstalled));
+            continue;
+        };
+        if dist.version != data.version {
+            problems.push((
+                data.name.clone(),
+                Problem::VersionMismatch {
+                    locked: data.version.to_string(),
+                    installed: dist.version.to_string(),
+                },
+            ));
+            continue;
+        }
+        let Some(expected) = data.hash.as_ref().and_then(|hash| hash.sha256()) else {
+            continue;
+        };
+        let installed_hash =
+            std::fs::read_to_string(prefix_root.join(&dist.dist_info).join("HASH"))
+                .ok()
+                .map(|hash| hash.trim().trim_start_matches("sha256-").to_string());
+        let expected = format!("{expected:x}");
+        if installed_hash.as_ref() != Some(&expected) {
+            problems.push((
+                data.name.clone(),
+                Problem::HashMismatch {
+                    algorithm: "sha256",
+                    locked: expected,
+                    installed: installed_hash,
+                },
+            ));
+        }
+    }
+
+    problems.extend(
+        installed
+            .into_values()
+            .map(|dist| (dist.name.as_str().to_string(), Problem::NotLocked)),
+    );
+    problems.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rattler_digest::compute_bytes_digest;
+
+    #[test]
+    fn test_verify_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("bin")).unwrap();
+        std::fs::write(dir.path().join("bin/tool"), "#!/bin/sh\necho hello\n").unwrap();
+        let hash = compute_bytes_digest::<Sha256>("#!/bin/sh\necho hello\n");
+
+        assert_eq!(
+            verify_file(dir.path(), Path::new("bin/tool"), Some(hash)),
+            None
+        );
+        assert_eq!(verify_file(dir.path(), Path::new("bin/tool"), None), None);
+        assert_eq!(
+            verify_file(dir.path(), Path::new("bin/other"), None),
+            Some(Problem::MissingFile {
+                path: PathBuf::from("bin/other")
+            })
+        );
+
+        std::fs::write(dir.path().join("bin/tool"), "#!/bin/sh\necho bye\n").unwrap();
+        let modified = compute_bytes_digest::<Sha256>("#!/bin/sh\necho bye\n");
+        assert_eq!(
+            verify_file(dir.path(), Path::new("bin/tool"), Some(hash)),
+            Some(Problem::ModifiedFile {
+                path: PathBuf::from("bin/tool"),
+                expected: format!("{hash:x}"),
+                actual: format!("{modified:x}"),
+            })
+        );
+    }
+}
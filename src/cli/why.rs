@@ -0,0 +1,173 @@
+use crate::{
+    lock_file::{
+        graph::{DependencyGraph, PackageKind},
+        load_lock_file, locked_packages,
+    },
+    Project,
+};
+use clap::Parser;
+use itertools::Itertools;
+use rattler_conda_types::{MatchSpec, Platform};
+use std::{collections::HashMap, path::PathBuf};
+
+/// Explain why a package is part of an environment by showing the chains of dependencies from the
+/// manifest to the package.
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// The name of the package.
+    pub package: String,
+
+    /// The environment to look in, defaults to the default environment.
+    #[arg(long, short)]
+    pub environment: Option<String>,
+
+    /// The platform to look in, defaults to the current platform.
+    #[arg(long, short)]
+    pub platform: Option<Platform>,
+
+    /// The path to 'pixi.toml'
+    #[arg(long)]
+    pub manifest_path: Option<PathBuf>,
+}
+
+pub async fn execute(args: Args) -> miette::Result<()> {
+    let project = Project::load_or_else_discover(args.manifest_path.as_deref())?;
+    let platform = args.platform.unwrap_or_else(Platform::current);
+    let environment = project.environment_from_name_or_default(args.environment.as_deref())?;
+    let environment_name = environment.name();
+
+    let lock_file = load_lock_file(&project).await?;
+    let (mut conda_packages, mut pypi_packages) = locked_packages(&lock_file, &environment)?;
+    let graph = DependencyGraph::new(
+        &conda_packages.remove(&platform).unwrap_or_default(),
+        &pypi_packages.remove(&platform).unwrap_or_default(),
+        &environment.virtual_packages(platform),
+    );
+    if graph.is_empty() {
+        miette::bail!(
+            "the environment `{environment_name}` is not locked for `{platform}`, run `pixi install` or `pixi lock` to update the lock-file"
+        );
+    }
+
+    let Some(target) = graph.find(&args.package) else {
+        miette::bail!(
+            "the package `{}` is not part of the environment `{environment_name}` for `{platform}`",
+            args.package
+        );
+    };
+
+    let manifest_specs = manifest_specs(&graph, &environment, platform);
+    let roots = manifest_specs
+        .keys()
+        .copied()
+        .sorted_by(|a, b| graph.package(*a).name.cmp(&graph.package(*b).name));
+    let paths = graph.shortest_paths(roots, target);
+
+    if paths.is_empty() {
+        println!(
+            "{} is not required by any of the dependencies of the environment `{environment_name}` for `{platform}`",
+            console::style(graph.package(target)).bold()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} is required by the environment `{environment_name}` for `{platform}` because of:\n",
+        console::style(graph.package(target)).bold()
+    );
+    for path in paths {
+        println!("{}", render_chain(&graph, &manifest_specs[&path[0]], &path));
+    }
+
+    Ok(())
+}
+
+/// Returns the specs in the manifest of the explicit dependencies of the `environment`, indexed
+/// by the package in the `graph` that they select.
+fn manifest_specs(
+    graph: &DependencyGraph,
+    environment: &crate::project::Environment<'_>,
+    platform: Platform,
+) -> HashMap<usize, String> {
+    let mut specs: HashMap<usize, Vec<String>> = HashMap::new();
+    for (name, spec) in environment.dependencies(None, Some(platform)).iter_specs() {
+        if let Some(idx) = graph.find_conda(name.as_normalized()) {
+            specs
+                .entry(idx)
+                .or_default()
+                .push(MatchSpec::from_nameless(spec.clone(), Some(name.clone())).to_string());
+        }
+    }
+    for (name, requirements) in environment.pypi_dependencies(Some(platform)) {
+        if let Some(idx) = graph.find_pypi(name.as_source_str()) {
+            specs.entry(idx).or_default().extend(
+                requirements
+                    .iter()
+                    .map(|requirement| requirement.as_pep508(&name).to_string()),
+            );
+        }
+    }
+    specs
+        .into_iter()
+        .map(|(idx, specs)| (idx, specs.join(", ")))
+        .collect()
+}
+
+/// Renders a chain of dependencies that starts with the spec in the manifest and names the spec
+/// that selected the next package at each hop.
+fn render_chain(graph: &DependencyGraph, manifest_spec: &str, path: &[usize]) -> String {
+    let mut result = format!(
+        "{} (manifest)\n",
+        console::style(manifest_spec).green().bold()
+    );
+    for (depth, (idx, next)) in path
+        .iter()
+        .zip(path.iter().skip(1).map(Some).chain([None]))
+        .enumerate()
+    {
+        let package = graph.package(*idx);
+        let kind = match package.kind {
+            PackageKind::Conda => "",
+            PackageKind::Pypi => " (pypi)",
+            PackageKind::Virtual => " (virtual)",
+        };
+        let requires = next
+            .and_then(|next| graph.dependency_spec(*idx, *next))
+            .map(|spec| format!(" requires {}", console::style(spec).cyan()))
+            .unwrap_or_default();
+        result.push_str(&format!(
+            "{}└── {package}{kind}{requires}\n",
+            "    ".repeat(depth)
+        ));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lock_file::fixtures::record;
+
+    #[test]
+    fn test_render_chain() {
+        let graph = DependencyGraph::new(
+            &[
+                record("pandas", "2.2.0", &["numpy >=1.22.4,<2", "python >=3.9"]),
+                record("numpy", "1.26.4", &["python >=3.9"]),
+                record("python", "3.12.1", &[]),
+            ],
+            &[],
+            &[],
+        );
+        let pandas = graph.find("pandas").unwrap();
+        let numpy = graph.find("numpy").unwrap();
+
+        let paths = graph.shortest_paths([pandas], numpy);
+        assert_eq!(
+            console::strip_ansi_codes(&render_chain(&graph, "pandas >=2", &paths[0])),
+            "pandas >=2 (manifest)\n\
+             └── pandas 2.2.0 requires numpy >=1.22.4,<2\n\
+             \u{20}   └── numpy 1.26.4\n"
+        );
+    }
+}
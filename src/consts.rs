@@ -1,9 +1,12 @@
 pub const PROJECT_MANIFEST: &str = "pixi.toml";
+pub const PYPROJECT_MANIFEST: &str = "pyproject.toml";
 pub const PROJECT_LOCK_FILE: &str = "pixi.lock";
 pub const PIXI_DIR: &str = ".pixi";
 pub const PREFIX_FILE_NAME: &str = "prefix";
 pub const ENVIRONMENTS_DIR: &str = "envs";
 pub const PYPI_DEPENDENCIES: &str = "pypi-dependencies";
+pub const CONSTRAINTS: &str = "constraints";
+pub const OVERRIDES: &str = "overrides";
 
 pub const DEFAULT_ENVIRONMENT_NAME: &str = "default";
 
@@ -0,0 +1,276 @@
+//! Parsing of `.env` files.
+//!
+//! The files use the common dotenv syntax: `KEY=VALUE` pairs, one per line, with an optional
+//! `export` prefix. Values can be unquoted, single-quoted (taken literally) or double-quoted (with
+//! escape sequences and spanning multiple lines). References to other variables with `$NAME` or
+//! `${NAME}` are expanded in unquoted and double-quoted values.
+
+use crate::project::metadata::expand_env_vars;
+use miette::{IntoDiagnostic, LabeledSpan, NamedSource, WrapErr};
+use std::{collections::HashMap, ops::Range, path::Path};
+use thiserror::Error;
+
+/// An error that occurred while parsing the contents of a dotenv file.
+#[derive(Debug, Error)]
+#[error("{message}")]
+pub struct DotenvParseError {
+    /// The location in the file where the error occurred.
+    pub span: Range<usize>,
+
+    /// A description of the error.
+    pub message: String,
+}
+
+impl DotenvParseError {
+    fn new(span: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+}
+
+/// Reads and parses the dotenv file at `path`. Variables that are referenced in the file but not
+/// defined in the file itself are looked up in `env`.
+pub fn load_env_file(
+    path: &Path,
+    env: &HashMap<String, String>,
+) -> miette::Result<Vec<(String, String)>> {
+    let contents = std::fs::read_to_string(path)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("failed to read env file {}", path.display()))?;
+    parse(&contents, |name| env.get(name).cloned()).map_err(|err| {
+        miette::miette!(
+            labels = vec![LabeledSpan::at(err.span.clone(), err.message.clone())],
+            "failed to parse env file {}",
+            path.display()
+        )
+        .with_source_code(NamedSource::new(
+            path.display().to_string(),
+            contents.clone(),
+        ))
+    })
+}
+
+/// Parses the contents of a dotenv file and returns the variables in the order they are defined.
+/// Variables that are referenced but not defined earlier in the file are looked up with `lookup`.
+pub fn parse(
+    contents: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Vec<(String, String)>, DotenvParseError> {
+    let mut vars: Vec<(String, String)> = Vec::new();
+    let mut pos = 0;
+    while pos < contents.len() {
+        let line_end = find_line_end(contents, pos);
+        let line = &contents[pos..line_end];
+
+        // Skip empty lines and comments
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            pos = line_end + 1;
+            continue;
+        }
+
+        // Skip leading whitespace and an optional `export` keyword
+        let mut start = pos + (line.len() - line.trim_start().len());
+        if let Some(rest) = contents[start..line_end].strip_prefix("export") {
+            if rest.starts_with([' ', '\t']) {
+                start = line_end - rest.trim_start().len();
+            }
+        }
+
+        // Parse the name of the variable
+        let Some(eq) = contents[start..line_end].find('=') else {
+            return Err(DotenvParseError::new(
+                start..line_end,
+                "expected a variable definition like `KEY=VALUE`",
+            ));
+        };
+        let name = contents[start..start + eq].trim_end();
+        if !is_valid_name(name) {
+            return Err(DotenvParseError::new(
+                start..start + name.len(),
+                "invalid variable name",
+            ));
+        }
+
+        // Skip whitespace between the `=` and the value
+        let value_start = start + eq + 1;
+        let value_start = line_end - contents[value_start..line_end].trim_start().len();
+
+        let defined = |var: &str| {
+            vars.iter()
+                .rev()
+                .find(|(name, _)| name == var)
+                .map(|(_, value)| value.clone())
+                .or_else(|| lookup(var))
+        };
+
+        let (value, value_end) = match contents[value_start..].chars().next() {
+            Some('\'') => {
+                let Some(len) = contents[value_start + 1..].find('\'') else {
+                    return Err(DotenvParseError::new(
+                        value_start..line_end,
+                        "missing closing single quote",
+                    ));
+                };
+                let end = value_start + 1 + len;
+                (contents[value_start + 1..end].to_string(), end + 1)
+            }
+            Some('"') => parse_double_quoted(contents, value_start, defined)?,
+            _ => {
+                // Unquoted values end at the end of the line or at an inline comment.
+                let raw = &contents[value_start..line_end];
+                let raw = match raw.find(" #").or_else(|| raw.find("\t#")) {
+                    Some(comment) => &raw[..comment],
+                    None => raw,
+                };
+                (expand_env_vars(raw.trim_end(), defined), line_end)
+            }
+        };
+
+        // Only whitespace or a comment is allowed after a quoted value.
+        let rest_end = find_line_end(contents, value_end);
+        let rest = contents[value_end..rest_end].trim();
+        if !rest.is_empty() && !rest.starts_with('#') {
+            return Err(DotenvParseError::new(
+                value_end..rest_end,
+                "unexpected characters after the value",
+            ));
+        }
+
+        vars.push((name.to_string(), value));
+        pos = rest_end + 1;
+    }
+
+    Ok(vars)
+}
+
+/// Parses a double-quoted value that starts at `start`. Returns the value and the offset right
+/// after the closing quote.
+fn parse_double_quoted(
+    contents: &str,
+    start: usize,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<(String, usize), DotenvParseError> {
+    let mut value = String::new();
+    let mut segment = String::new();
+    let mut chars = contents[start + 1..].char_indices();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => {
+                value.push_str(&expand_env_vars(&segment, &lookup));
+                return Ok((value, start + 1 + idx + 1));
+            }
+            '\\' => match chars.next() {
+                Some((_, 'n')) => segment.push('\n'),
+                Some((_, 'r')) => segment.push('\r'),
+                Some((_, 't')) => segment.push('\t'),
+                Some((_, c @ ('"' | '\\'))) => segment.push(c),
+                Some((_, '$')) => {
+                    // An escaped dollar is never expanded
+                    value.push_str(&expand_env_vars(&segment, &lookup));
+                    value.push('$');
+                    segment.clear();
+                }
+                Some((_, c)) => {
+                    segment.push('\\');
+                    segment.push(c);
+                }
+                None => break,
+            },
+            c => segment.push(c),
+        }
+    }
+
+    Err(DotenvParseError::new(
+        start..find_line_end(contents, start),
+        "missing closing double quote",
+    ))
+}
+
+/// Returns the offset of the first newline at or after `pos`, or the length of `contents`.
+fn find_line_end(contents: &str, pos: usize) -> usize {
+    contents[pos..]
+        .find('\n')
+        .map_or(contents.len(), |idx| pos + idx)
+}
+
+/// Returns true if `name` is a valid environment variable name, which matches
+/// `[A-Za-z_][A-Za-z0-9_]*`.
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_ok(contents: &str) -> Vec<(String, String)> {
+        parse(contents, |name| {
+            (name == "HOME").then(|| String::from("/home/user"))
+        })
+        .unwrap()
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let contents = r#"
+# A comment
+FOO=bar
+export EXPORTED = value with spaces # and a comment
+EMPTY=
+SINGLE='$HOME is not expanded'
+DOUBLE="line1\nline2 \"quoted\" \$HOME"
+MULTI="first
+second"
+EXPANDED=${HOME}/$FOO
+"#;
+        assert_eq!(
+            parse_ok(contents),
+            pairs(&[
+                ("FOO", "bar"),
+                ("EXPORTED", "value with spaces"),
+                ("EMPTY", ""),
+                ("SINGLE", "$HOME is not expanded"),
+                ("DOUBLE", "line1\nline2 \"quoted\" $HOME"),
+                ("MULTI", "first\nsecond"),
+                ("EXPANDED", "/home/user/bar"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |contents: &str| parse(contents, |_| None).unwrap_err();
+
+        let contents = "FOO=bar\nnot a definition\n";
+        let error = err(contents);
+        assert_eq!(&contents[error.span.clone()], "not a definition");
+
+        let contents = "1FOO=bar";
+        let error = err(contents);
+        assert_eq!(&contents[error.span.clone()], "1FOO");
+
+        let contents = "FOO.BAR=baz";
+        let error = err(contents);
+        assert_eq!(&contents[error.span.clone()], "FOO.BAR");
+
+        let contents = "FOO=\"unterminated\nBAR=baz";
+        let error = err(contents);
+        assert_eq!(error.message, "missing closing double quote");
+
+        let contents = "FOO='quoted' trailing";
+        let error = err(contents);
+        assert_eq!(&contents[error.span.clone()], " trailing");
+    }
+}
@@ -4,16 +4,14 @@ use miette::{Context, IntoDiagnostic};
 use crate::lock_file::lock_file_satisfies_project;
 use crate::project::manifest::SystemRequirements;
 use crate::project::virtual_packages::verify_current_platform_has_required_virtual_packages;
-use itertools::Itertools;
+use crate::utils::error::format_error_chain;
 use rattler::install::{PythonInfo, Transaction};
 use rattler_conda_types::{Platform, PrefixRecord, RepoDataRecord};
-use rattler_lock::{LockFile, PypiPackageData, PypiPackageEnvironmentData};
+use rattler_lock::{PypiPackageData, PypiPackageEnvironmentData};
 use rattler_networking::AuthenticatedClient;
 use rattler_repodata_gateway::sparse::SparseRepoData;
 use rip::index::PackageDb;
 use rip::resolve::SDistResolution;
-use std::error::Error;
-use std::fmt::Write;
 use std::{io::ErrorKind, path::Path};
 
 /// Verify the location of the prefix folder is not changed so the applied prefix path is still valid.
@@ -158,22 +156,32 @@ pub async fn get_up_to_date_prefix(
     };
 
     // Load the lock-file into memory.
-    let lock_file = lock_file::load_lock_file(project).await?;
+    let (mut lock_file, has_conflicts) = lock_file::load_lock_file_with_conflicts(project).await?;
+
+    // If the lock-file contains merge conflicts, solve every environment of the project again using
+    // the packages of both sides of the conflicts and replace the lock-file with the result.
+    if has_conflicts {
+        if !usage.allows_lock_file_updates() {
+            miette::bail!(
+                "the lock-file contains merge conflicts, run `pixi lock` to resolve them"
+            );
+        }
+        tracing::info!("the lock-file contains merge conflicts, solving all environments again");
+        lock_file = lock_file::merge::solve_merged_lock_file(project, &lock_file).await?;
+        lock_file
+            .to_path(&project.lock_file_path())
+            .into_diagnostic()
+            .context("failed to write updated lock-file to disk")?;
+    }
 
     // Check if the lock-file is up to date, but only if the current usage allows it.
     let update_lock_file = if usage.should_check_if_out_of_date() {
         match lock_file_satisfies_project(project, &lock_file) {
             Err(err) => {
-                // Construct an error message
-                let mut report = String::new();
-                let mut err: &dyn Error = &err;
-                write!(&mut report, "{}", err).unwrap();
-                while let Some(source) = err.source() {
-                    write!(&mut report, "\nbecause {}", source).unwrap();
-                    err = source
-                }
-
-                tracing::info!("lock-file is not up to date with the project\nbecause {report}",);
+                tracing::info!(
+                    "lock-file is not up to date with the project\nbecause {}",
+                    format_error_chain(&err)
+                );
 
                 if !usage.allows_lock_file_updates() {
                     miette::bail!("lock-file not up-to-date with the project");
@@ -213,6 +221,7 @@ pub async fn get_up_to_date_prefix(
         updated_repodata_records.insert(
             lock_file::update_lock_file_conda(
                 &environment,
+                environment.platforms(),
                 &locked_repodata_records,
                 sparse_repo_data,
             )
@@ -222,6 +231,10 @@ pub async fn get_up_to_date_prefix(
         &locked_repodata_records
     };
 
+    // Make sure the packages do not violate the license policy of the project before they are
+    // installed.
+    lock_file::check_license_policy(&environment, repodata_records)?;
+
     // Update the prefix with the conda packages. This will also return the python status.
     let python_status = if !no_install {
         let installed_prefix_records = installed_packages_future.await.into_diagnostic()??;
@@ -258,6 +271,7 @@ pub async fn get_up_to_date_prefix(
         updated_pypi_records.insert(
             lock_file::update_lock_file_for_pypi(
                 &environment,
+                environment.platforms(),
                 repodata_records,
                 &locked_pypi_records,
                 python_path.as_deref(),
@@ -269,6 +283,8 @@ pub async fn get_up_to_date_prefix(
         &locked_pypi_records
     };
 
+    lock_file::warn_unchecked_pypi_licenses(&environment, pypi_records);
+
     if project.has_pypi_dependencies() && !no_install {
         // Then update the pypi packages.
         let empty_repodata_vec = Vec::new();
@@ -292,40 +308,15 @@ pub async fn get_up_to_date_prefix(
 
     // If any of the records have changed we need to update the contents of the lock-file.
     if updated_repodata_records.is_some() || updated_pypi_records.is_some() {
-        let mut builder = LockFile::builder();
-
-        let channels = environment
-            .channels()
-            .into_iter()
-            .map(|channel| rattler_lock::Channel::from(channel.base_url().to_string()))
-            .collect_vec();
-        builder.set_channels(environment.name().as_str(), channels);
-
-        // Add the conda records
-        for (platform, records) in updated_repodata_records.unwrap_or(locked_repodata_records) {
-            for record in records {
-                builder.add_conda_package(environment.name().as_str(), platform, record.into());
-            }
-        }
-
-        // Add the PyPi records
-        for (platform, packages) in updated_pypi_records.unwrap_or(locked_pypi_records) {
-            for (pkg_data, pkg_env_data) in packages {
-                builder.add_pypi_package(
-                    environment.name().as_str(),
-                    platform,
-                    pkg_data,
-                    pkg_env_data,
-                );
-            }
-        }
-
-        // Write to disk
-        let lock_file = builder.finish();
-        lock_file
-            .to_path(&project.lock_file_path())
-            .into_diagnostic()
-            .context("failed to write updated lock-file to disk")?;
+        lock_file::write_lock_file(
+            project,
+            &lock_file,
+            vec![(
+                &environment,
+                updated_repodata_records.unwrap_or(locked_repodata_records),
+                updated_pypi_records.unwrap_or(locked_pypi_records),
+            )],
+        )?;
     }
 
     Ok(prefix)
@@ -1,6 +1,7 @@
 pub mod cli;
 pub mod config;
 pub mod consts;
+pub mod dotenv;
 pub mod environment;
 pub mod install;
 pub mod install_pypi;
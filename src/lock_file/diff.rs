@@ -0,0 +1,372 @@
+//! Computes the changes to the locked packages of an environment between two versions of the
+//! lock-file.
+
+use super::{LockedCondaEnvironment, LockedPypiEnvironment};
+use itertools::Itertools;
+use miette::{Context, IntoDiagnostic};
+use rattler_conda_types::{Platform, RepoDataRecord};
+use rattler_lock::{LockFile, PypiPackageData, PypiPackageEnvironmentData};
+use serde::Serialize;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt::{Display, Formatter},
+};
+
+/// The kind of a locked package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PackageKind {
+    Conda,
+    Pypi,
+}
+
+impl Display for PackageKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackageKind::Conda => write!(f, "conda"),
+            PackageKind::Pypi => write!(f, "pypi"),
+        }
+    }
+}
+
+/// How a package changed between two versions of the lock-file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Upgraded,
+    Downgraded,
+    /// The version is the same but the build string changed.
+    Rebuilt,
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChangeKind::Added => write!(f, "added"),
+            ChangeKind::Removed => write!(f, "removed"),
+            ChangeKind::Upgraded => write!(f, "upgraded"),
+            ChangeKind::Downgraded => write!(f, "downgraded"),
+            ChangeKind::Rebuilt => write!(f, "rebuilt"),
+        }
+    }
+}
+
+/// The version and build string of a locked package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LockedVersion {
+    pub version: String,
+
+    /// The build string, pypi packages do not have one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build: Option<String>,
+}
+
+impl Display for LockedVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.build {
+            Some(build) => write!(f, "{} {build}", self.version),
+            None => write!(f, "{}", self.version),
+        }
+    }
+}
+
+/// A change of a single package between two versions of the lock-file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackageChange {
+    /// The name of the package.
+    pub name: String,
+
+    /// Whether this is a conda or a pypi package.
+    pub kind: PackageKind,
+
+    /// How the package changed.
+    pub change: ChangeKind,
+
+    /// The version that was locked before, or `None` if the package was added.
+    pub before: Option<LockedVersion>,
+
+    /// The version that is locked after, or `None` if the package was removed.
+    pub after: Option<LockedVersion>,
+}
+
+impl Display for PackageChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.before, &self.after) {
+            (Some(before), Some(after)) => write!(f, "{} {before} -> {after}", self.name),
+            (None, Some(after)) => write!(f, "+ {} {after}", self.name),
+            (Some(before), None) => write!(f, "- {} {before}", self.name),
+            (None, None) => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// The changes of the packages of an environment for a single platform.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformChanges {
+    pub environment: String,
+    pub platform: Platform,
+    pub changes: Vec<PackageChange>,
+}
+
+/// Returns the changes between the `before` and `after` conda packages, sorted by name.
+pub fn conda_changes(before: &[RepoDataRecord], after: &[RepoDataRecord]) -> Vec<PackageChange> {
+    let versions = |records: &[RepoDataRecord]| {
+        records
+            .iter()
+            .map(|record| {
+                let record = &record.package_record;
+                (
+                    record.name.as_normalized().to_string(),
+                    (
+                        record.version.clone(),
+                        LockedVersion {
+                            version: record.version.to_string(),
+                            build: Some(record.build.clone()),
+                        },
+                    ),
+                )
+            })
+            .collect()
+    };
+    changes(PackageKind::Conda, versions(before), versions(after))
+}
+
+/// Returns the changes between the `before` and `after` pypi packages, sorted by name.
+pub fn pypi_changes(
+    before: &[(PypiPackageData, PypiPackageEnvironmentData)],
+    after: &[(PypiPackageData, PypiPackageEnvironmentData)],
+) -> Vec<PackageChange> {
+    let versions = |packages: &[(PypiPackageData, PypiPackageEnvironmentData)]| {
+        packages
+            .iter()
+            .map(|(data, _)| {
+                (
+                    data.name.to_lowercase(),
+                    (
+                        data.version.clone(),
+                        LockedVersion {
+                            version: data.version.to_string(),
+                            build: None,
+                        },
+                    ),
+                )
+            })
+            .collect()
+    };
+    changes(PackageKind::Pypi, versions(before), versions(after))
+}
+
+/// Returns the changes of the conda and pypi packages of an environment for each of the
+/// `platforms` that changed.
+pub fn environment_changes(
+    platforms: impl IntoIterator<Item = Platform>,
+    conda_before: &LockedCondaEnvironment,
+    conda_after: &LockedCondaEnvironment,
+    pypi_before: &LockedPypiEnvironment,
+    pypi_after: &LockedPypiEnvironment,
+) -> Vec<(Platform, Vec<PackageChange>)> {
+    platforms
+        .into_iter()
+        .map(|platform| {
+            let changes = conda_changes(
+                conda_before
+                    .get(&platform)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default(),
+                conda_after
+                    .get(&platform)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default(),
+            )
+            .into_iter()
+            .chain(pypi_changes(
+                pypi_before
+                    .get(&platform)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default(),
+                pypi_after
+                    .get(&platform)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default(),
+            ))
+            .collect_vec();
+            (platform, changes)
+        })
+        .filter(|(_, changes)| !changes.is_empty())
+        .collect()
+}
+
+/// Returns the changes between two lock-files for every environment and platform in either of
+/// them, sorted by environment and platform.
+pub fn lock_file_changes(
+    before: &LockFile,
+    after: &LockFile,
+) -> miette::Result<Vec<PlatformChanges>> {
+    let locked_packages = |lock_file: &LockFile, name: &str| {
+        let Some(environment) = lock_file.environment(name) else {
+            return Ok((
+                LockedCondaEnvironment::default(),
+                LockedPypiEnvironment::default(),
+            ));
+        };
+        let conda_packages = environment
+            .conda_repodata_records()
+            .into_diagnostic()
+            .context("failed to parse the contents of the lock-file")?;
+        Ok::<_, miette::Report>((conda_packages, environment.pypi_packages()))
+    };
+
+    let names: BTreeSet<_> = before
+        .environments()
+        .chain(after.environments())
+        .map(|(name, _)| name.to_string())
+        .collect();
+
+    let mut result = Vec::new();
+    for name in names {
+        let (conda_before, pypi_before) = locked_packages(before, &name)?;
+        let (conda_after, pypi_after) = locked_packages(after, &name)?;
+        let platforms: HashSet<_> = conda_before
+            .keys()
+            .chain(conda_after.keys())
+            .chain(pypi_before.keys())
+            .chain(pypi_after.keys())
+            .copied()
+            .collect();
+        let changes = environment_changes(
+            platforms
+                .into_iter()
+                .sorted_by_key(|platform| platform.as_str()),
+            &conda_before,
+            &conda_after,
+            &pypi_before,
+            &pypi_after,
+        );
+        result.extend(
+            changes
+                .into_iter()
+                .map(|(platform, changes)| PlatformChanges {
+                    environment: name.clone(),
+                    platform,
+                    changes,
+                }),
+        );
+    }
+    Ok(result)
+}
+
+/// Compares two maps from package name to version. The first element of the versions is used to
+/// determine whether a package was upgraded or downgraded.
+fn changes<V: Ord>(
+    kind: PackageKind,
+    before: BTreeMap<String, (V, LockedVersion)>,
+    mut after: BTreeMap<String, (V, LockedVersion)>,
+) -> Vec<PackageChange> {
+    let mut changes = Vec::new();
+    for (name, (version, locked)) in before {
+        let change = match after.remove(&name) {
+            None => PackageChange {
+                name,
+                kind,
+                change: ChangeKind::Removed,
+                before: Some(locked),
+                after: None,
+            },
+            Some((new_version, new_locked)) => {
+                let change = match new_version.cmp(&version) {
+                    Ordering::Greater => ChangeKind::Upgraded,
+                    Ordering::Less => ChangeKind::Downgraded,
+                    Ordering::Equal if new_locked != locked => ChangeKind::Rebuilt,
+                    Ordering::Equal => continue,
+                };
+                PackageChange {
+                    name,
+                    kind,
+                    change,
+                    before: Some(locked),
+                    after: Some(new_locked),
+                }
+            }
+        };
+        changes.push(change);
+    }
+    changes.extend(after.into_iter().map(|(name, (_, locked))| PackageChange {
+        name,
+        kind,
+        change: ChangeKind::Added,
+        before: None,
+        after: Some(locked),
+    }));
+    changes
+        .into_iter()
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rattler_conda_types::Version;
+    use std::str::FromStr;
+
+    fn versions(packages: &[(&str, &str, &str)]) -> BTreeMap<String, (Version, LockedVersion)> {
+        packages
+            .iter()
+            .map(|(name, version, build)| {
+                (
+                    name.to_string(),
+                    (
+                        Version::from_str(version).unwrap(),
+                        LockedVersion {
+                            version: version.to_string(),
+                            build: Some(build.to_string()),
+                        },
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_changes() {
+        let changes = changes(
+            PackageKind::Conda,
+            versions(&[
+                ("numpy", "1.26.4", "py312_0"),
+                ("python", "3.12.1", "h1_0"),
+                ("six", "1.16.0", "pyhd8ed1ab_0"),
+                ("zlib", "1.3", "h2_0"),
+            ]),
+            versions(&[
+                ("numpy", "2.0.0", "py312_0"),
+                ("pandas", "2.2.0", "py312_0"),
+                ("python", "3.12.1", "h1_0"),
+                ("zlib", "1.2.13", "h3_0"),
+            ]),
+        );
+        assert_eq!(
+            changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "numpy 1.26.4 py312_0 -> 2.0.0 py312_0",
+                "+ pandas 2.2.0 py312_0",
+                "- six 1.16.0 pyhd8ed1ab_0",
+                "zlib 1.3 h2_0 -> 1.2.13 h3_0",
+            ]
+        );
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| change.change)
+                .collect::<Vec<_>>(),
+            vec![
+                ChangeKind::Upgraded,
+                ChangeKind::Added,
+                ChangeKind::Removed,
+                ChangeKind::Downgraded
+            ]
+        );
+    }
+}
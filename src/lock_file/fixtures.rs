@@ -0,0 +1,24 @@
+//! Packages that are shared by the tests of the lock-file and the commands that inspect it.
+
+use rattler_conda_types::{PackageName, PackageRecord, RepoDataRecord, Version};
+use std::str::FromStr;
+use url::Url;
+
+/// Returns a `noarch` conda package of conda-forge with the given `depends`.
+pub fn record(name: &str, version: &str, depends: &[&str]) -> RepoDataRecord {
+    let mut package_record = PackageRecord::new(
+        PackageName::from_str(name).unwrap(),
+        Version::from_str(version).unwrap(),
+        String::from("0"),
+    );
+    package_record.depends = depends.iter().map(|d| d.to_string()).collect();
+    RepoDataRecord {
+        package_record,
+        file_name: format!("{name}-{version}-0.conda"),
+        url: Url::parse(&format!(
+            "https://conda.anaconda.org/conda-forge/noarch/{name}-{version}-0.conda"
+        ))
+        .unwrap(),
+        channel: String::from("https://conda.anaconda.org/conda-forge/"),
+    }
+}
@@ -0,0 +1,388 @@
+//! A dependency graph of the locked packages of an environment for a single platform.
+
+use crate::project::Environment;
+use rattler_conda_types::{GenericVirtualPackage, MatchSpec, Platform, RepoDataRecord};
+use rattler_lock::{PypiPackageData, PypiPackageEnvironmentData};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+/// The kind of a package in the [`DependencyGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageKind {
+    Conda,
+    Pypi,
+    /// A virtual package that describes the system, like `__glibc`.
+    Virtual,
+}
+
+/// A package in the [`DependencyGraph`].
+#[derive(Debug, Clone)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    pub kind: PackageKind,
+}
+
+impl Display for Package {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.name, self.version)
+    }
+}
+
+/// A dependency of one package on another.
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    /// The index of the package that is depended on, or of the dependent package for the reverse
+    /// dependencies returned by [`DependencyGraph::dependents`].
+    pub package: usize,
+
+    /// The requirement that selected the package, e.g. `numpy >=1.22` or `requests[socks]`.
+    pub spec: String,
+}
+
+/// The dependency graph of the locked packages of an environment.
+///
+/// The edges are created from the `depends` of the conda packages and the `requires_dist` of the
+/// pypi packages. Requirements on packages that are not locked, like pypi requirements with
+/// markers that do not apply, are ignored. Requirements on virtual packages are resolved against
+/// the virtual packages of the platform. The graph can contain cycles.
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    packages: Vec<Package>,
+    dependencies: Vec<Vec<Dependency>>,
+    dependents: Vec<Vec<Dependency>>,
+    conda_by_name: HashMap<String, usize>,
+    pypi_by_name: HashMap<String, usize>,
+}
+
+/// Normalizes the name of a pypi package so it can be compared with other names.
+pub fn normalize_pypi_name(name: &str) -> String {
+    name.to_lowercase().replace(['_', '.'], "-")
+}
+
+impl DependencyGraph {
+    /// Constructs the graph from the locked conda and pypi packages of a platform.
+    pub fn new(
+        conda_packages: &[RepoDataRecord],
+        pypi_packages: &[(PypiPackageData, PypiPackageEnvironmentData)],
+        virtual_packages: &[GenericVirtualPackage],
+    ) -> Self {
+        let mut graph = Self::default();
+
+        for record in conda_packages {
+            let record = &record.package_record;
+            let idx = graph.add_package(Package {
+                name: record.name.as_normalized().to_string(),
+                version: record.version.to_string(),
+                kind: PackageKind::Conda,
+            });
+            graph
+                .conda_by_name
+                .insert(record.name.as_normalized().to_string(), idx);
+        }
+        for virtual_package in virtual_packages {
+            let idx = graph.add_package(Package {
+                name: virtual_package.name.as_normalized().to_string(),
+                version: virtual_package.version.to_string(),
+                kind: PackageKind::Virtual,
+            });
+            graph
+                .conda_by_name
+                .insert(virtual_package.name.as_normalized().to_string(), idx);
+        }
+        for (data, _) in pypi_packages {
+            let idx = graph.add_package(Package {
+                name: data.name.clone(),
+                version: data.version.to_string(),
+                kind: PackageKind::Pypi,
+            });
+            graph
+                .pypi_by_name
+                .insert(normalize_pypi_name(&data.name), idx);
+        }
+
+        // Add the edges of the conda packages.
+        for (idx, record) in conda_packages.iter().enumerate() {
+            for depends in &record.package_record.depends {
+                let Some(name) = MatchSpec::from_str(depends).ok().and_then(|spec| spec.name)
+                else {
+                    continue;
+                };
+                if let Some(&target) = graph.conda_by_name.get(name.as_normalized()) {
+                    graph.add_dependency(idx, target, depends.clone());
+                }
+            }
+        }
+
+        // Add the edges of the pypi packages. A requirement is satisfied by a pypi package or by a
+        // conda package with the same name.
+        let first_pypi = conda_packages.len() + virtual_packages.len();
+        for (offset, (data, _)) in pypi_packages.iter().enumerate() {
+            for requirement in &data.requires_dist {
+                if let Some(target) = graph.find_pypi(&requirement.name) {
+                    graph.add_dependency(first_pypi + offset, target, requirement.to_string());
+                }
+            }
+        }
+
+        graph
+    }
+
+    fn add_package(&mut self, package: Package) -> usize {
+        self.packages.push(package);
+        self.dependencies.push(Vec::new());
+        self.dependents.push(Vec::new());
+        self.packages.len() - 1
+    }
+
+    fn add_dependency(&mut self, from: usize, to: usize, spec: String) {
+        if from == to || self.dependencies[from].iter().any(|dep| dep.package == to) {
+            return;
+        }
+        self.dependencies[from].push(Dependency {
+            package: to,
+            spec: spec.clone(),
+        });
+        self.dependents[to].push(Dependency {
+            package: from,
+            spec,
+        });
+    }
+
+    /// Returns the package with the given index.
+    pub fn package(&self, idx: usize) -> &Package {
+        &self.packages[idx]
+    }
+
+    /// Returns the number of packages in the graph.
+    pub fn len(&self) -> usize {
+        self.packages.len()
+    }
+
+    /// Returns true if the graph does not contain any packages.
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+
+    /// Finds a conda or virtual package by name.
+    pub fn find_conda(&self, name: &str) -> Option<usize> {
+        self.conda_by_name.get(name).copied()
+    }
+
+    /// Finds the package that satisfies a pypi requirement on `name`. This is either a pypi
+    /// package or a conda package with the same name.
+    pub fn find_pypi(&self, name: &str) -> Option<usize> {
+        let name = normalize_pypi_name(name);
+        self.pypi_by_name.get(&name).copied().or_else(|| {
+            self.conda_by_name
+                .iter()
+                .find(|(conda_name, _)| normalize_pypi_name(conda_name) == name)
+                .map(|(_, &idx)| idx)
+        })
+    }
+
+    /// Finds a package by name, conda packages take precedence over pypi packages.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.find_conda(&name.to_lowercase())
+            .or_else(|| self.find_pypi(name))
+    }
+
+    /// Returns the dependencies of a package.
+    pub fn dependencies(&self, idx: usize) -> &[Dependency] {
+        &self.dependencies[idx]
+    }
+
+    /// Returns the packages that depend on a package. The spec of each dependency is the
+    /// requirement of the dependent package.
+    pub fn dependents(&self, idx: usize) -> &[Dependency] {
+        &self.dependents[idx]
+    }
+
+    /// Returns the packages in the graph that are explicit dependencies of the `environment`.
+    pub fn explicit_packages(
+        &self,
+        environment: &Environment<'_>,
+        platform: Platform,
+    ) -> HashSet<usize> {
+        let conda = environment
+            .dependencies(None, Some(platform))
+            .names()
+            .filter_map(|name| self.find_conda(name.as_normalized()))
+            .collect::<Vec<_>>();
+        let pypi = environment
+            .pypi_dependencies(Some(platform))
+            .into_keys()
+            .filter_map(|name| self.find_pypi(name.as_source_str()))
+            .collect::<Vec<_>>();
+        conda.into_iter().chain(pypi).collect()
+    }
+
+    /// Returns the indices of all packages that can be reached from `roots`.
+    pub fn reachable(&self, roots: impl IntoIterator<Item = usize>) -> HashSet<usize> {
+        let mut visited = HashSet::new();
+        let mut queue: Vec<_> = roots.into_iter().collect();
+        while let Some(idx) = queue.pop() {
+            if visited.insert(idx) {
+                queue.extend(self.dependencies[idx].iter().map(|dep| dep.package));
+            }
+        }
+        visited
+    }
+
+    /// Returns every shortest path from each of the `roots` to `target`. A path starts with the
+    /// root and ends with the target, roots from which the target cannot be reached are skipped.
+    pub fn shortest_paths(
+        &self,
+        roots: impl IntoIterator<Item = usize>,
+        target: usize,
+    ) -> Vec<Vec<usize>> {
+        // Compute the distance of every package to the target by walking the reverse edges.
+        let mut distance = vec![None; self.len()];
+        distance[target] = Some(0);
+        let mut queue = VecDeque::from([target]);
+        while let Some(idx) = queue.pop_front() {
+            let next = distance[idx].map(|d: usize| d + 1);
+            for dep in &self.dependents[idx] {
+                if distance[dep.package].is_none() {
+                    distance[dep.package] = next;
+                    queue.push_back(dep.package);
+                }
+            }
+        }
+
+        // Every edge that brings a package one step closer to the target is part of a shortest
+        // path, so the paths can be enumerated by only following those edges.
+        let mut paths = Vec::new();
+        let mut stack: Vec<Vec<usize>> = roots
+            .into_iter()
+            .filter(|&root| distance[root].is_some())
+            .map(|root| vec![root])
+            .rev()
+            .collect();
+        while let Some(path) = stack.pop() {
+            let last = *path.last().expect("paths are never empty");
+            if last == target {
+                paths.push(path);
+                continue;
+            }
+            let closer = distance[last].map(|d| d - 1);
+            for dep in self.dependencies[last].iter().rev() {
+                if distance[dep.package] == closer {
+                    let mut path = path.clone();
+                    path.push(dep.package);
+                    stack.push(path);
+                }
+            }
+        }
+        paths
+    }
+
+    /// Returns the spec with which package `from` depends on package `to`.
+    pub fn dependency_spec(&self, from: usize, to: usize) -> Option<&str> {
+        self.dependencies[from]
+            .iter()
+            .find(|dep| dep.package == to)
+            .map(|dep| dep.spec.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lock_file::fixtures::record;
+    use rattler_conda_types::{PackageName, Version};
+
+    #[test]
+    fn test_dependency_graph() {
+        let graph = DependencyGraph::new(
+            &[
+                record("pandas", "1.0", &["numpy >=1.22", "python >=3.9"]),
+                record("numpy", "1.0", &["python >=3.9", "libblas"]),
+                record("python", "1.0", &["__glibc >=2.17", "pip"]),
+                record("pip", "1.0", &["python >=3.7"]),
+            ],
+            &[],
+            &[GenericVirtualPackage {
+                name: PackageName::from_str("__glibc").unwrap(),
+                version: Version::from_str("2.17").unwrap(),
+                build_string: String::from("0"),
+            }],
+        );
+
+        let pandas = graph.find("pandas").unwrap();
+        let numpy = graph.find("numpy").unwrap();
+        let python = graph.find("python").unwrap();
+        let glibc = graph.find("__glibc").unwrap();
+        assert_eq!(graph.package(glibc).kind, PackageKind::Virtual);
+
+        // `libblas` is not locked so it is not part of the graph.
+        assert_eq!(
+            graph
+                .dependencies(numpy)
+                .iter()
+                .map(|dep| dep.spec.as_str())
+                .collect::<Vec<_>>(),
+            vec!["python >=3.9"]
+        );
+
+        // `python` and `pip` depend on each other.
+        assert_eq!(
+            graph
+                .dependents(python)
+                .iter()
+                .map(|dep| graph.package(dep.package).name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["pandas", "numpy", "pip"]
+        );
+        assert_eq!(graph.reachable([pandas]).len(), graph.len());
+    }
+
+    #[test]
+    fn test_shortest_paths() {
+        let graph = DependencyGraph::new(
+            &[
+                record("pandas", "1.0", &["numpy", "python"]),
+                record("numpy", "1.0", &["python", "libblas"]),
+                record("scipy", "1.0", &["numpy", "libblas"]),
+                record("libblas", "1.0", &["libopenblas"]),
+                record("libopenblas", "1.0", &[]),
+                record("python", "1.0", &[]),
+            ],
+            &[],
+            &[],
+        );
+        let names = |paths: Vec<Vec<usize>>| {
+            paths
+                .into_iter()
+                .map(|path| {
+                    path.into_iter()
+                        .map(|idx| graph.package(idx).name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" -> ")
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let pandas = graph.find("pandas").unwrap();
+        let scipy = graph.find("scipy").unwrap();
+        let python = graph.find("python").unwrap();
+        let libopenblas = graph.find("libopenblas").unwrap();
+
+        assert_eq!(
+            names(graph.shortest_paths([pandas, scipy], libopenblas)),
+            vec![
+                "pandas -> numpy -> libblas -> libopenblas",
+                "scipy -> libblas -> libopenblas",
+            ]
+        );
+        assert_eq!(
+            names(graph.shortest_paths([scipy, pandas, python], python)),
+            vec!["scipy -> numpy -> python", "pandas -> python", "python"]
+        );
+        assert_eq!(graph.dependency_spec(pandas, python), Some("python"));
+        assert_eq!(graph.dependency_spec(python, pandas), None);
+    }
+}
@@ -0,0 +1,142 @@
+//! Checks the licenses of the locked packages against the license policy of the project.
+
+use super::{graph::DependencyGraph, LockedCondaEnvironment, LockedPypiEnvironment};
+use crate::project::{manifest::LicenseStatus, Environment};
+use itertools::Itertools;
+use miette::Diagnostic;
+use rattler_conda_types::Platform;
+use thiserror::Error;
+
+/// The locked packages of an environment violate the license policy of the project.
+#[derive(Debug, Error, Diagnostic)]
+#[error("the environment `{environment}` contains packages with a license that is not allowed by the license policy")]
+#[diagnostic(help(
+    "remove the dependencies that require these packages or change the `[license-policy]` of the project"
+))]
+pub struct LicensePolicyError {
+    pub environment: String,
+
+    #[related]
+    pub violations: Vec<LicenseViolation>,
+}
+
+/// A locked package with a license that violates the license policy.
+#[derive(Debug, Error, Diagnostic)]
+#[error(
+    "{package} for {platform} has the license '{license}' which is {status} by the license policy"
+)]
+pub struct LicenseViolation {
+    pub package: String,
+    pub platform: Platform,
+    pub license: String,
+    pub status: LicenseStatus,
+
+    /// The shortest chain of dependencies from an explicit dependency to the package.
+    #[help]
+    pub dependency_path: Option<String>,
+}
+
+/// Checks the licenses of the locked conda packages of an environment against the license policy
+/// of the project. Pypi packages are not checked because their license is not part of the
+/// lock-file, see [`warn_unchecked_pypi_licenses`].
+pub fn check_license_policy(
+    environment: &Environment<'_>,
+    conda_packages: &LockedCondaEnvironment,
+) -> Result<(), LicensePolicyError> {
+    let policy = environment.project().license_policy();
+    if policy.is_empty() {
+        return Ok(());
+    }
+
+    let mut violations = Vec::new();
+    for (platform, records) in conda_packages
+        .iter()
+        .sorted_by_key(|(platform, _)| platform.as_str())
+    {
+        let mut graph = None;
+        for record in records {
+            let package = &record.package_record;
+            let status = policy.status(package.license.as_deref());
+            if !status.is_violation() {
+                continue;
+            }
+
+            let graph = graph.get_or_insert_with(|| DependencyGraph::new(records, &[], &[]));
+            violations.push(LicenseViolation {
+                package: format!("{} {}", package.name.as_normalized(), package.version),
+                platform: *platform,
+                license: package
+                    .license
+                    .clone()
+                    .unwrap_or_else(|| String::from("unknown")),
+                status,
+                dependency_path: dependency_path(
+                    graph,
+                    environment,
+                    *platform,
+                    package.name.as_normalized(),
+                )
+                .map(|path| format!("required through {path}")),
+            });
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(LicensePolicyError {
+            environment: environment.name().to_string(),
+            violations,
+        })
+    }
+}
+
+/// Warns about the pypi packages of an environment if the project has a license policy. Their
+/// licenses are not part of the lock-file, so they are not checked against the policy.
+pub fn warn_unchecked_pypi_licenses(
+    environment: &Environment<'_>,
+    pypi_packages: &LockedPypiEnvironment,
+) {
+    if environment.project().license_policy().is_empty() {
+        return;
+    }
+
+    let packages = pypi_packages
+        .values()
+        .flatten()
+        .map(|(data, _)| format!("{} {}", data.name, data.version))
+        .sorted()
+        .dedup()
+        .collect_vec();
+    if !packages.is_empty() {
+        tracing::warn!(
+            "the licenses of the pypi packages of the environment `{}` are not checked against the license policy: {}",
+            environment.name(),
+            packages.join(", ")
+        );
+    }
+}
+
+/// Formats the shortest chain of dependencies from an explicit dependency of the `environment` to
+/// the package with the given `name`, e.g. `pandas 2.2.0 -> numpy 1.26.4 -> libblas 3.9.0`.
+pub fn dependency_path(
+    graph: &DependencyGraph,
+    environment: &Environment<'_>,
+    platform: Platform,
+    name: &str,
+) -> Option<String> {
+    let target = graph.find(name)?;
+    let roots = graph
+        .explicit_packages(environment, platform)
+        .into_iter()
+        .sorted_by(|a, b| graph.package(*a).name.cmp(&graph.package(*b).name));
+    let path = graph
+        .shortest_paths(roots, target)
+        .into_iter()
+        .min_by_key(Vec::len)?;
+    Some(
+        path.into_iter()
+            .map(|idx| graph.package(idx).to_string())
+            .join(" -> "),
+    )
+}
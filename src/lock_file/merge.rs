@@ -0,0 +1,258 @@
+//! Resolves git merge conflicts in the lock-file.
+//!
+//! A conflicted lock-file is split into the two sides of the conflicts and the packages of both
+//! sides are combined. The combined packages are not necessarily consistent with each other or
+//! with the manifest, so `pixi install` and `pixi lock` use them as the preferred versions to solve
+//! every environment of the project again, which results in a clean lock-file.
+
+use super::{
+    build_lock_file, graph::normalize_pypi_name, update_lock_file_conda, update_lock_file_for_pypi,
+    LockedCondaEnvironment, LockedPypiEnvironment,
+};
+use crate::Project;
+use miette::{Context, IntoDiagnostic};
+use rattler_conda_types::RepoDataRecord;
+use rattler_lock::{LockFile, PypiPackageData, PypiPackageEnvironmentData};
+use std::collections::{BTreeSet, HashMap};
+
+/// Splits the contents of a file with git merge conflicts into "our" and "their" version of the
+/// file. The lines outside of the conflicts are part of both versions, the common ancestor of a
+/// `diff3` style conflict is dropped. Returns `None` if the contents contain no conflicts.
+pub fn split_conflicts(contents: &str) -> Option<(String, String)> {
+    enum Section {
+        Common,
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let mut ours = String::with_capacity(contents.len());
+    let mut theirs = String::with_capacity(contents.len());
+    let mut section = Section::Common;
+    let mut has_conflicts = false;
+    for line in contents.split_inclusive('\n') {
+        match section {
+            Section::Common if line.starts_with("<<<<<<<") => {
+                section = Section::Ours;
+                has_conflicts = true;
+            }
+            Section::Ours if line.starts_with("|||||||") => section = Section::Base,
+            Section::Ours | Section::Base if line.starts_with("=======") => {
+                section = Section::Theirs
+            }
+            Section::Theirs if line.starts_with(">>>>>>>") => section = Section::Common,
+            Section::Common => {
+                ours.push_str(line);
+                theirs.push_str(line);
+            }
+            Section::Ours => ours.push_str(line),
+            Section::Base => {}
+            Section::Theirs => theirs.push_str(line),
+        }
+    }
+
+    has_conflicts.then_some((ours, theirs))
+}
+
+/// Combines the packages of two lock-files. For every environment and platform the packages of
+/// both lock-files are kept, if both contain the same package the highest version is used. The
+/// result is not necessarily a consistent set of packages and should be solved again with
+/// [`solve_merged_lock_file`].
+pub fn merge_lock_files(ours: &LockFile, theirs: &LockFile) -> miette::Result<LockFile> {
+    let names: BTreeSet<_> = ours
+        .environments()
+        .chain(theirs.environments())
+        .map(|(name, _)| name.to_string())
+        .collect();
+
+    let mut builder = LockFile::builder();
+    for name in names {
+        let (our_channels, our_conda, our_pypi) = environment_packages(ours, &name)?;
+        let (their_channels, their_conda, their_pypi) = environment_packages(theirs, &name)?;
+
+        builder.set_channels(&name, our_channels.or(their_channels).unwrap_or_default());
+
+        for (platform, records) in merge_conda_packages(our_conda, their_conda) {
+            for record in records {
+                builder.add_conda_package(&name, platform, record.into());
+            }
+        }
+
+        for (platform, packages) in merge_pypi_packages(our_pypi, their_pypi) {
+            for (pkg_data, pkg_env_data) in packages {
+                builder.add_pypi_package(&name, platform, pkg_data, pkg_env_data);
+            }
+        }
+    }
+
+    Ok(builder.finish())
+}
+
+/// Solves all environments of the project again, preferring the versions of the packages in the
+/// `merged` lock-file, and returns the resulting lock-file.
+pub async fn solve_merged_lock_file(
+    project: &Project,
+    merged: &LockFile,
+) -> miette::Result<LockFile> {
+    let environments = project.environments();
+    let mut updated_environments = Vec::with_capacity(environments.len());
+    for environment in &environments {
+        let (locked_conda_packages, locked_pypi_packages) =
+            super::locked_packages(merged, environment)?;
+        let conda_packages = update_lock_file_conda(
+            environment,
+            environment.platforms(),
+            &locked_conda_packages,
+            None,
+        )
+        .await?;
+        let pypi_packages = if environment.has_pypi_dependencies() {
+            update_lock_file_for_pypi(
+                environment,
+                environment.platforms(),
+                &conda_packages,
+                &locked_pypi_packages,
+                None,
+                Default::default(),
+            )
+            .await?
+        } else {
+            Default::default()
+        };
+        updated_environments.push((environment, conda_packages, pypi_packages));
+    }
+
+    build_lock_file(project, merged, updated_environments)
+}
+
+/// Returns the channels and the packages of the environment with the given `name`, or `None` for
+/// the channels if the lock-file does not contain the environment.
+fn environment_packages(
+    lock_file: &LockFile,
+    name: &str,
+) -> miette::Result<(
+    Option<Vec<rattler_lock::Channel>>,
+    LockedCondaEnvironment,
+    LockedPypiEnvironment,
+)> {
+    let Some(environment) = lock_file.environment(name) else {
+        return Ok(Default::default());
+    };
+    let conda_packages = environment
+        .conda_repodata_records()
+        .into_diagnostic()
+        .context("failed to parse the contents of the lock-file")?;
+    Ok((
+        Some(environment.channels().to_vec()),
+        conda_packages,
+        environment.pypi_packages(),
+    ))
+}
+
+/// Combines the conda packages per platform, if both sides contain a package with the same name
+/// the highest version is kept. On a tie our package is kept.
+fn merge_conda_packages(
+    ours: LockedCondaEnvironment,
+    theirs: LockedCondaEnvironment,
+) -> LockedCondaEnvironment {
+    let mut result = ours;
+    for (platform, their_records) in theirs {
+        let records = result.entry(platform).or_default();
+        for record in their_records {
+            let name = record.package_record.name.as_normalized();
+            match records
+                .iter_mut()
+                .find(|ours| ours.package_record.name.as_normalized() == name)
+            {
+                Some(ours) => {
+                    if is_newer_conda_record(&record, ours) {
+                        *ours = record;
+                    }
+                }
+                None => records.push(record),
+            }
+        }
+    }
+    result
+}
+
+fn is_newer_conda_record(record: &RepoDataRecord, than: &RepoDataRecord) -> bool {
+    let (package, than) = (&record.package_record, &than.package_record);
+    (&*package.version, package.build_number) > (&*than.version, than.build_number)
+}
+
+/// Combines the pypi packages per platform, if both sides contain a package with the same
+/// normalized name the highest version is kept. On a tie our package is kept.
+fn merge_pypi_packages(
+    ours: LockedPypiEnvironment,
+    theirs: LockedPypiEnvironment,
+) -> LockedPypiEnvironment {
+    let mut result = ours;
+    for (platform, their_packages) in theirs {
+        let packages = result.entry(platform).or_default();
+        let mut index: HashMap<String, usize> = packages
+            .iter()
+            .enumerate()
+            .map(|(idx, (data, _))| (normalize_pypi_name(&data.name), idx))
+            .collect();
+        for package in their_packages {
+            let name = normalize_pypi_name(&package.0.name);
+            match index.get(&name) {
+                Some(&idx) => {
+                    if is_newer_pypi_package(&package, &packages[idx]) {
+                        packages[idx] = package;
+                    }
+                }
+                None => {
+                    index.insert(name, packages.len());
+                    packages.push(package);
+                }
+            }
+        }
+    }
+    result
+}
+
+fn is_newer_pypi_package(
+    (data, _): &(PypiPackageData, PypiPackageEnvironmentData),
+    (than, _): &(PypiPackageData, PypiPackageEnvironmentData),
+) -> bool {
+    data.version > than.version
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_conflicts() {
+        assert_eq!(split_conflicts("version: 4\nenvironments: {}\n"), None);
+
+        let contents = "\
+version: 4
+<<<<<<< HEAD
+- numpy 1.26.4
+=======
+- numpy 1.26.3
+- pandas 2.2.0
+>>>>>>> feature
+common: true
+<<<<<<< HEAD
+ours: true
+||||||| base
+base: true
+=======
+theirs: true
+>>>>>>> feature
+";
+        let (ours, theirs) = split_conflicts(contents).unwrap();
+        assert_eq!(
+            ours,
+            "version: 4\n- numpy 1.26.4\ncommon: true\nours: true\n"
+        );
+        assert_eq!(
+            theirs,
+            "version: 4\n- numpy 1.26.3\n- pandas 2.2.0\ncommon: true\ntheirs: true\n"
+        );
+    }
+}
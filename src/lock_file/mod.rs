@@ -1,16 +1,25 @@
+pub mod diff;
+#[cfg(test)]
+pub(crate) mod fixtures;
+pub mod graph;
+mod license;
+pub mod merge;
+mod overrides;
 mod package_identifier;
 mod pypi;
 mod pypi_name_mapping;
+mod pypi_upload_time;
 mod satisfiability;
 
 use crate::{progress, Project};
 use futures::TryStreamExt;
 use futures::{stream, StreamExt};
+use indexmap::IndexMap;
 use indicatif::ProgressBar;
 use itertools::{izip, Itertools};
 use miette::{Context, IntoDiagnostic};
 use rattler_conda_types::{
-    GenericVirtualPackage, MatchSpec, PackageName, Platform, RepoDataRecord,
+    Channel, GenericVirtualPackage, MatchSpec, PackageName, Platform, RepoDataRecord,
 };
 use rattler_lock::{
     LockFile, PackageHashes, PypiPackageData, PypiPackageDataRef, PypiPackageEnvironmentData,
@@ -20,10 +29,20 @@ use rattler_solve::{resolvo, SolverImpl};
 use rip::resolve::SDistResolution;
 use std::collections::HashMap;
 use std::path::Path;
+use std::str::FromStr;
 use std::{sync::Arc, time::Duration};
 
-use crate::project::Environment;
-pub use satisfiability::lock_file_satisfies_project;
+use crate::project::{
+    manifest::{ChannelPriority, ExcludeNewer, ExcludePattern},
+    Dependencies, Environment,
+};
+pub use license::{
+    check_license_policy, dependency_path, warn_unchecked_pypi_licenses, LicensePolicyError,
+    LicenseViolation,
+};
+pub use overrides::excluded_by;
+pub use pypi::amend_pypi_purls;
+pub use satisfiability::{lock_file_satisfies_project, verify_environment_satisfiability};
 
 /// A list of conda packages that are locked for a specific platform.
 pub type LockedCondaPackages = Vec<RepoDataRecord>;
@@ -47,16 +66,47 @@ pub type LockedPypiEnvironment = HashMap<Platform, LockedPypiPackages>;
 pub type LockedPypiEnvironmentRef<'p> = HashMap<Platform, LockedPypiPackagesRef<'p>>;
 
 /// Loads the lockfile for the specified project or returns a dummy one if none could be found.
+///
+/// If the lock-file contains git merge conflicts, the union of the packages of both sides of the
+/// conflicts is returned. Use [`load_lock_file_with_conflicts`] to find out whether that happened.
 pub async fn load_lock_file(project: &Project) -> miette::Result<LockFile> {
+    let (lock_file, has_conflicts) = load_lock_file_with_conflicts(project).await?;
+    if has_conflicts {
+        tracing::warn!("the lock-file contains merge conflicts, run `pixi lock` to resolve them");
+    }
+    Ok(lock_file)
+}
+
+/// Loads the lockfile for the specified project like [`load_lock_file`]. The returned boolean is
+/// true if the lock-file contains git merge conflicts, in that case the returned lock-file
+/// contains the union of the packages of both sides and should be re-solved with
+/// [`merge::solve_merged_lock_file`].
+pub async fn load_lock_file_with_conflicts(project: &Project) -> miette::Result<(LockFile, bool)> {
     let lock_file_path = project.lock_file_path();
-    if lock_file_path.is_file() {
-        // Spawn a background task because loading the file might be IO bound.
-        tokio::task::spawn_blocking(move || LockFile::from_path(&lock_file_path).into_diagnostic())
-            .await
-            .unwrap_or_else(|e| Err(e).into_diagnostic())
-    } else {
-        Ok(LockFile::default())
+    if !lock_file_path.is_file() {
+        return Ok((LockFile::default(), false));
     }
+
+    // Spawn a background task because loading the file might be IO bound.
+    tokio::task::spawn_blocking(move || {
+        let contents = std::fs::read_to_string(&lock_file_path)
+            .into_diagnostic()
+            .with_context(|| format!("failed to read {}", lock_file_path.display()))?;
+        match merge::split_conflicts(&contents) {
+            None => Ok((LockFile::from_str(&contents).into_diagnostic()?, false)),
+            Some((ours, theirs)) => {
+                let ours = LockFile::from_str(&ours)
+                    .into_diagnostic()
+                    .context("failed to parse our side of the conflicts in the lock-file")?;
+                let theirs = LockFile::from_str(&theirs)
+                    .into_diagnostic()
+                    .context("failed to parse their side of the conflicts in the lock-file")?;
+                Ok((merge::merge_lock_files(&ours, &theirs)?, true))
+            }
+        }
+    })
+    .await
+    .unwrap_or_else(|e| Err(e).into_diagnostic())
 }
 
 fn main_progress_bar(num_bars: u64, message: &'static str) -> ProgressBar {
@@ -86,13 +136,111 @@ fn platform_solve_bars(platforms: impl IntoIterator<Item = Platform>) -> Vec<Pro
         .collect_vec()
 }
 
-/// Updates the lock file for conda dependencies for the specified project.
+/// Returns the locked conda and pypi packages of `environment`. Both are empty if the environment
+/// is not part of the `lock_file`.
+pub fn locked_packages(
+    lock_file: &LockFile,
+    environment: &Environment<'_>,
+) -> miette::Result<(LockedCondaEnvironment, LockedPypiEnvironment)> {
+    let Some(locked_environment) = lock_file.environment(environment.name().as_str()) else {
+        return Ok(Default::default());
+    };
+    let conda_packages = locked_environment
+        .conda_repodata_records()
+        .into_diagnostic()
+        .context("failed to parse the contents of the lock-file. Try removing the lock-file and running again")?;
+    Ok((conda_packages, locked_environment.pypi_packages()))
+}
+
+/// The locked conda and pypi packages of an environment that replace the packages of the
+/// environment in the lock-file.
+pub type UpdatedEnvironment<'a, 'p> = (
+    &'a Environment<'p>,
+    LockedCondaEnvironment,
+    LockedPypiEnvironment,
+);
+
+/// Writes the lock-file of the project. The packages of the `updated_environments` are written as
+/// given, the other environments of the project are copied from the `existing_lock_file`.
+/// Environments that are no longer part of the project are dropped.
+pub fn write_lock_file(
+    project: &Project,
+    existing_lock_file: &LockFile,
+    updated_environments: Vec<UpdatedEnvironment<'_, '_>>,
+) -> miette::Result<()> {
+    build_lock_file(project, existing_lock_file, updated_environments)?
+        .to_path(&project.lock_file_path())
+        .into_diagnostic()
+        .context("failed to write updated lock-file to disk")
+}
+
+/// Constructs the lock-file of the project like [`write_lock_file`] without writing it to disk.
+pub fn build_lock_file(
+    project: &Project,
+    existing_lock_file: &LockFile,
+    updated_environments: Vec<UpdatedEnvironment<'_, '_>>,
+) -> miette::Result<LockFile> {
+    let mut updated_environments: HashMap<_, _> = updated_environments
+        .into_iter()
+        .map(|(environment, conda, pypi)| (environment.name().as_str().to_string(), (conda, pypi)))
+        .collect();
+
+    let mut builder = LockFile::builder();
+    for environment in project.environments() {
+        let name = environment.name().as_str();
+        let (channels, conda_packages, pypi_packages) = match updated_environments.remove(name) {
+            Some((conda_packages, pypi_packages)) => {
+                let channels = environment
+                    .channels()
+                    .into_iter()
+                    .map(|channel| rattler_lock::Channel::from(channel.base_url().to_string()))
+                    .collect_vec();
+                (channels, conda_packages, pypi_packages)
+            }
+            None => {
+                let Some(locked_environment) = existing_lock_file.environment(name) else {
+                    continue;
+                };
+                let conda_packages = locked_environment
+                    .conda_repodata_records()
+                    .into_diagnostic()
+                    .context("failed to parse the contents of the lock-file. Try removing the lock-file and running again")?;
+                (
+                    locked_environment.channels().to_vec(),
+                    conda_packages,
+                    locked_environment.pypi_packages(),
+                )
+            }
+        };
+
+        builder.set_channels(name, channels);
+
+        // Add the conda records
+        for (platform, records) in conda_packages {
+            for record in records {
+                builder.add_conda_package(name, platform, record.into());
+            }
+        }
+
+        // Add the PyPi records
+        for (platform, packages) in pypi_packages {
+            for (pkg_data, pkg_env_data) in packages {
+                builder.add_pypi_package(name, platform, pkg_data, pkg_env_data);
+            }
+        }
+    }
+
+    Ok(builder.finish())
+}
+
+/// Updates the lock file for conda dependencies of `environment` for the given `platforms`.
 pub async fn update_lock_file_conda(
     environment: &Environment<'_>,
+    platforms: impl IntoIterator<Item = Platform>,
     existing_lock_file: &LockedCondaEnvironment,
     repodata: Option<Vec<SparseRepoData>>,
 ) -> miette::Result<LockedCondaEnvironment> {
-    let platforms = environment.platforms();
+    let platforms = platforms.into_iter().collect_vec();
 
     // Get the repodata for the project
     let sparse_repo_data: Arc<[_]> = if let Some(sparse_repo_data) = repodata {
@@ -156,14 +304,17 @@ pub async fn update_lock_file_conda(
     result
 }
 
+/// Updates the lock file for pypi dependencies of `environment` for the given `platforms`. The
+/// resolver prefers the versions of the `locked_pypi_packages`.
 pub async fn update_lock_file_for_pypi(
     environment: &Environment<'_>,
+    platforms: impl IntoIterator<Item = Platform>,
     locked_conda_packages: &LockedCondaEnvironment,
     locked_pypi_packages: &LockedPypiEnvironment,
     python_location: Option<&Path>,
     sdist_resolution: SDistResolution,
 ) -> miette::Result<LockedPypiEnvironment> {
-    let platforms = environment.platforms().into_iter().collect_vec();
+    let platforms = platforms.into_iter().collect_vec();
 
     // Construct the progress bars
     let _top_level_progress =
@@ -241,7 +392,7 @@ pub async fn update_lock_file_for_pypi(
 async fn resolve_pypi(
     environment: &Environment<'_>,
     locked_conda_records: &[RepoDataRecord],
-    _locked_pypi_records: &[(PypiPackageData, PypiPackageEnvironmentData)],
+    locked_pypi_records: &[(PypiPackageData, PypiPackageEnvironmentData)],
     platform: Platform,
     pb: &ProgressBar,
     python_location: Option<&Path>,
@@ -253,6 +404,7 @@ async fn resolve_pypi(
         environment,
         platform,
         locked_conda_records,
+        locked_pypi_records,
         python_location,
         sdist_resolution,
     )
@@ -331,6 +483,7 @@ async fn resolve_platform(
         // TODO(baszalmstra): We should not need to clone here. We should be able to pass a reference to the data instead.
         existing_lock_file.clone(),
         available_packages,
+        &SolveOptions::new(environment, platform),
     )
     .await?;
 
@@ -342,26 +495,185 @@ async fn resolve_platform(
     Ok(records)
 }
 
+/// The settings of an environment that change how its conda packages are solved.
+struct SolveOptions<'p> {
+    /// Constraints on the versions of packages.
+    constraints: Dependencies,
+
+    /// Overrides of the requirements on packages.
+    overrides: Dependencies,
+
+    /// Patterns of the packages that must not be selected.
+    exclude: Vec<&'p ExcludePattern>,
+
+    /// Packages published after this cutoff must not be selected.
+    exclude_newer: Option<ExcludeNewer>,
+
+    /// The channels that packages are pinned to.
+    pinned_channels: IndexMap<PackageName, Arc<Channel>>,
+
+    /// How packages that are available in more than one channel are treated.
+    channel_priority: ChannelPriority,
+
+    /// The base urls of the channels, in order of priority.
+    channels: Vec<String>,
+}
+
+impl<'p> SolveOptions<'p> {
+    /// Returns the options of the `environment` for the given `platform`.
+    fn new(environment: &Environment<'p>, platform: Platform) -> Self {
+        Self {
+            constraints: environment.constraints(Some(platform)),
+            overrides: environment.overrides(Some(platform)),
+            exclude: environment.exclude(),
+            exclude_newer: environment.exclude_newer(),
+            pinned_channels: environment.pinned_channels(Some(platform)),
+            channel_priority: environment.channel_priority(),
+            channels: environment
+                .channels()
+                .into_iter()
+                .map(|channel| channel.base_url().to_string())
+                .collect(),
+        }
+    }
+}
+
 /// Solves the conda package environment for the given input. This function is async because it
 /// spawns a background task for the solver. Since solving is a CPU intensive task we do not want to
 /// block the main task.
+///
+/// The constraints, overrides, pinned channels and channel priority of the `options` are applied
+/// to the available and locked packages before solving. Packages that match one of the exclude patterns are never
+/// selected, neither are packages that were published after the `exclude-newer` cutoff.
 async fn resolve_conda_dependencies(
     specs: Vec<MatchSpec>,
     virtual_packages: Vec<GenericVirtualPackage>,
     locked_packages: Vec<RepoDataRecord>,
     available_packages: Vec<Vec<RepoDataRecord>>,
+    options: &SolveOptions<'_>,
 ) -> miette::Result<LockedCondaPackages> {
+    // Remember the original records, the changes made for the solve must not end up in the
+    // lock-file.
+    let original_records = available_packages
+        .iter()
+        .flatten()
+        .chain(locked_packages.iter())
+        .map(|record| (record.url.clone(), record.clone()))
+        .collect::<HashMap<_, _>>();
+
+    // Drop the packages that were published after the cutoff, also from the locked packages so
+    // they are not preferred.
+    let (available_packages, locked_packages) = match options.exclude_newer {
+        Some(exclude_newer) => {
+            let is_older =
+                |record: &RepoDataRecord| !exclude_newer.excludes(&record.package_record);
+            (
+                available_packages
+                    .into_iter()
+                    .map(|records| records.into_iter().filter(is_older).collect_vec())
+                    .collect_vec(),
+                locked_packages.into_iter().filter(is_older).collect_vec(),
+            )
+        }
+        None => (available_packages, locked_packages),
+    };
+
+    let (constraints, overrides) = (&options.constraints, &options.overrides);
+    let mut locked_packages =
+        overrides::apply_constraints_and_overrides(locked_packages, constraints, overrides);
+    let mut available_packages = available_packages
+        .into_iter()
+        .map(|records| overrides::apply_constraints_and_overrides(records, constraints, overrides))
+        .collect_vec();
+
+    overrides::apply_pinned_channels(
+        &mut available_packages,
+        &mut locked_packages,
+        &options.pinned_channels,
+    );
+    match options.channel_priority {
+        ChannelPriority::Strict => overrides::apply_strict_channel_priority(
+            &mut available_packages,
+            &mut locked_packages,
+            &options.channels,
+        ),
+        // The original channels of the records are restored after solving.
+        ChannelPriority::Disabled => {
+            for records in available_packages.iter_mut() {
+                overrides::pool_channels(records);
+            }
+            overrides::pool_channels(&mut locked_packages);
+        }
+    }
+    let exclude = options.exclude.as_slice();
+
+    // Remove the excluded packages.
+    let is_allowed = |record: &&RepoDataRecord| {
+        overrides::excluded_by(&record.package_record, exclude).is_none()
+    };
+    let allowed_packages = available_packages
+        .iter()
+        .map(|records| records.iter().filter(is_allowed).cloned().collect_vec())
+        .collect_vec();
+    let allowed_locked_packages = locked_packages
+        .iter()
+        .filter(is_allowed)
+        .cloned()
+        .collect_vec();
+
     // Construct a solver task that we can start solving.
     let task = rattler_solve::SolverTask {
-        specs,
-        available_packages: &available_packages,
-        locked_packages,
+        specs: specs.clone(),
+        available_packages: &allowed_packages,
+        locked_packages: allowed_locked_packages,
         pinned_packages: vec![],
-        virtual_packages,
+        virtual_packages: virtual_packages.clone(),
     };
 
     // Solve the task
-    resolvo::Solver.solve(task).into_diagnostic()
+    let mut records = match resolvo::Solver.solve(task) {
+        Ok(records) => records,
+        Err(err) if !exclude.is_empty() => {
+            // Solve again including the excluded packages to find out if the exclusions are the
+            // reason the environment cannot be solved.
+            let task = rattler_solve::SolverTask {
+                specs,
+                available_packages: &available_packages,
+                locked_packages,
+                pinned_packages: vec![],
+                virtual_packages,
+            };
+            let excluded = resolvo::Solver
+                .solve(task)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|record| {
+                    let pattern = overrides::excluded_by(&record.package_record, exclude)?;
+                    Some(format!(
+                        "  {} {} {} (excluded by `{pattern}`)",
+                        record.package_record.name.as_source(),
+                        record.package_record.version,
+                        record.package_record.build
+                    ))
+                })
+                .collect_vec();
+            if excluded.is_empty() {
+                return Err(err).into_diagnostic();
+            }
+            miette::bail!(
+                help = "remove the patterns from the `exclude` list to allow these packages",
+                "the environment can only be solved with packages that are excluded:\n{}",
+                excluded.join("\n")
+            );
+        }
+        Err(err) => return Err(err).into_diagnostic(),
+    };
+    for record in records.iter_mut() {
+        if let Some(original) = original_records.get(&record.url) {
+            *record = original.clone();
+        }
+    }
+    Ok(records)
 }
 
 /// Load the repodata records for the specified platform and package names in the background. This
@@ -394,3 +706,52 @@ async fn load_sparse_repo_data_async(
         )
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::record;
+    use super::*;
+
+    #[tokio::test]
+    async fn test_channel_priority() {
+        let internal = "https://example.com/internal/";
+        let conda_forge = "https://conda.anaconda.org/conda-forge/";
+        let mut internal_numpy = record("numpy", "1.26.4", &[]);
+        internal_numpy.channel = internal.to_string();
+        let available_packages = vec![vec![internal_numpy], vec![record("numpy", "2.0.0", &[])]];
+
+        let solve = |channel_priority| {
+            let options = SolveOptions {
+                constraints: Default::default(),
+                overrides: Default::default(),
+                exclude: Vec::new(),
+                exclude_newer: None,
+                pinned_channels: IndexMap::new(),
+                channel_priority,
+                channels: vec![internal.to_string(), conda_forge.to_string()],
+            };
+            let available_packages = available_packages.clone();
+            async move {
+                resolve_conda_dependencies(
+                    vec![MatchSpec::from_str("numpy").unwrap()],
+                    Vec::new(),
+                    Vec::new(),
+                    available_packages,
+                    &options,
+                )
+                .await
+                .unwrap()
+            }
+        };
+
+        let records = solve(ChannelPriority::Strict).await;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].package_record.version.as_str(), "1.26.4");
+
+        // The newest version of any channel is selected, with its original channel.
+        let records = solve(ChannelPriority::Disabled).await;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].package_record.version.as_str(), "2.0.0");
+        assert_eq!(records[0].channel, conda_forge);
+    }
+}
@@ -0,0 +1,277 @@
+//! Applies the `[constraints]`, `[overrides]`, `exclude` patterns, pinned channels and channel
+//! priority of an environment to the conda packages that are passed to the solver.
+//!
+//! Constraints restrict the versions of a package that can be selected without requiring the
+//! package, so records that do not satisfy the constraints are removed before solving. Overrides
+//! replace the requirements on a package in the `depends` and `constrains` of other packages.
+//! Excluded packages are never selected.
+
+use crate::project::{manifest::ExcludePattern, Dependencies};
+use indexmap::IndexMap;
+use rattler_conda_types::{Channel, MatchSpec, PackageName, PackageRecord, RepoDataRecord};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+use url::Url;
+
+/// Returns the specs that replace the requirement `spec` of another package, or `None` if the
+/// package `spec` refers to is not overridden.
+pub fn overridden_specs(spec: &MatchSpec, overrides: &Dependencies) -> Option<Vec<MatchSpec>> {
+    let name = spec.name.as_ref()?;
+    overrides
+        .iter()
+        .find(|(overridden, _)| *overridden == name)
+        .map(|(name, specs)| {
+            specs
+                .iter()
+                .map(|spec| MatchSpec::from_nameless(spec.clone(), Some(name.clone())))
+                .collect()
+        })
+}
+
+/// Returns the constraint that `record` does not satisfy, if any.
+pub fn unsatisfied_constraint(
+    record: &PackageRecord,
+    constraints: &Dependencies,
+) -> Option<MatchSpec> {
+    constraints
+        .iter()
+        .filter(|(name, _)| **name == record.name)
+        .flat_map(|(name, specs)| {
+            specs
+                .iter()
+                .map(|spec| MatchSpec::from_nameless(spec.clone(), Some(name.clone())))
+        })
+        .find(|spec| !spec.matches(record))
+}
+
+/// Returns the first of the `exclude` patterns that matches `record`, if any.
+pub fn excluded_by<'p>(
+    record: &PackageRecord,
+    exclude: &[&'p ExcludePattern],
+) -> Option<&'p ExcludePattern> {
+    exclude
+        .iter()
+        .copied()
+        .find(|pattern| pattern.matches(record))
+}
+
+/// Removes the records that do not satisfy the `constraints` and rewrites the requirements of
+/// the remaining records according to the `overrides`.
+pub fn apply_constraints_and_overrides(
+    records: Vec<RepoDataRecord>,
+    constraints: &Dependencies,
+    overrides: &Dependencies,
+) -> Vec<RepoDataRecord> {
+    records
+        .into_iter()
+        .filter(|record| unsatisfied_constraint(&record.package_record, constraints).is_none())
+        .map(|mut record| {
+            let package_record = &mut record.package_record;
+            package_record.depends = override_requirements(&package_record.depends, overrides);
+            package_record.constrains =
+                override_requirements(&package_record.constrains, overrides);
+            record
+        })
+        .collect()
+}
+
+/// Returns true if the package at `url` is part of `channel`.
+pub fn is_from_channel(url: &Url, channel: &Channel) -> bool {
+    url.as_str().starts_with(channel.base_url().as_str())
+}
+
+/// Removes the records of packages that are pinned to a channel but come from another channel from
+/// the `available` and `locked` records.
+pub fn apply_pinned_channels(
+    available: &mut [Vec<RepoDataRecord>],
+    locked: &mut Vec<RepoDataRecord>,
+    pinned_channels: &IndexMap<PackageName, Arc<Channel>>,
+) {
+    let is_allowed = |record: &RepoDataRecord| {
+        pinned_channels
+            .get(&record.package_record.name)
+            .map_or(true, |channel| is_from_channel(&record.url, channel))
+    };
+    for records in available.iter_mut() {
+        records.retain(is_allowed);
+    }
+    locked.retain(is_allowed);
+}
+
+/// Removes the records of packages that are also available from a channel with a higher priority
+/// from the `available` and `locked` records. `channels` are the base urls of the channels in
+/// order of priority, records of channels that are not in the list have the lowest priority.
+pub fn apply_strict_channel_priority(
+    available: &mut [Vec<RepoDataRecord>],
+    locked: &mut Vec<RepoDataRecord>,
+    channels: &[String],
+) {
+    let priority = |record: &RepoDataRecord| {
+        channels
+            .iter()
+            .position(|channel| channel == &record.channel)
+            .unwrap_or(channels.len())
+    };
+
+    // Find the channel with the highest priority for every package.
+    let mut best_channel = HashMap::new();
+    for record in available.iter().flatten() {
+        let best = best_channel
+            .entry(record.package_record.name.clone())
+            .or_insert(usize::MAX);
+        *best = (*best).min(priority(record));
+    }
+
+    let is_allowed = |record: &RepoDataRecord| {
+        best_channel
+            .get(&record.package_record.name)
+            .map_or(true, |best| *best == priority(record))
+    };
+    for records in available.iter_mut() {
+        records.retain(is_allowed);
+    }
+    locked.retain(is_allowed);
+}
+
+/// Makes all records appear to come from the same channel, so the solver does not prefer the
+/// records of one channel over those of another.
+///
+/// The solver has no option to disable channel priority: it only considers the records of a
+/// package from the first channel it finds the package in, based on the `channel` of the records.
+/// Clearing the channel therefore pools the records of all channels. The records must be replaced
+/// by the original records after solving, which `resolve_conda_dependencies` does based on their
+/// urls.
+pub fn pool_channels(records: &mut [RepoDataRecord]) {
+    for record in records {
+        record.channel = String::new();
+    }
+}
+
+/// Replaces the requirements on overridden packages. Requirements that cannot be parsed are kept
+/// as-is, the solver reports them.
+fn override_requirements(requirements: &[String], overrides: &Dependencies) -> Vec<String> {
+    requirements
+        .iter()
+        .flat_map(|requirement| {
+            match MatchSpec::from_str(requirement)
+                .ok()
+                .and_then(|spec| overridden_specs(&spec, overrides))
+            {
+                Some(specs) => specs.iter().map(ToString::to_string).collect(),
+                None => vec![requirement.clone()],
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lock_file::fixtures::record;
+    use rattler_conda_types::{NamelessMatchSpec, PackageName};
+
+    fn dependencies(specs: &[(&str, &str)]) -> Dependencies {
+        Dependencies::from(
+            specs
+                .iter()
+                .map(|(name, spec)| {
+                    (
+                        PackageName::from_str(name).unwrap(),
+                        NamelessMatchSpec::from_str(spec).unwrap(),
+                    )
+                })
+                .collect::<IndexMap<_, _>>(),
+        )
+    }
+
+    #[test]
+    fn test_apply_strict_channel_priority() {
+        let mut internal = record("numpy", "1.26.4", &[]);
+        internal.channel = String::from("https://example.com/internal/");
+        let mut available = vec![
+            vec![internal],
+            vec![
+                record("numpy", "2.0.0", &[]),
+                record("pandas", "2.2.0", &[]),
+            ],
+        ];
+        let mut locked = vec![record("numpy", "2.0.0", &[])];
+
+        apply_strict_channel_priority(
+            &mut available,
+            &mut locked,
+            &[
+                String::from("https://example.com/internal/"),
+                String::from("https://conda.anaconda.org/conda-forge/"),
+            ],
+        );
+        assert_eq!(
+            available
+                .iter()
+                .flatten()
+                .map(|record| record.file_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["numpy-1.26.4-0.conda", "pandas-2.2.0-0.conda"]
+        );
+        assert!(locked.is_empty());
+    }
+
+    #[test]
+    fn test_apply_pinned_channels() {
+        let mut internal = record("mylib", "1.2.0", &[]);
+        internal.url =
+            Url::parse("https://conda.anaconda.org/my-internal/noarch/mylib-1.2.0-0.conda")
+                .unwrap();
+        let mut available = vec![
+            vec![internal],
+            vec![record("mylib", "1.3.0", &[]), record("numpy", "2.0.0", &[])],
+        ];
+        let mut locked = vec![record("mylib", "1.3.0", &[])];
+
+        let channel = Channel::from_str(
+            "my-internal",
+            &rattler_conda_types::ChannelConfig::default(),
+        )
+        .unwrap();
+        apply_pinned_channels(
+            &mut available,
+            &mut locked,
+            &IndexMap::from([(PackageName::from_str("mylib").unwrap(), Arc::new(channel))]),
+        );
+        assert_eq!(
+            available
+                .iter()
+                .flatten()
+                .map(|record| record.file_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["mylib-1.2.0-0.conda", "numpy-2.0.0-0.conda"]
+        );
+        assert!(locked.is_empty());
+    }
+
+    #[test]
+    fn test_apply_constraints_and_overrides() {
+        let records = vec![
+            record("numpy", "1.26.4", &["python >=3.9"]),
+            record("numpy", "2.0.0", &["python >=3.9"]),
+            record("pandas", "2.2.0", &["numpy >=1.22,<2", "python >=3.9"]),
+        ];
+
+        let records = apply_constraints_and_overrides(
+            records,
+            &dependencies(&[("numpy", ">=2")]),
+            &dependencies(&[("numpy", ">=1.22")]),
+        );
+
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| record.file_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["numpy-2.0.0-0.conda", "pandas-2.2.0-0.conda"]
+        );
+        assert_eq!(
+            records[1].package_record.depends,
+            vec!["numpy >=1.22", "python >=3.9"]
+        );
+    }
+}
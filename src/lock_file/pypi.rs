@@ -1,14 +1,18 @@
 use crate::consts::PROJECT_MANIFEST;
-use crate::lock_file::{package_identifier, pypi_name_mapping};
+use crate::lock_file::{package_identifier, pypi_name_mapping, pypi_upload_time::UploadCutoff};
 use crate::project::Environment;
 use crate::pypi_marker_env::determine_marker_environment;
 use crate::pypi_tags::{is_python_record, project_platform_tags};
 use itertools::Itertools;
 use miette::{Context, IntoDiagnostic};
 use rattler_conda_types::{Platform, RepoDataRecord};
+use rattler_lock::{PypiPackageData, PypiPackageEnvironmentData};
+use rip::index::PackageDb;
 use rip::python_env::PythonLocation;
 use rip::resolve::{resolve, PinnedPackage, ResolveOptions, SDistResolution};
+use rip::types::NormalizedPackageName;
 use std::path::Path;
+use std::str::FromStr;
 use std::{collections::HashMap, vec};
 
 /// Resolve python packages for the specified project.
@@ -16,6 +20,7 @@ pub async fn resolve_dependencies<'p>(
     environment: &Environment<'p>,
     platform: Platform,
     conda_packages: &[RepoDataRecord],
+    favored_packages: &[(PypiPackageData, PypiPackageEnvironmentData)],
     python_location: Option<&Path>,
     sdist_resolution: SDistResolution,
 ) -> miette::Result<Vec<PinnedPackage<'p>>> {
@@ -69,6 +74,7 @@ pub async fn resolve_dependencies<'p>(
         .iter()
         .flat_map(|(name, req)| req.iter().map(move |req| (name, req)))
         .map(|(name, req)| req.as_pep508(name))
+        .filter(|req| req.evaluate_markers(&marker_environment, Vec::new()))
         .collect::<Vec<pep508_rs::Requirement>>();
 
     // If we only have a system python
@@ -81,29 +87,91 @@ pub async fn resolve_dependencies<'p>(
     };
 
     // Resolve the PyPi dependencies
-    let mut result = resolve(
-        environment.project().pypi_package_db()?,
-        &requirements,
-        &marker_environment,
-        Some(&compatible_tags),
-        conda_python_packages
-            .into_iter()
-            .map(|p| (p.name.clone(), p))
-            .collect(),
-        HashMap::default(),
-        &ResolveOptions {
-            sdist_resolution,
-            python_location,
-            clean_env: false,
-        },
-        HashMap::default(),
-    )
-    .await?;
+    let project = environment.project();
+    let package_db = project.pypi_package_db()?;
+    let locked_packages: HashMap<_, _> = conda_python_packages
+        .into_iter()
+        .map(|p| (p.name.clone(), p))
+        .collect();
+    let favored_packages = favored(package_db, favored_packages).await?;
+    let options = ResolveOptions {
+        sdist_resolution,
+        python_location,
+        clean_env: false,
+    };
+    let mut upload_cutoff = environment.exclude_newer().map(UploadCutoff::new);
+    loop {
+        let excluded_versions = match &upload_cutoff {
+            Some(upload_cutoff) => upload_cutoff.requirements()?,
+            None => Vec::new(),
+        };
+        let mut result = resolve(
+            package_db,
+            requirements.iter().chain(&excluded_versions),
+            &marker_environment,
+            Some(&compatible_tags),
+            locked_packages.clone(),
+            favored_packages.clone(),
+            &options,
+            HashMap::default(),
+        )
+        .await?;
 
-    // Remove any conda package from the result
-    result.retain(|p| !p.artifacts.is_empty());
+        // Remove any conda package from the result
+        result.retain(|p| !p.artifacts.is_empty());
+
+        // Only keep the artifacts from before the `exclude-newer` cutoff, if a selected version
+        // has none left it is excluded and the dependencies are resolved again.
+        let Some(upload_cutoff) = &mut upload_cutoff else {
+            return Ok(result);
+        };
+        if upload_cutoff
+            .apply(
+                project.client(),
+                &project.pypi_index_urls(),
+                package_db,
+                &mut result,
+            )
+            .await?
+        {
+            return Ok(result);
+        }
+    }
+}
 
-    Ok(result)
+/// Returns the `locked` packages in the form the resolver prefers them in. A locked package is
+/// only preferred if the artifact it was locked with is still available from the index, the
+/// resolver then uses that artifact.
+async fn favored<'db>(
+    package_db: &'db PackageDb,
+    locked: &[(PypiPackageData, PypiPackageEnvironmentData)],
+) -> miette::Result<HashMap<NormalizedPackageName, PinnedPackage<'db>>> {
+    let mut favored = HashMap::with_capacity(locked.len());
+    for (data, _) in locked {
+        let Ok(name) = rip::types::PackageName::from_str(&data.name) else {
+            continue;
+        };
+        let name = NormalizedPackageName::from(name);
+        let artifacts = package_db.available_artifacts(name.clone()).await?;
+        let Some(artifact) = artifacts
+            .get(&data.version)
+            .into_iter()
+            .flatten()
+            .find(|artifact| artifact.url == data.url)
+        else {
+            continue;
+        };
+        favored.insert(
+            name.clone(),
+            PinnedPackage {
+                name,
+                version: data.version.clone(),
+                extras: Default::default(),
+                artifacts: vec![artifact],
+            },
+        );
+    }
+    Ok(favored)
 }
 
 /// Amend the records with pypi purls if they are not present yet.
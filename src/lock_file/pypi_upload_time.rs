@@ -0,0 +1,259 @@
+//! Applies the `exclude-newer` cutoff to pypi packages.
+//!
+//! The package database of rip does not know when artifacts were uploaded, so the upload times are
+//! read from the JSON simple API of the index ([PEP 691](https://peps.python.org/pep-0691/) and
+//! [PEP 700](https://peps.python.org/pep-0700/)). The resolver can not filter artifacts itself,
+//! instead the versions of which every artifact was uploaded after the cutoff are excluded with
+//! extra requirements and the dependencies are resolved again until no excluded version is
+//! selected.
+
+use crate::project::manifest::ExcludeNewer;
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use miette::{IntoDiagnostic, WrapErr};
+use percent_encoding::percent_decode_str;
+use reqwest::{
+    header::{ACCEPT, CONTENT_TYPE},
+    Client, StatusCode,
+};
+use rip::{
+    index::PackageDb,
+    resolve::PinnedPackage,
+    types::{ArtifactInfo, NormalizedPackageName, Version},
+};
+use serde::Deserialize;
+use std::{collections::HashMap, str::FromStr};
+use url::Url;
+
+/// The content type of the JSON simple API.
+const SIMPLE_JSON_CONTENT_TYPE: &str = "application/vnd.pypi.simple.v1+json";
+
+#[derive(Deserialize)]
+struct ProjectPage {
+    files: Vec<ProjectFile>,
+}
+
+#[derive(Deserialize)]
+struct ProjectFile {
+    filename: String,
+    #[serde(rename = "upload-time")]
+    upload_time: Option<String>,
+}
+
+/// The upload times of the artifacts of a package by their filename.
+type UploadTimes = HashMap<String, DateTime<Utc>>;
+
+/// Removes the pypi artifacts that were uploaded after the `exclude-newer` cutoff from resolved
+/// packages and keeps track of the versions that must not be selected.
+pub struct UploadCutoff {
+    exclude_newer: ExcludeNewer,
+
+    /// The upload times of the artifacts of every package that was checked.
+    upload_times: HashMap<NormalizedPackageName, UploadTimes>,
+
+    /// The versions of every checked package that have no artifact from before the cutoff.
+    excluded_versions: HashMap<NormalizedPackageName, Vec<Version>>,
+}
+
+impl UploadCutoff {
+    pub fn new(exclude_newer: ExcludeNewer) -> Self {
+        Self {
+            exclude_newer,
+            upload_times: HashMap::new(),
+            excluded_versions: HashMap::new(),
+        }
+    }
+
+    /// Returns the requirements that keep the resolver from selecting the excluded versions.
+    pub fn requirements(&self) -> miette::Result<Vec<pep508_rs::Requirement>> {
+        self.excluded_versions
+            .iter()
+            .filter(|(_, versions)| !versions.is_empty())
+            .sorted_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()))
+            .map(|(name, versions)| {
+                let requirement = format!(
+                    "{} {}",
+                    name.as_str(),
+                    versions
+                        .iter()
+                        .map(|version| format!("!={version}"))
+                        .join(", ")
+                );
+                pep508_rs::Requirement::from_str(&requirement)
+                    .into_diagnostic()
+                    .with_context(|| format!("failed to exclude the versions of `{name}`"))
+            })
+            .collect()
+    }
+
+    /// Removes the artifacts that were uploaded after the cutoff from the resolved `packages`.
+    /// Returns false if a selected version has no artifact left, that version is excluded from
+    /// then on and the packages have to be resolved again. Artifacts of which the index does not
+    /// report the upload time are kept.
+    pub async fn apply<'db>(
+        &mut self,
+        client: &Client,
+        index_urls: &[Url],
+        package_db: &'db PackageDb,
+        packages: &mut [PinnedPackage<'db>],
+    ) -> miette::Result<bool> {
+        let mut is_complete = true;
+        for package in packages.iter_mut() {
+            if !self.upload_times.contains_key(&package.name) {
+                let upload_times = fetch_upload_times(client, index_urls, &package.name).await?;
+                let available_artifacts =
+                    package_db.available_artifacts(package.name.clone()).await?;
+                let excluded_versions = available_artifacts
+                    .iter()
+                    .filter(|(_, artifacts)| {
+                        !artifacts.is_empty()
+                            && artifacts
+                                .iter()
+                                .all(|artifact| self.is_excluded(&upload_times, artifact))
+                    })
+                    .map(|(version, _)| version.clone())
+                    .collect();
+                if available_artifacts
+                    .values()
+                    .flatten()
+                    .any(|artifact| upload_time(&upload_times, artifact).is_none())
+                {
+                    tracing::warn!(
+                        "the index does not report when all artifacts of `{}` were uploaded, the `exclude-newer` cutoff is not applied to those artifacts",
+                        package.name
+                    );
+                }
+                self.excluded_versions
+                    .insert(package.name.clone(), excluded_versions);
+                self.upload_times.insert(package.name.clone(), upload_times);
+            }
+
+            let upload_times = &self.upload_times[&package.name];
+            let exclude_newer = self.exclude_newer;
+            package.artifacts.retain(|artifact| {
+                upload_time(upload_times, artifact)
+                    .map_or(true, |time| !exclude_newer.excludes_timestamp(time))
+            });
+            if package.artifacts.is_empty() {
+                let excluded_versions = self
+                    .excluded_versions
+                    .entry(package.name.clone())
+                    .or_default();
+                if !excluded_versions.contains(&package.version) {
+                    excluded_versions.push(package.version.clone());
+                }
+                is_complete = false;
+            }
+        }
+        Ok(is_complete)
+    }
+
+    fn is_excluded(&self, upload_times: &UploadTimes, artifact: &ArtifactInfo) -> bool {
+        upload_time(upload_times, artifact)
+            .map_or(false, |time| self.exclude_newer.excludes_timestamp(time))
+    }
+}
+
+/// Returns the upload time of the `artifact`, looked up by the filename in its url.
+fn upload_time(upload_times: &UploadTimes, artifact: &ArtifactInfo) -> Option<DateTime<Utc>> {
+    let filename = artifact.url.path_segments()?.last()?;
+    let filename = percent_decode_str(filename).decode_utf8().ok()?;
+    upload_times.get(filename.as_ref()).copied()
+}
+
+/// Downloads the upload times of the artifacts of the package `name` from the JSON simple API of
+/// the `index_urls`. Indexes that only serve the HTML simple API or do not report upload times do
+/// not contribute to the result.
+async fn fetch_upload_times(
+    client: &Client,
+    index_urls: &[Url],
+    name: &NormalizedPackageName,
+) -> miette::Result<UploadTimes> {
+    let mut upload_times = UploadTimes::new();
+    for index_url in index_urls {
+        let url = index_url
+            .join(&format!("{}/", name.as_str()))
+            .into_diagnostic()?;
+        let response = client
+            .get(url.clone())
+            .header(ACCEPT, SIMPLE_JSON_CONTENT_TYPE)
+            .send()
+            .await
+            .into_diagnostic()
+            .with_context(|| format!("failed to fetch {url}"))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            continue;
+        }
+        let response = response
+            .error_for_status()
+            .into_diagnostic()
+            .with_context(|| format!("failed to fetch {url}"))?;
+        let is_json = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .map_or(false, |content_type| {
+                content_type.starts_with(SIMPLE_JSON_CONTENT_TYPE)
+            });
+        if !is_json {
+            continue;
+        }
+
+        let page: ProjectPage = response
+            .json()
+            .await
+            .into_diagnostic()
+            .with_context(|| format!("failed to parse {url}"))?;
+        upload_times.extend(page.files.into_iter().filter_map(|file| {
+            let upload_time = DateTime::parse_from_rfc3339(file.upload_time.as_deref()?).ok()?;
+            Some((file.filename, upload_time.with_timezone(&Utc)))
+        }));
+    }
+    Ok(upload_times)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_requirements() {
+        let mut cutoff = UploadCutoff::new(ExcludeNewer::from_str("2024-01-01").unwrap());
+        cutoff.excluded_versions.insert(
+            NormalizedPackageName::from_str("numpy").unwrap(),
+            vec![
+                Version::from_str("2.0.0").unwrap(),
+                Version::from_str("1.26.4").unwrap(),
+            ],
+        );
+        cutoff.excluded_versions.insert(
+            NormalizedPackageName::from_str("pandas").unwrap(),
+            Vec::new(),
+        );
+        assert_eq!(
+            cutoff
+                .requirements()
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["numpy !=2.0.0, !=1.26.4"]
+        );
+
+        let upload_times = UploadTimes::from([(
+            String::from("numpy-1.26.4+local-py3-none-any.whl"),
+            Utc.with_ymd_and_hms(2023, 12, 1, 0, 0, 0).unwrap(),
+        )]);
+        let artifact: ArtifactInfo = serde_json::from_value(serde_json::json!({
+            "filename": "numpy-1.26.4+local-py3-none-any.whl",
+            "url": "https://files.pythonhosted.org/packages/numpy-1.26.4%2Blocal-py3-none-any.whl",
+        }))
+        .unwrap();
+        assert_eq!(
+            upload_time(&upload_times, &artifact),
+            Some(Utc.with_ymd_and_hms(2023, 12, 1, 0, 0, 0).unwrap())
+        );
+        assert!(!cutoff.is_excluded(&upload_times, &artifact));
+    }
+}
@@ -1,4 +1,4 @@
-use super::package_identifier;
+use super::{overrides, package_identifier};
 use crate::{
     project::Environment, pypi_marker_env::determine_marker_environment,
     pypi_tags::is_python_record, Project,
@@ -48,6 +48,15 @@ pub enum PlatformUnsat {
     #[error("failed to parse requirement '{0}'")]
     FailedToParseMatchSpec(String, #[source] ParseMatchSpecError),
 
+    #[error("the locked package '{0}' is excluded by the pattern '{1}'")]
+    ExcludedPackage(String, String),
+
+    #[error("the locked package '{0}' was published after the `exclude-newer` cutoff {1}")]
+    PublishedAfterCutoff(String, String),
+
+    #[error("the locked package '{0}' is not from the channel '{1}' it is pinned to")]
+    ChannelMismatch(String, String),
+
     #[error("too many conda packages in the lock-file")]
     TooManyCondaPackages,
 
@@ -193,6 +202,59 @@ pub fn verify_conda_platform_satisfiability(
         .map(|vpkg| (vpkg.name.clone(), vpkg))
         .collect::<HashMap<_, _>>();
 
+    // Every locked package has to satisfy the constraints of the environment.
+    let constraints = environment.constraints(Some(platform));
+    for record in locked_environment {
+        if let Some(spec) = overrides::unsatisfied_constraint(record.package_record(), &constraints)
+        {
+            return Err(PlatformUnsat::UnsatisfiableMatchSpec(
+                spec,
+                String::from("<constraints>"),
+            ));
+        }
+    }
+
+    // None of the locked packages may be excluded.
+    let exclude = environment.exclude();
+    for record in locked_environment {
+        if let Some(pattern) = overrides::excluded_by(record.package_record(), &exclude) {
+            return Err(PlatformUnsat::ExcludedPackage(
+                record.package_record().name.as_source().to_string(),
+                pattern.to_string(),
+            ));
+        }
+    }
+
+    // None of the locked packages may be published after the cutoff.
+    if let Some(exclude_newer) = environment.exclude_newer() {
+        if let Some(record) = locked_environment
+            .iter()
+            .find(|record| exclude_newer.excludes(record.package_record()))
+        {
+            return Err(PlatformUnsat::PublishedAfterCutoff(
+                record.package_record().name.as_source().to_string(),
+                exclude_newer.to_string(),
+            ));
+        }
+    }
+
+    // Packages that are pinned to a channel must come from that channel.
+    let pinned_channels = environment.pinned_channels(Some(platform));
+    for record in locked_environment {
+        let Some(channel) = pinned_channels.get(&record.package_record().name) else {
+            continue;
+        };
+        if !overrides::is_from_channel(record.url(), channel) {
+            return Err(PlatformUnsat::ChannelMismatch(
+                record.package_record().name.as_source().to_string(),
+                channel.base_url().to_string(),
+            ));
+        }
+    }
+
+    // The requirements of packages on overridden packages are replaced by the overrides.
+    let overrides = environment.overrides(Some(platform));
+
     // Keep a list of all records we have seen.
     let mut records_visited = HashSet::new();
 
@@ -263,7 +325,10 @@ pub fn verify_conda_platform_satisfiability(
         for depends in &record.package_record().depends {
             let spec = MatchSpec::from_str(depends.as_str())
                 .map_err(|e| PlatformUnsat::FailedToParseMatchSpec(depends.clone(), e))?;
-            specs.push((spec, source))
+            match overrides::overridden_specs(&spec, &overrides) {
+                Some(overridden) => specs.extend(overridden.into_iter().map(|spec| (spec, source))),
+                None => specs.push((spec, source)),
+            }
         }
     }
 
@@ -325,6 +390,9 @@ pub fn verify_pypi_platform_satisfiability(
             Err(e) => return Err(PlatformUnsat::FailedToDetermineMarkerEnvironment(e.into())),
         };
 
+    // Only the requirements of which the environment markers match the platform are required.
+    requirements.retain(|(req, _source)| req.evaluate_markers(&marker_environment, Vec::new()));
+
     // Keep a list of all requirements we have seen so we don't check them again.
     let mut requirements_visited = requirements
         .iter()
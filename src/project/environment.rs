@@ -1,17 +1,22 @@
 use super::{
     dependencies::Dependencies,
     errors::{UnknownTask, UnsupportedPlatformError},
-    manifest::{self, EnvironmentName, Feature, FeatureName, SystemRequirements},
+    manifest::{
+        self, ChannelPriority, EnvironmentName, ExcludeNewer, ExcludePattern, Feature, FeatureName,
+        Manifest, SystemRequirements,
+    },
     PyPiRequirement, SpecType,
 };
 use crate::{task::Task, Project};
 use indexmap::{IndexMap, IndexSet};
 use itertools::{Either, Itertools};
-use rattler_conda_types::{Channel, Platform};
+use rattler_conda_types::{Channel, PackageName, Platform};
 use std::{
     borrow::Cow,
     collections::{HashMap, HashSet},
     fmt::Debug,
+    path::Path,
+    sync::Arc,
 };
 
 /// Describes a single environment from a project manifest. This is used to describe environments
@@ -70,22 +75,58 @@ impl<'p> Environment<'p> {
     }
 
     /// Returns references to the features that make up this environment. The default feature is
-    /// always added at the end.
+    /// always added at the end. If the project defines a workspace, the features of the workspace
+    /// members are added right before it, see [`Self::member_features`].
     pub fn features(&self) -> impl Iterator<Item = &'p Feature> + DoubleEndedIterator + '_ {
-        self.environment
-            .features
+        self.named_features()
+            .chain(self.member_features())
+            .chain([self.project.manifest.default_feature()])
+    }
+
+    /// Returns references to the features of the workspace members that are part of this
+    /// environment. For every member these are its features with the same name as one of the
+    /// features of this environment, followed by its default feature. Features of members that
+    /// are not used by this environment, like the `optional-dependencies` of a `pyproject.toml`
+    /// that no environment asks for, are not included.
+    fn member_features(&self) -> impl Iterator<Item = &'p Feature> + DoubleEndedIterator + '_ {
+        let feature_names = &self.environment.features;
+        self.project
+            .workspace_members()
             .iter()
-            .map(|feature_name| {
-                self.project
-                    .manifest
-                    .parsed
-                    .features
-                    .get(&FeatureName::Named(feature_name.clone()))
-                    .expect("feature usage should have been validated upfront")
+            .flat_map(move |member| {
+                feature_names
+                    .iter()
+                    .filter_map(|name| {
+                        member
+                            .parsed
+                            .features
+                            .get(&FeatureName::Named(name.clone()))
+                    })
+                    .chain([member.default_feature()])
             })
+    }
+
+    /// Returns references to the features of the project itself that make up this environment,
+    /// the features of workspace members are not included. The default feature is always added at
+    /// the end.
+    fn project_features(&self) -> impl Iterator<Item = &'p Feature> + DoubleEndedIterator + '_ {
+        self.named_features()
             .chain([self.project.manifest.default_feature()])
     }
 
+    /// Returns references to the named features that make up this environment in the order they
+    /// are defined for the environment.
+    fn named_features(&self) -> impl Iterator<Item = &'p Feature> + DoubleEndedIterator + '_ {
+        self.environment.features.iter().map(|feature_name| {
+            self.project
+                .manifest
+                .parsed
+                .features
+                .get(&FeatureName::Named(feature_name.clone()))
+                .expect("feature usage should have been validated upfront")
+        })
+    }
+
     /// Returns the channels associated with this environment.
     ///
     /// Users can specify custom channels on a per feature basis. This method collects and
@@ -120,6 +161,18 @@ impl<'p> Environment<'p> {
             .collect()
     }
 
+    /// Returns how the solver treats packages that are available in more than one of the
+    /// [`Self::channels`] of this environment.
+    pub fn channel_priority(&self) -> ChannelPriority {
+        self.project.manifest.parsed.project.channel_priority
+    }
+
+    /// Returns the cutoff after which published packages are not selected by the solver for this
+    /// environment, if any.
+    pub fn exclude_newer(&self) -> Option<ExcludeNewer> {
+        self.project.manifest.parsed.project.exclude_newer
+    }
+
     /// Returns the platforms that this environment is compatible with.
     ///
     /// Which platforms an environment support depends on which platforms the selected features of
@@ -147,7 +200,9 @@ impl<'p> Environment<'p> {
 
     /// Returns the tasks defined for this environment.
     ///
-    /// Tasks are defined on a per-target per-feature per-environment basis.
+    /// Tasks are defined on a per-target per-feature per-environment basis. The tasks of workspace
+    /// members are not included, except for the tasks of the member the project was loaded from.
+    /// Those take precedence over the tasks of the project itself.
     ///
     /// If a `platform` is specified but this environment doesn't support the specified platform,
     /// an [`UnsupportedPlatformError`] error is returned.
@@ -157,7 +212,11 @@ impl<'p> Environment<'p> {
     ) -> Result<HashMap<&'p str, &'p Task>, UnsupportedPlatformError> {
         self.validate_platform_support(platform)?;
         let result = self
-            .features()
+            .project
+            .current_member()
+            .map(Manifest::default_feature)
+            .into_iter()
+            .chain(self.project_features())
             .flat_map(|feature| feature.targets.resolve(platform))
             .rev() // Reverse to get the most specific targets last.
             .flat_map(|target| target.tasks.iter())
@@ -207,6 +266,55 @@ impl<'p> Environment<'p> {
             .unwrap_or_default()
     }
 
+    /// Returns the channels that conda dependencies of this environment are pinned to for the
+    /// given `platform`, e.g. `mylib = { version = ">=1.2", channel = "my-internal" }`. These
+    /// packages must only be taken from that channel.
+    pub fn pinned_channels(
+        &self,
+        platform: Option<Platform>,
+    ) -> IndexMap<PackageName, Arc<Channel>> {
+        self.dependencies(None, platform)
+            .into_specs()
+            .filter_map(|(name, spec)| Some((name, spec.channel?)))
+            .collect()
+    }
+
+    /// Returns the patterns of the packages that must not be selected by the solver for this
+    /// environment. These are the patterns of the project followed by those of its features.
+    pub fn exclude(&self) -> Vec<&'p ExcludePattern> {
+        self.project
+            .manifest
+            .parsed
+            .project
+            .exclude
+            .iter()
+            .chain(self.features().flat_map(|feature| feature.exclude.iter()))
+            .unique()
+            .collect()
+    }
+
+    /// Returns the constraints on the versions of conda packages of this environment.
+    ///
+    /// The constraints of all features are combined, a package has to satisfy all the constraints
+    /// that are defined for it.
+    pub fn constraints(&self, platform: Option<Platform>) -> Dependencies {
+        self.features()
+            .map(|f| Dependencies::from(f.constraints(platform)))
+            .reduce(|acc, deps| acc.union(&deps))
+            .unwrap_or_default()
+    }
+
+    /// Returns the overrides of conda packages of this environment.
+    ///
+    /// The overrides of all features are combined. A requirement on an overridden package is
+    /// replaced by all the overrides that are defined for it.
+    pub fn overrides(&self, platform: Option<Platform>) -> Dependencies {
+        self.features()
+            .map(|f| Dependencies::from(f.overrides(platform)))
+            .reduce(|acc, deps| acc.union(&deps))
+            .unwrap_or_default()
+    }
+
     /// Returns the PyPi dependencies to install for this environment.
     ///
     /// The dependencies of all features are combined. This means that if two features define a
@@ -238,15 +346,49 @@ impl<'p> Environment<'p> {
             })
     }
 
+    /// Returns the default feature of the workspace member the project was loaded from, together
+    /// with the directory of the member relative to the project root.
+    fn current_member_feature(&self) -> Option<(&'p Path, &'p Feature)> {
+        self.project.current_member().map(|member| {
+            let member_dir = member
+                .path
+                .parent()
+                .and_then(|dir| dir.strip_prefix(self.project.root()).ok())
+                .unwrap_or(Path::new(""));
+            (member_dir, member.default_feature())
+        })
+    }
+
     /// Returns the activation scripts that should be run when activating this environment.
     ///
     /// The activation scripts of all features are combined in the order they are defined for the
-    /// environment.
+    /// environment. The activation scripts of the workspace member the project was loaded from are
+    /// added at the end, relative to the project root.
     pub fn activation_scripts(&self, platform: Option<Platform>) -> Vec<String> {
-        self.features()
-            .filter_map(|f| f.activation_scripts(platform))
-            .flatten()
-            .cloned()
+        self.project_features()
+            .map(|feature| (Path::new(""), feature))
+            .chain(self.current_member_feature())
+            .flat_map(|(dir, feature)| {
+                feature
+                    .activation_scripts(platform)
+                    .into_iter()
+                    .flatten()
+                    .map(move |script| dir.join(script).to_string_lossy().into_owned())
+            })
+            .collect()
+    }
+
+    /// Returns the environment variables that should be set when activating this environment.
+    ///
+    /// The variables of the default feature are applied first, followed by the variables of the
+    /// named features in the order they are defined for the environment. Variables of later
+    /// features overwrite those of earlier features. The variables of the workspace member the
+    /// project was loaded from are applied last.
+    pub fn activation_env(&self, platform: Option<Platform>) -> IndexMap<String, String> {
+        std::iter::once(self.project.manifest.default_feature())
+            .chain(self.named_features())
+            .chain(self.current_member_feature().map(|(_, feature)| feature))
+            .flat_map(|feature| feature.activation_env(platform))
             .collect()
     }
 
@@ -420,6 +562,113 @@ mod tests {
         assert_display_snapshot!(format_dependencies(deps));
     }
 
+    #[test]
+    fn test_constraints_and_overrides() {
+        let manifest = Project::from_str(
+            Path::new(""),
+            r#"
+        [project]
+        name = "foobar"
+        channels = []
+        platforms = ["linux-64", "osx-64"]
+
+        [constraints]
+        numpy = "<2"
+
+        [target.osx-64.constraints]
+        numpy = "<1.26"
+
+        [overrides]
+        openssl = ">=3"
+
+        [feature.foo.constraints]
+        numpy = ">=1.20"
+
+        [environments]
+        foo = ["foo"]
+        "#,
+        )
+        .unwrap();
+
+        let foo_env = manifest.environment("foo").unwrap();
+        assert_eq!(
+            format_dependencies(foo_env.constraints(Some(Platform::Linux64))),
+            "numpy = >=1.20\nnumpy = <2"
+        );
+        assert_eq!(
+            format_dependencies(foo_env.constraints(Some(Platform::Osx64))),
+            "numpy = >=1.20\nnumpy = <1.26"
+        );
+        assert_eq!(
+            format_dependencies(foo_env.overrides(None)),
+            "openssl = >=3"
+        );
+        assert!(foo_env.dependencies(None, None).names().next().is_none());
+    }
+
+    #[test]
+    fn test_pinned_channels() {
+        let manifest = Project::from_str(
+            Path::new(""),
+            r#"
+        [project]
+        name = "foobar"
+        channels = ["conda-forge"]
+        platforms = ["linux-64"]
+
+        [dependencies]
+        numpy = "*"
+        mylib = { version = ">=1.2", channel = "my-internal" }
+        "#,
+        )
+        .unwrap();
+
+        let pinned = manifest.default_environment().pinned_channels(None);
+        assert_eq!(
+            pinned
+                .iter()
+                .map(|(name, channel)| (name.as_source(), channel.canonical_name()))
+                .collect_vec(),
+            vec![(
+                "mylib",
+                String::from("https://conda.anaconda.org/my-internal/")
+            )]
+        );
+    }
+
+    #[test]
+    fn test_exclude() {
+        let manifest = Project::from_str(
+            Path::new(""),
+            r#"
+        [project]
+        name = "foobar"
+        channels = []
+        platforms = ["linux-64"]
+        exclude = ["mkl"]
+
+        [feature.cpu]
+        exclude = ["*=*=*_cuda*", "mkl"]
+
+        [environments]
+        cpu = ["cpu"]
+        "#,
+        )
+        .unwrap();
+
+        let exclude = |name: &str| {
+            manifest
+                .environment(name)
+                .unwrap()
+                .exclude()
+                .into_iter()
+                .map(|pattern| pattern.as_str())
+                .collect_vec()
+        };
+        assert_eq!(exclude("default"), vec!["mkl"]);
+        assert_eq!(exclude("cpu"), vec!["mkl", "*=*=*_cuda*"]);
+    }
+
     #[test]
     fn test_activation() {
         let manifest = Project::from_str(
@@ -456,6 +705,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_activation_env() {
+        let manifest = Project::from_str(
+            Path::new(""),
+            r#"
+        [project]
+        name = "foobar"
+        channels = []
+        platforms = ["linux-64"]
+
+        [activation.env]
+        NAME = "default"
+        DEFAULT = "1"
+
+        [feature.foo.activation.env]
+        NAME = "foo"
+
+        [environments]
+        foo = ["foo"]
+                "#,
+        )
+        .unwrap();
+
+        // The variables of the named features overwrite those of the default feature
+        let foo_env = manifest.environment("foo").unwrap();
+        assert_eq!(
+            foo_env.activation_env(None).into_iter().collect_vec(),
+            vec![
+                ("NAME".to_string(), "foo".to_string()),
+                ("DEFAULT".to_string(), "1".to_string())
+            ]
+        );
+    }
+
     #[test]
     fn test_channel_priorities() {
         let manifest = Project::from_str(
@@ -1,7 +1,13 @@
+use indexmap::IndexMap;
+use schemars::JsonSchema;
 use serde::Deserialize;
 
-#[derive(Default, Clone, Deserialize, Debug)]
+#[derive(Default, Clone, Deserialize, Debug, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct Activation {
+    /// Scripts to run when the environment is activated.
     pub scripts: Option<Vec<String>>,
+
+    /// Environment variables to set when the environment is activated.
+    pub env: Option<IndexMap<String, String>>,
 }
@@ -1,4 +1,6 @@
+use crate::utils::schema;
 use rattler_conda_types::{Channel, ChannelConfig};
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde::de::Error;
 use serde::{Deserialize, Deserializer};
 use serde_with::serde_as;
@@ -8,10 +10,14 @@ use std::borrow::Cow;
 /// If the priority is not specified, it is assumed to be 0.
 /// The higher the priority, the more important the channel is.
 #[serde_as]
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, JsonSchema)]
 pub struct PrioritizedChannel {
+    /// The name or url of the channel.
     #[serde_as(as = "ChannelStr")]
+    #[schemars(with = "String")]
     pub channel: Channel,
+
+    /// The priority of the channel, channels with a higher priority are preferred.
     pub priority: Option<i32>,
 }
 
@@ -24,6 +30,19 @@ impl PrioritizedChannel {
     }
 }
 
+/// How the solver treats packages that are available in more than one channel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChannelPriority {
+    /// A package is only taken from the channel with the highest priority that contains it, even
+    /// if another channel has a newer version.
+    #[default]
+    Strict,
+
+    /// The packages of all channels are pooled, the solver can select a package from any channel.
+    Disabled,
+}
+
 pub enum TomlPrioritizedChannelStrOrMap {
     Map(PrioritizedChannel),
     Str(Channel),
@@ -57,6 +76,19 @@ impl<'de> Deserialize<'de> for TomlPrioritizedChannelStrOrMap {
     }
 }
 
+impl JsonSchema for TomlPrioritizedChannelStrOrMap {
+    fn schema_name() -> String {
+        String::from("Channel")
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        schema::any_of([
+            schema::string("The name or url of a channel, e.g. `conda-forge`"),
+            gen.subschema_for::<PrioritizedChannel>(),
+        ])
+    }
+}
+
 /// Helper so that we can deserialize [`crate::project::manifest::serde::PrioritizedChannel`] from a string or a map.
 impl<'de> serde_with::DeserializeAs<'de, PrioritizedChannel> for TomlPrioritizedChannelStrOrMap {
     fn deserialize_as<D>(deserializer: D) -> Result<PrioritizedChannel, D::Error>
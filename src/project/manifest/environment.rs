@@ -1,8 +1,9 @@
 use crate::consts;
-use crate::utils::spanned::PixiSpanned;
+use crate::utils::{schema, spanned::PixiSpanned};
 use lazy_static::lazy_static;
 use miette::Diagnostic;
 use regex::Regex;
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde::{self, Deserialize, Deserializer};
 use std::borrow::Borrow;
 use std::fmt;
@@ -111,10 +112,15 @@ pub struct Environment {
 
 /// Helper struct to deserialize the environment from TOML.
 /// The environment description can only hold these values.
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub(super) struct TomlEnvironment {
+    /// The names of the features that make up the environment.
+    #[schemars(with = "Vec<String>")]
     pub features: PixiSpanned<Vec<String>>,
+
+    /// The solve-group of the environment. All environments in the same solve-group are solved
+    /// together.
     pub solve_group: Option<String>,
 }
 
@@ -156,6 +162,19 @@ impl<'de> Deserialize<'de> for TomlEnvironmentMapOrSeq {
     }
 }
 
+impl JsonSchema for TomlEnvironmentMapOrSeq {
+    fn schema_name() -> String {
+        String::from("Environment")
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        schema::any_of([
+            gen.subschema_for::<TomlEnvironment>(),
+            gen.subschema_for::<Vec<String>>(),
+        ])
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,139 @@
+use rattler_conda_types::PackageRecord;
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde_with::DeserializeFromStr;
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+use thiserror::Error;
+
+/// An entry of the `exclude` list of the project or a feature. Packages that match the pattern are
+/// never selected by the solver.
+///
+/// Like a match spec the pattern has the form `name`, `name=version` or `name=version=build`, the
+/// version and the build string are only matched if the pattern contains them. So `mkl` excludes
+/// the `mkl` package and `*=*=*_cuda*` excludes all CUDA builds. `*` and `?` can be used as
+/// wildcards in every part.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, DeserializeFromStr)]
+pub struct ExcludePattern {
+    source: String,
+    name: glob::Pattern,
+    version: Option<glob::Pattern>,
+    build: Option<glob::Pattern>,
+}
+
+/// An error that can occur when parsing an [`ExcludePattern`].
+#[derive(Debug, Error)]
+pub enum ParseExcludePatternError {
+    #[error("invalid pattern `{0}`")]
+    InvalidGlob(String, #[source] glob::PatternError),
+
+    #[error("`{0}` has too many parts, expected `name`, `name=version` or `name=version=build`")]
+    TooManyParts(String),
+}
+
+impl ExcludePattern {
+    /// Returns true if the package `record` is excluded by this pattern.
+    pub fn matches(&self, record: &PackageRecord) -> bool {
+        self.name.matches(record.name.as_normalized())
+            && self
+                .version
+                .as_ref()
+                .map_or(true, |version| version.matches(&record.version.to_string()))
+            && self
+                .build
+                .as_ref()
+                .map_or(true, |build| build.matches(&record.build))
+    }
+
+    /// Returns the pattern as it was written in the manifest.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl FromStr for ExcludePattern {
+    type Err = ParseExcludePatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |part: &str| {
+            glob::Pattern::new(part)
+                .map_err(|e| ParseExcludePatternError::InvalidGlob(part.to_string(), e))
+        };
+
+        let mut parts = s.split('=');
+        let name = parse(parts.next().unwrap_or_default())?;
+        let version = parts.next().map(parse).transpose()?;
+        let build = parts.next().map(parse).transpose()?;
+        if parts.next().is_some() {
+            return Err(ParseExcludePatternError::TooManyParts(s.to_string()));
+        }
+
+        Ok(Self {
+            source: s.to_string(),
+            name,
+            version,
+            build,
+        })
+    }
+}
+
+impl Display for ExcludePattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl JsonSchema for ExcludePattern {
+    fn schema_name() -> String {
+        String::from("ExcludePattern")
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rattler_conda_types::{PackageName, Version};
+
+    #[test]
+    fn test_matches() {
+        let record = |name: &str, build: &str| {
+            PackageRecord::new(
+                PackageName::from_str(name).unwrap(),
+                Version::from_str("1.0").unwrap(),
+                build.to_string(),
+            )
+        };
+
+        let mkl = ExcludePattern::from_str("mkl").unwrap();
+        assert!(mkl.matches(&record("mkl", "h84fe81f_50496")));
+        assert!(!mkl.matches(&record("mkl-include", "h84fe81f_50496")));
+
+        // The build string is only matched if the pattern contains one
+        let py = ExcludePattern::from_str("py*").unwrap();
+        assert!(py.matches(&record("pytorch", "cpu_py311h0e4b9e4_0")));
+        assert!(!py.matches(&record("numpy", "py312h8753938_0")));
+
+        let cuda = ExcludePattern::from_str("*=*=*_cuda*").unwrap();
+        assert!(cuda.matches(&record("pytorch", "py311_cuda120_h1d2b2a4_0")));
+        assert!(!cuda.matches(&record("pytorch", "cpu_py311h0e4b9e4_0")));
+
+        let numpy = ExcludePattern::from_str("numpy=2.*").unwrap();
+        assert!(!numpy.matches(&record("numpy", "py312h8753938_0")));
+        let numpy = ExcludePattern::from_str("numpy=1.*").unwrap();
+        assert!(numpy.matches(&record("numpy", "py312h8753938_0")));
+
+        assert!(matches!(
+            ExcludePattern::from_str("numpy=1.*=0=1"),
+            Err(ParseExcludePatternError::TooManyParts(_))
+        ));
+        assert!(matches!(
+            ExcludePattern::from_str("numpy=[="),
+            Err(ParseExcludePatternError::InvalidGlob(_, _))
+        ));
+    }
+}
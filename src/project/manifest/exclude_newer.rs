@@ -0,0 +1,104 @@
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use rattler_conda_types::PackageRecord;
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde_with::DeserializeFromStr;
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+use thiserror::Error;
+
+/// The `exclude-newer` cutoff of the project. Packages that were published after the cutoff are
+/// never selected by the solver, which makes solving reproducible regardless of when it runs.
+///
+/// The cutoff is either a date like `2024-01-01`, which means midnight UTC at the start of that
+/// day, or an RFC 3339 timestamp like `2024-01-01T12:00:00Z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, DeserializeFromStr)]
+pub struct ExcludeNewer(DateTime<Utc>);
+
+/// The `exclude-newer` cutoff is neither a date nor a timestamp.
+#[derive(Debug, Error)]
+#[error("'{0}' is not a date (YYYY-MM-DD) or an RFC 3339 timestamp (YYYY-MM-DDTHH:MM:SSZ)")]
+pub struct ParseExcludeNewerError(String);
+
+impl ExcludeNewer {
+    /// Returns true if the package `record` was published after the cutoff. Packages without a
+    /// timestamp are never excluded.
+    pub fn excludes(&self, record: &PackageRecord) -> bool {
+        record
+            .timestamp
+            .map_or(false, |timestamp| self.excludes_timestamp(timestamp))
+    }
+
+    /// Returns true if something that was published at `timestamp` is after the cutoff.
+    pub fn excludes_timestamp(&self, timestamp: DateTime<Utc>) -> bool {
+        timestamp > self.0
+    }
+
+    /// Returns the cutoff as a UTC timestamp.
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+impl FromStr for ExcludeNewer {
+    type Err = ParseExcludeNewerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(timestamp) = DateTime::parse_from_rfc3339(s) {
+            return Ok(Self(timestamp.with_timezone(&Utc)));
+        }
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|timestamp| Self(Utc.from_utc_datetime(&timestamp)))
+            .ok_or_else(|| ParseExcludeNewerError(s.to_string()))
+    }
+}
+
+impl Display for ExcludeNewer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.to_rfc3339())
+    }
+}
+
+impl JsonSchema for ExcludeNewer {
+    fn schema_name() -> String {
+        String::from("ExcludeNewer")
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rattler_conda_types::{PackageName, Version};
+
+    #[test]
+    fn test_excludes() {
+        let exclude_newer = ExcludeNewer::from_str("2024-01-01").unwrap();
+        assert_eq!(
+            exclude_newer.timestamp(),
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            ExcludeNewer::from_str("2024-01-01T02:00:00+02:00").unwrap(),
+            exclude_newer
+        );
+        assert!(ExcludeNewer::from_str("01-01-2024").is_err());
+
+        let mut record = PackageRecord::new(
+            PackageName::from_str("numpy").unwrap(),
+            Version::from_str("1.26.4").unwrap(),
+            String::from("py312_0"),
+        );
+        assert!(!exclude_newer.excludes(&record));
+        record.timestamp = Some(Utc.with_ymd_and_hms(2023, 12, 31, 23, 59, 59).unwrap());
+        assert!(!exclude_newer.excludes(&record));
+        record.timestamp = Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 1).unwrap());
+        assert!(exclude_newer.excludes(&record));
+    }
+}
@@ -1,13 +1,16 @@
-use super::{Activation, PyPiRequirement, SystemRequirements, Target, TargetSelector};
+use super::{
+    Activation, ExcludePattern, PyPiRequirement, SystemRequirements, Target, TargetSelector,
+};
 use crate::consts;
 use crate::project::manifest::channel::{PrioritizedChannel, TomlPrioritizedChannelStrOrMap};
 use crate::project::manifest::target::Targets;
 use crate::project::SpecType;
 use crate::task::Task;
-use crate::utils::spanned::PixiSpanned;
+use crate::utils::{schema, spanned::PixiSpanned};
 use indexmap::IndexMap;
 use itertools::Either;
 use rattler_conda_types::{NamelessMatchSpec, PackageName, Platform};
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde::de::Error;
 use serde::{Deserialize, Deserializer};
 use serde_with::{serde_as, DisplayFromStr, PickFirst};
@@ -111,6 +114,10 @@ pub struct Feature {
     /// channels from the project should be used.
     pub channels: Option<Vec<PrioritizedChannel>>,
 
+    /// Patterns of packages that are never selected by the solver for environments that include
+    /// this feature. These are added to the patterns of the project.
+    pub exclude: Vec<ExcludePattern>,
+
     /// Additional system requirements
     pub system_requirements: SystemRequirements,
 
@@ -184,6 +191,36 @@ impl Feature {
             })
     }
 
+    /// Returns the constraints on conda packages of the feature for a given `platform`. The
+    /// constraints of more specific targets overwrite those of less specific targets.
+    pub fn constraints(
+        &self,
+        platform: Option<Platform>,
+    ) -> IndexMap<PackageName, NamelessMatchSpec> {
+        self.targets
+            .resolve(platform)
+            // Get the targets in reverse order, from least specific to most specific.
+            .rev()
+            .flat_map(|t| t.constraints.iter())
+            .map(|(name, spec)| (name.clone(), spec.clone()))
+            .collect()
+    }
+
+    /// Returns the overrides of conda packages of the feature for a given `platform`. The
+    /// overrides of more specific targets overwrite those of less specific targets.
+    pub fn overrides(
+        &self,
+        platform: Option<Platform>,
+    ) -> IndexMap<PackageName, NamelessMatchSpec> {
+        self.targets
+            .resolve(platform)
+            // Get the targets in reverse order, from least specific to most specific.
+            .rev()
+            .flat_map(|t| t.overrides.iter())
+            .map(|(name, spec)| (name.clone(), spec.clone()))
+            .collect()
+    }
+
     /// Returns the activation scripts for the most specific target that matches the given
     /// `platform`.
     ///
@@ -196,6 +233,22 @@ impl Feature {
             .next()
     }
 
+    /// Returns the environment variables to set on activation for the given `platform`.
+    ///
+    /// The variables of all targets that match the platform are combined, the variables of more
+    /// specific targets overwrite those of less specific targets.
+    pub fn activation_env(&self, platform: Option<Platform>) -> IndexMap<String, String> {
+        self.targets
+            .resolve(platform)
+            // Get the targets in reverse order, from least specific to most specific.
+            .rev()
+            .filter_map(|t| t.activation.as_ref())
+            .filter_map(|a| a.env.as_ref())
+            .flatten()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+
     /// Returns true if the feature contains any reference to a pypi dependencies.
     pub fn has_pypi_dependencies(&self) -> bool {
         self.targets
@@ -204,49 +257,91 @@ impl Feature {
     }
 }
 
+/// The structure of a feature (e.g. `[feature.test]`) as it is written in the toml file.
+#[serde_as]
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct TomlFeature {
+    /// The platforms supported by the feature, overrides the platforms of the project.
+    #[serde(default)]
+    #[schemars(schema_with = "schema::platforms")]
+    platforms: Option<PixiSpanned<Vec<Platform>>>,
+
+    /// The channels used by the feature, overrides the channels of the project.
+    #[serde(default)]
+    channels: Option<Vec<TomlPrioritizedChannelStrOrMap>>,
+
+    /// Patterns of packages that are never selected by the solver.
+    #[serde(default)]
+    exclude: Vec<ExcludePattern>,
+
+    /// The system requirements of the feature.
+    #[serde(default)]
+    system_requirements: SystemRequirements,
+
+    /// Platform specific configuration of the feature, e.g. `[feature.test.target.linux-64]`.
+    #[serde(default)]
+    target: IndexMap<PixiSpanned<TargetSelector>, Target>,
+
+    /// The conda packages required by the feature.
+    #[serde(default)]
+    #[serde_as(as = "IndexMap<_, PickFirst<(DisplayFromStr, _)>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    dependencies: IndexMap<PackageName, NamelessMatchSpec>,
+
+    /// The conda packages required on the host when building the feature.
+    #[serde(default)]
+    #[serde_as(as = "Option<IndexMap<_, PickFirst<(DisplayFromStr, _)>>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    host_dependencies: Option<IndexMap<PackageName, NamelessMatchSpec>>,
+
+    /// The conda packages required on the build machine when building the feature.
+    #[serde(default)]
+    #[serde_as(as = "Option<IndexMap<_, PickFirst<(DisplayFromStr, _)>>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    build_dependencies: Option<IndexMap<PackageName, NamelessMatchSpec>>,
+
+    /// The PyPI packages required by the feature.
+    #[serde(default)]
+    pypi_dependencies: Option<IndexMap<rip::types::PackageName, PyPiRequirement>>,
+
+    /// Constraints on the versions of the conda packages used by the feature.
+    #[serde(default)]
+    #[serde_as(as = "IndexMap<_, PickFirst<(DisplayFromStr, _)>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    constraints: IndexMap<PackageName, NamelessMatchSpec>,
+
+    /// Overrides of the requirements on conda packages used by the feature.
+    #[serde(default)]
+    #[serde_as(as = "IndexMap<_, PickFirst<(DisplayFromStr, _)>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    overrides: IndexMap<PackageName, NamelessMatchSpec>,
+
+    /// Additional information to activate an environment.
+    #[serde(default)]
+    activation: Option<Activation>,
+
+    /// Target specific tasks to run in the environment
+    #[serde(default)]
+    tasks: HashMap<String, Task>,
+}
+
+impl JsonSchema for Feature {
+    fn schema_name() -> String {
+        String::from("Feature")
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        TomlFeature::json_schema(gen)
+    }
+}
+
 impl<'de> Deserialize<'de> for Feature {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        #[serde_as]
-        #[derive(Deserialize)]
-        #[serde(deny_unknown_fields, rename_all = "kebab-case")]
-        struct FeatureInner {
-            #[serde(default)]
-            platforms: Option<PixiSpanned<Vec<Platform>>>,
-            #[serde(default)]
-            channels: Option<Vec<TomlPrioritizedChannelStrOrMap>>,
-            #[serde(default)]
-            system_requirements: SystemRequirements,
-            #[serde(default)]
-            target: IndexMap<PixiSpanned<TargetSelector>, Target>,
-
-            #[serde(default)]
-            #[serde_as(as = "IndexMap<_, PickFirst<(DisplayFromStr, _)>>")]
-            dependencies: IndexMap<PackageName, NamelessMatchSpec>,
-
-            #[serde(default)]
-            #[serde_as(as = "Option<IndexMap<_, PickFirst<(DisplayFromStr, _)>>>")]
-            host_dependencies: Option<IndexMap<PackageName, NamelessMatchSpec>>,
-
-            #[serde(default)]
-            #[serde_as(as = "Option<IndexMap<_, PickFirst<(DisplayFromStr, _)>>>")]
-            build_dependencies: Option<IndexMap<PackageName, NamelessMatchSpec>>,
-
-            #[serde(default)]
-            pypi_dependencies: Option<IndexMap<rip::types::PackageName, PyPiRequirement>>,
-
-            /// Additional information to activate an environment.
-            #[serde(default)]
-            activation: Option<Activation>,
-
-            /// Target specific tasks to run in the environment
-            #[serde(default)]
-            tasks: HashMap<String, Task>,
-        }
-
-        let inner = FeatureInner::deserialize(deserializer)?;
+        let inner = TomlFeature::deserialize(deserializer)?;
 
         let mut dependencies = HashMap::from_iter([(SpecType::Run, inner.dependencies)]);
         if let Some(host_deps) = inner.host_dependencies {
@@ -259,6 +354,8 @@ impl<'de> Deserialize<'de> for Feature {
         let default_target = Target {
             dependencies,
             pypi_dependencies: inner.pypi_dependencies,
+            constraints: inner.constraints,
+            overrides: inner.overrides,
             activation: inner.activation,
             tasks: inner.tasks,
         };
@@ -272,6 +369,7 @@ impl<'de> Deserialize<'de> for Feature {
                     .map(|channel| channel.into_prioritized_channel())
                     .collect()
             }),
+            exclude: inner.exclude,
             system_requirements: inner.system_requirements,
             targets: Targets::from_default_and_user_defined(default_target, inner.target),
         })
@@ -385,4 +483,49 @@ mod tests {
             "should have selected the activation from the [linux-64] section"
         );
     }
+
+    #[test]
+    fn test_activation_env() {
+        let manifest = Manifest::from_str(
+            Path::new(""),
+            r#"
+        [project]
+        name = "foo"
+        platforms = ["linux-64", "osx-64", "win-64"]
+        channels = []
+
+        [activation.env]
+        FOO = "foo"
+        BAR = "bar"
+
+        [target.linux-64.activation.env]
+        BAR = "linux-bar"
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest
+                .default_feature()
+                .activation_env(Some(Platform::Osx64))
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![
+                ("FOO".to_string(), "foo".to_string()),
+                ("BAR".to_string(), "bar".to_string())
+            ]
+        );
+        assert_eq!(
+            manifest
+                .default_feature()
+                .activation_env(Some(Platform::Linux64))
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![
+                ("FOO".to_string(), "foo".to_string()),
+                ("BAR".to_string(), "linux-bar".to_string())
+            ],
+            "the [linux-64] section should overwrite the default BAR"
+        );
+    }
 }
@@ -0,0 +1,165 @@
+//! Formatting of the project manifest. The formatter works on the toml document, so comments and
+//! the layout of everything that is not touched by the formatter are preserved.
+
+use super::Manifest;
+use crate::{consts, project::SpecType};
+use rattler_conda_types::NamelessMatchSpec;
+use std::str::FromStr;
+use toml_edit::{Item, TableLike, Value};
+
+impl Manifest {
+    /// Formats the manifest: the dependency tables are sorted by package name and the specs of the
+    /// dependencies are normalized. Use [`Manifest::save`] to write the result.
+    pub fn format(&mut self) {
+        let table = self.pixi_table_mut();
+        format_feature(table);
+
+        if let Some(features) = table.get_mut("feature").and_then(Item::as_table_like_mut) {
+            for (_, feature) in features.iter_mut() {
+                if let Some(feature) = feature.as_table_like_mut() {
+                    format_feature(feature);
+                }
+            }
+        }
+    }
+
+    /// Returns true if the manifest is formatted, i.e. formatting it would not change it.
+    pub fn is_formatted(&self) -> bool {
+        let mut formatted = self.clone();
+        formatted.format();
+        formatted.document.to_string() == self.document.to_string()
+    }
+}
+
+/// Formats the dependency tables of a feature and of its targets.
+fn format_feature(feature: &mut dyn TableLike) {
+    format_dependency_tables(feature);
+
+    if let Some(targets) = feature.get_mut("target").and_then(Item::as_table_like_mut) {
+        for (_, target) in targets.iter_mut() {
+            if let Some(target) = target.as_table_like_mut() {
+                format_dependency_tables(target);
+            }
+        }
+    }
+}
+
+/// Sorts the dependency, constraint and override tables of a feature or target and normalizes the
+/// specs in them.
+fn format_dependency_tables(table: &mut dyn TableLike) {
+    for spec_type in SpecType::all() {
+        if let Some(dependencies) = table
+            .get_mut(spec_type.name())
+            .and_then(Item::as_table_like_mut)
+        {
+            format_dependencies(dependencies, normalize_conda_spec);
+        }
+    }
+
+    for name in [consts::CONSTRAINTS, consts::OVERRIDES] {
+        if let Some(specs) = table.get_mut(name).and_then(Item::as_table_like_mut) {
+            format_dependencies(specs, normalize_conda_spec);
+        }
+    }
+
+    if let Some(dependencies) = table
+        .get_mut(consts::PYPI_DEPENDENCIES)
+        .and_then(Item::as_table_like_mut)
+    {
+        format_dependencies(dependencies, normalize_pypi_spec);
+    }
+}
+
+/// Sorts a dependency table by package name and normalizes the specs that are written as a string.
+fn format_dependencies(dependencies: &mut dyn TableLike, normalize: fn(&str) -> Option<String>) {
+    dependencies.sort_values();
+
+    for (_, item) in dependencies.iter_mut() {
+        let Some(value) = item.as_value_mut() else {
+            continue;
+        };
+        let Some(normalized) = value.as_str().and_then(normalize) else {
+            continue;
+        };
+        if value.as_str() != Some(normalized.as_str()) {
+            let decor = value.decor().clone();
+            *value = Value::from(normalized);
+            *value.decor_mut() = decor;
+        }
+    }
+}
+
+/// Returns the canonical representation of a conda spec, e.g. `>= 1.2` becomes `>=1.2`.
+fn normalize_conda_spec(spec: &str) -> Option<String> {
+    NamelessMatchSpec::from_str(spec)
+        .ok()
+        .map(|spec| spec.to_string())
+}
+
+/// Returns the canonical representation of a PEP 440 version specifier, e.g. `>= 1.2,<2`
+/// becomes `>=1.2, <2`.
+fn normalize_pypi_spec(spec: &str) -> Option<String> {
+    if spec.trim() == "*" {
+        return Some(String::from("*"));
+    }
+    pep440_rs::VersionSpecifiers::from_str(spec)
+        .ok()
+        .map(|specifiers| specifiers.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_format() {
+        let mut manifest = Manifest::from_str(
+            Path::new(""),
+            r#"[project]
+name = "foo"
+channels = []
+platforms = ["linux-64"]
+
+[dependencies]
+# The python interpreter
+python = ">= 3.10"
+numpy = "1.26.*" # numerical computing
+
+[pypi-dependencies]
+requests = " >= 2.31"
+flask = "*"
+
+[feature.test.target.linux-64.dependencies]
+pytest = "*"
+coverage = "7.*"
+"#,
+        )
+        .unwrap();
+        assert!(!manifest.is_formatted());
+
+        manifest.format();
+        assert!(manifest.is_formatted());
+        assert_eq!(
+            manifest.document.to_string(),
+            r#"[project]
+name = "foo"
+channels = []
+platforms = ["linux-64"]
+
+[dependencies]
+numpy = "1.26.*" # numerical computing
+# The python interpreter
+python = ">=3.10"
+
+[pypi-dependencies]
+flask = "*"
+requests = ">=2.31"
+
+[feature.test.target.linux-64.dependencies]
+coverage = "7.*"
+pytest = "*"
+"#
+        );
+    }
+}
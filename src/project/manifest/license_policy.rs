@@ -0,0 +1,180 @@
+use miette::{IntoDiagnostic, WrapErr};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use spdx::{Expression, LicenseItem, LicenseReq, Licensee};
+use std::fmt::{Display, Formatter};
+
+/// Parses an SPDX license expression, e.g. `MIT AND Apache-2.0`.
+pub fn parse_license_expression(expression: &str) -> miette::Result<Expression> {
+    Expression::parse(expression)
+        .into_diagnostic()
+        .with_context(|| {
+            format!(
+                "failed to parse the SPDX license expression '{}'",
+                expression
+            )
+        })
+}
+
+/// Describes the `[license-policy]` section of the project manifest. The policy restricts the
+/// licenses of the packages that can be part of the environments of the project.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct LicensePolicy {
+    /// SPDX license expressions of the licenses that are allowed. If this is not empty, packages
+    /// with any other license, or without a valid license, are rejected.
+    #[serde(default)]
+    pub allow: Vec<String>,
+
+    /// SPDX license expressions of the licenses that are never allowed.
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+/// How the license of a package relates to the [`LicensePolicy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LicenseStatus {
+    /// The license is allowed.
+    Allowed,
+
+    /// The license can only be satisfied with a license that is denied.
+    Denied { license: String },
+
+    /// The license is not one of the allowed licenses.
+    NotAllowed,
+
+    /// The package has no license, or a license that is not a valid SPDX expression, and the
+    /// policy does not restrict the allowed licenses.
+    Unknown,
+}
+
+impl LicenseStatus {
+    /// Returns true if the license violates the policy.
+    pub fn is_violation(&self) -> bool {
+        matches!(
+            self,
+            LicenseStatus::Denied { .. } | LicenseStatus::NotAllowed
+        )
+    }
+}
+
+impl Display for LicenseStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LicenseStatus::Allowed => write!(f, "allowed"),
+            LicenseStatus::Denied { license } => write!(f, "denied ({license})"),
+            LicenseStatus::NotAllowed => write!(f, "not allowed"),
+            LicenseStatus::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+impl LicensePolicy {
+    /// Returns true if the policy does not restrict any licenses.
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    /// Determines the status of the `license` of a package. A license expression with choices,
+    /// like `MIT OR GPL-3.0-only`, is allowed if one of the choices is allowed.
+    pub fn status(&self, license: Option<&str>) -> LicenseStatus {
+        let allowed = licensees(&self.allow);
+        let denied = licensees(&self.deny);
+
+        let Some(expression) = license.and_then(|license| Expression::parse(license).ok()) else {
+            return if allowed.is_empty() {
+                LicenseStatus::Unknown
+            } else {
+                LicenseStatus::NotAllowed
+            };
+        };
+
+        let is_denied = |req: &LicenseReq| denied.iter().any(|licensee| licensee.satisfies(req));
+        let is_allowed = |req: &LicenseReq| {
+            !is_denied(req)
+                && (allowed.is_empty() || allowed.iter().any(|licensee| licensee.satisfies(req)))
+        };
+        if expression.evaluate(is_allowed) {
+            return LicenseStatus::Allowed;
+        }
+
+        match expression
+            .requirements()
+            .find(|requirement| is_denied(&requirement.req))
+        {
+            Some(requirement) => LicenseStatus::Denied {
+                license: requirement.req.to_string(),
+            },
+            None => LicenseStatus::NotAllowed,
+        }
+    }
+}
+
+/// Converts the license expressions of the policy to the licenses they contain. The expressions
+/// are validated when the manifest is loaded, invalid expressions are ignored here.
+fn licensees(expressions: &[String]) -> Vec<Licensee> {
+    expressions
+        .iter()
+        .filter_map(|expression| Expression::parse(expression).ok())
+        .flat_map(|expression| {
+            expression
+                .requirements()
+                .map(|requirement| {
+                    let license = match &requirement.req.license {
+                        LicenseItem::Spdx { id, .. } => LicenseItem::Spdx {
+                            id: *id,
+                            or_later: false,
+                        },
+                        other => other.clone(),
+                    };
+                    Licensee::new(license, requirement.req.exception)
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_license_status() {
+        let policy = LicensePolicy {
+            allow: Vec::new(),
+            deny: vec![String::from("GPL-3.0-only OR AGPL-3.0-only")],
+        };
+        assert_eq!(policy.status(Some("MIT")), LicenseStatus::Allowed);
+        assert_eq!(
+            policy.status(Some("MIT OR GPL-3.0-only")),
+            LicenseStatus::Allowed
+        );
+        assert_eq!(
+            policy.status(Some("MIT AND GPL-3.0-only")),
+            LicenseStatus::Denied {
+                license: String::from("GPL-3.0-only")
+            }
+        );
+        assert_eq!(policy.status(Some("BSD")), LicenseStatus::Unknown);
+        assert_eq!(policy.status(None), LicenseStatus::Unknown);
+
+        let policy = LicensePolicy {
+            allow: vec![
+                String::from("MIT"),
+                String::from("BSD-3-Clause OR Apache-2.0"),
+            ],
+            deny: Vec::new(),
+        };
+        assert_eq!(policy.status(Some("Apache-2.0")), LicenseStatus::Allowed);
+        assert_eq!(
+            policy.status(Some("MIT AND BSD-3-Clause")),
+            LicenseStatus::Allowed
+        );
+        assert_eq!(
+            policy.status(Some("MIT AND MPL-2.0")),
+            LicenseStatus::NotAllowed
+        );
+        assert_eq!(policy.status(Some("BSD")), LicenseStatus::NotAllowed);
+        assert!(policy.status(None).is_violation());
+    }
+}
@@ -0,0 +1,496 @@
+//! Lints that point out problems in a project manifest that are not errors, but are likely to be
+//! mistakes or could be improved.
+
+use super::{get_nested_toml_table_name, FeatureName, Manifest, ProjectManifest, TargetSelector};
+use crate::{consts, project::SpecType};
+use indexmap::IndexMap;
+use itertools::Itertools;
+use rattler_conda_types::Platform;
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter},
+};
+use toml_edit::Table;
+
+/// The kind of problem that was found by a lint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    /// A dependency that is declared without any version constraint.
+    UnpinnedDependency,
+
+    /// A dependency that is declared by multiple features.
+    DuplicateDependency,
+
+    /// A feature that is not used by any environment.
+    UnusedFeature,
+
+    /// A task that has the same name as an executable.
+    ShadowedExecutable,
+
+    /// A target for a platform that is not one of the platforms of the project.
+    UnsupportedPlatform,
+
+    /// A table without any entries.
+    EmptyTable,
+}
+
+impl LintKind {
+    /// Returns the name of the lint.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LintKind::UnpinnedDependency => "unpinned-dependency",
+            LintKind::DuplicateDependency => "duplicate-dependency",
+            LintKind::UnusedFeature => "unused-feature",
+            LintKind::ShadowedExecutable => "shadowed-executable",
+            LintKind::UnsupportedPlatform => "unsupported-platform",
+            LintKind::EmptyTable => "empty-table",
+        }
+    }
+}
+
+/// A problem that was found in the project manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    /// The kind of problem.
+    pub kind: LintKind,
+
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl Lint {
+    fn new(kind: LintKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}]", self.message, self.kind.name())
+    }
+}
+
+/// The environments a manifest is used by. The features and targets of a workspace member are
+/// only used through the environments of the workspace, so a member is linted in the context of
+/// the workspace instead of its own environments.
+#[derive(Debug, Clone, Default)]
+pub struct LintContext {
+    /// The names of the features that are part of any environment.
+    pub used_features: HashSet<String>,
+
+    /// The union of the platforms of all environments.
+    pub platforms: HashSet<Platform>,
+}
+
+impl LintContext {
+    /// Returns the context of a manifest that is linted on its own. The platforms of an
+    /// environment are the platforms that all of its features support, a feature without
+    /// platforms supports the platforms of the project.
+    pub fn from_manifest(manifest: &ProjectManifest) -> Self {
+        let feature_platforms = |name: &FeatureName| -> HashSet<Platform> {
+            manifest
+                .features
+                .get(name)
+                .and_then(|feature| feature.platforms.as_ref())
+                .unwrap_or(&manifest.project.platforms)
+                .get_ref()
+                .iter()
+                .copied()
+                .collect()
+        };
+
+        let platforms = manifest
+            .environments
+            .values()
+            .flat_map(|environment| {
+                environment
+                    .features
+                    .iter()
+                    .map(|name| feature_platforms(&FeatureName::Named(name.clone())))
+                    .fold(
+                        feature_platforms(&FeatureName::Default),
+                        |acc, platforms| acc.intersection(&platforms).copied().collect(),
+                    )
+            })
+            .collect();
+        let used_features = manifest
+            .environments
+            .values()
+            .flat_map(|environment| environment.features.iter().cloned())
+            .collect();
+
+        Self {
+            used_features,
+            platforms,
+        }
+    }
+}
+
+impl Manifest {
+    /// Lints the manifest on its own. `is_executable` is used to determine whether a task shadows
+    /// an executable with the same name.
+    pub fn lint(&self, is_executable: impl Fn(&str) -> bool) -> Vec<Lint> {
+        self.lint_with_context(&LintContext::from_manifest(&self.parsed), is_executable)
+    }
+
+    /// Lints the manifest as part of the environments described by `context`, see
+    /// [`LintContext`].
+    pub fn lint_with_context(
+        &self,
+        context: &LintContext,
+        is_executable: impl Fn(&str) -> bool,
+    ) -> Vec<Lint> {
+        let mut lints = Vec::new();
+        lint_unpinned_dependencies(&self.parsed, &mut lints);
+        lint_duplicate_dependencies(&self.parsed, &mut lints);
+        lint_unused_features(&self.parsed, context, &mut lints);
+        lint_shadowed_executables(&self.parsed, is_executable, &mut lints);
+        lint_unsupported_platforms(&self.parsed, context, &mut lints);
+        lint_empty_tables(self.pixi_table(), "", &mut lints);
+        lints
+    }
+}
+
+/// Returns the platform of a target selector.
+fn selector_platform(selector: Option<&TargetSelector>) -> Option<Platform> {
+    selector.map(|selector| match selector {
+        TargetSelector::Platform(platform) => *platform,
+    })
+}
+
+/// Flags dependencies that are declared with `*`.
+fn lint_unpinned_dependencies(manifest: &ProjectManifest, lints: &mut Vec<Lint>) {
+    for feature in manifest.features.values() {
+        for (target, selector) in feature.targets.iter() {
+            let platform = selector_platform(selector);
+            for spec_type in SpecType::all() {
+                let Some(dependencies) = target.dependencies.get(&spec_type) else {
+                    continue;
+                };
+                for (name, spec) in dependencies {
+                    if matches!(spec.to_string().as_str(), "" | "*") {
+                        lints.push(Lint::new(
+                            LintKind::UnpinnedDependency,
+                            format!(
+                                "`{}` in [{}] has no version constraint, consider pinning it",
+                                name.as_source(),
+                                get_nested_toml_table_name(
+                                    &feature.name,
+                                    platform,
+                                    spec_type.name()
+                                )
+                            ),
+                        ));
+                    }
+                }
+            }
+
+            for (name, requirement) in target.pypi_dependencies.iter().flatten() {
+                if requirement.version.is_none() {
+                    lints.push(Lint::new(
+                        LintKind::UnpinnedDependency,
+                        format!(
+                            "`{}` in [{}] has no version constraint, consider pinning it",
+                            name.as_source_str(),
+                            get_nested_toml_table_name(
+                                &feature.name,
+                                platform,
+                                consts::PYPI_DEPENDENCIES
+                            )
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Flags dependencies that are declared by more than one feature for the same target.
+fn lint_duplicate_dependencies(manifest: &ProjectManifest, lints: &mut Vec<Lint>) {
+    // Maps the table and name of a dependency to the features that declare it.
+    let mut declared_by: IndexMap<(String, String), Vec<&FeatureName>> = IndexMap::new();
+    for feature in manifest.features.values() {
+        for (target, selector) in feature.targets.iter() {
+            let platform = selector_platform(selector);
+            for spec_type in SpecType::all() {
+                for name in target.dependencies.get(&spec_type).into_iter().flatten() {
+                    let table = get_nested_toml_table_name(
+                        &FeatureName::Default,
+                        platform,
+                        spec_type.name(),
+                    );
+                    declared_by
+                        .entry((table, name.as_normalized().to_string()))
+                        .or_default()
+                        .push(&feature.name);
+                }
+            }
+            for name in target
+                .pypi_dependencies
+                .iter()
+                .flatten()
+                .map(|(name, _)| name)
+            {
+                let table = get_nested_toml_table_name(
+                    &FeatureName::Default,
+                    platform,
+                    consts::PYPI_DEPENDENCIES,
+                );
+                declared_by
+                    .entry((table, name.as_str().to_string()))
+                    .or_default()
+                    .push(&feature.name);
+            }
+        }
+    }
+
+    for ((table, name), features) in declared_by {
+        if features.len() > 1 {
+            lints.push(Lint::new(
+                LintKind::DuplicateDependency,
+                format!(
+                    "`{name}` is declared in [{table}] of multiple features: {}",
+                    features.iter().map(|feature| feature.as_str()).join(", ")
+                ),
+            ));
+        }
+    }
+}
+
+/// Flags named features that are not part of any environment.
+fn lint_unused_features(manifest: &ProjectManifest, context: &LintContext, lints: &mut Vec<Lint>) {
+    for name in manifest.features.keys() {
+        let FeatureName::Named(name) = name else {
+            continue;
+        };
+        if !context.used_features.contains(name) {
+            lints.push(Lint::new(
+                LintKind::UnusedFeature,
+                format!("the feature `{name}` is not used by any environment"),
+            ));
+        }
+    }
+}
+
+/// Flags tasks that have the same name as an executable. Running the name with `pixi run` would
+/// run the task instead of the executable.
+fn lint_shadowed_executables(
+    manifest: &ProjectManifest,
+    is_executable: impl Fn(&str) -> bool,
+    lints: &mut Vec<Lint>,
+) {
+    let task_names = manifest
+        .features
+        .values()
+        .flat_map(|feature| feature.targets.targets())
+        .flat_map(|target| target.tasks.keys())
+        .unique();
+    for name in task_names {
+        if is_executable(name) {
+            lints.push(Lint::new(
+                LintKind::ShadowedExecutable,
+                format!("the task `{name}` shadows the executable with the same name"),
+            ));
+        }
+    }
+}
+
+/// Flags targets for platforms that are not a platform of any environment of the project.
+fn lint_unsupported_platforms(
+    manifest: &ProjectManifest,
+    context: &LintContext,
+    lints: &mut Vec<Lint>,
+) {
+    for feature in manifest.features.values() {
+        for selector in feature.targets.user_defined_selectors() {
+            let TargetSelector::Platform(platform) = selector;
+            if !context.platforms.contains(platform) {
+                let table = match &feature.name {
+                    FeatureName::Default => format!("target.{platform}"),
+                    FeatureName::Named(name) => format!("feature.{name}.target.{platform}"),
+                };
+                lints.push(Lint::new(
+                    LintKind::UnsupportedPlatform,
+                    format!(
+                        "[{table}] targets `{platform}` which is not a platform of any environment"
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+/// Flags tables that do not contain any entries. An empty `pypi-dependencies` table is not flagged
+/// because it enables pypi support for the environments that use it.
+fn lint_empty_tables(table: &Table, path: &str, lints: &mut Vec<Lint>) {
+    for (key, item) in table.iter() {
+        let Some(child) = item.as_table() else {
+            continue;
+        };
+        if key == consts::PYPI_DEPENDENCIES {
+            continue;
+        }
+        let child_path = if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        };
+        if child.is_empty() {
+            lints.push(Lint::new(
+                LintKind::EmptyTable,
+                format!("the table [{child_path}] is empty"),
+            ));
+        } else {
+            lint_empty_tables(child, &child_path, lints);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_lint() {
+        let manifest = Manifest::from_str(
+            Path::new(""),
+            r#"
+        [project]
+        name = "foo"
+        channels = []
+        platforms = ["linux-64"]
+
+        [dependencies]
+        python = "3.11.*"
+        numpy = "*"
+
+        [tasks]
+        python = "python main.py"
+
+        [host-dependencies]
+
+        [pypi-dependencies]
+
+        [feature.test.dependencies]
+        python = "3.12.*"
+
+        [feature.unused]
+        platforms = ["linux-64", "osx-64"]
+
+        [feature.unused.dependencies]
+        pytest = ">=7"
+
+        [feature.unused.target.osx-64.dependencies]
+        clang = ">=16"
+
+        [feature.unused.target.linux-64.pypi-dependencies]
+
+        [environments]
+        test = ["test"]
+        "#,
+        )
+        .unwrap();
+
+        let lints = manifest.lint(|name| name == "python");
+        let kinds = lints.iter().map(|lint| lint.kind).collect_vec();
+        assert_eq!(
+            kinds,
+            vec![
+                LintKind::UnpinnedDependency,
+                LintKind::DuplicateDependency,
+                LintKind::UnusedFeature,
+                LintKind::ShadowedExecutable,
+                LintKind::UnsupportedPlatform,
+                LintKind::EmptyTable,
+            ],
+            "{lints:#?}"
+        );
+        assert_eq!(
+            lints[0].message,
+            "`numpy` in [dependencies] has no version constraint, consider pinning it"
+        );
+        assert_eq!(
+            lints[1].message,
+            "`python` is declared in [dependencies] of multiple features: default, test"
+        );
+        assert_eq!(lints[5].message, "the table [host-dependencies] is empty");
+
+        // An empty pypi-dependencies table enables pypi support, so it is not reported
+        assert!(!lints
+            .iter()
+            .any(|lint| lint.message.contains(consts::PYPI_DEPENDENCIES)));
+    }
+
+    #[test]
+    fn test_lint_unsupported_platforms() {
+        let manifest = Manifest::from_str(
+            Path::new(""),
+            r#"
+        [project]
+        name = "foo"
+        channels = []
+        platforms = ["linux-64", "osx-64"]
+
+        [target.osx-64.dependencies]
+        clang = ">=16"
+
+        [feature.linux]
+        platforms = ["linux-64"]
+
+        [feature.linux.target.linux-64.dependencies]
+        gcc = ">=12"
+
+        [environments]
+        default = ["linux"]
+        "#,
+        )
+        .unwrap();
+
+        let lints = manifest.lint(|_| false);
+        assert_eq!(
+            lints.iter().map(|lint| &lint.message).collect_vec(),
+            vec!["[target.osx-64] targets `osx-64` which is not a platform of any environment"],
+            "{lints:#?}"
+        );
+    }
+
+    #[test]
+    fn test_lint_with_context() {
+        // A workspace member whose features are used by the environments of the workspace
+        let manifest = Manifest::from_str(
+            Path::new(""),
+            r#"
+        [project]
+        name = "member"
+        channels = []
+        platforms = ["linux-64"]
+
+        [feature.test]
+        platforms = ["linux-64", "win-64"]
+
+        [feature.test.dependencies]
+        pytest = ">=7"
+
+        [feature.test.target.win-64.dependencies]
+        pywin32 = ">=306"
+        "#,
+        )
+        .unwrap();
+
+        let kinds = |lints: Vec<Lint>| lints.iter().map(|lint| lint.kind).collect_vec();
+        assert_eq!(
+            kinds(manifest.lint(|_| false)),
+            vec![LintKind::UnusedFeature, LintKind::UnsupportedPlatform]
+        );
+
+        let context = LintContext {
+            used_features: HashSet::from([String::from("test")]),
+            platforms: HashSet::from([Platform::Linux64, Platform::Win64]),
+        };
+        assert!(manifest.lint_with_context(&context, |_| false).is_empty());
+    }
+}
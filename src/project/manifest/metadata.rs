@@ -1,5 +1,6 @@
-use crate::utils::spanned::PixiSpanned;
+use crate::utils::{schema, spanned::PixiSpanned};
 use rattler_conda_types::{Platform, Version};
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_with::{serde_as, DisplayFromStr};
 use std::path::PathBuf;
@@ -7,14 +8,17 @@ use url::Url;
 
 /// Describes the contents of the `[package]` section of the project manifest.
 #[serde_as]
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields, rename_all = "kebab-case")]
 pub struct ProjectMetadata {
-    /// The name of the project
+    /// The name of the project. In a `pyproject.toml` it can be omitted in favor of the name in
+    /// the `[project]` table, so a missing name is reported by the validation of the manifest.
+    #[serde(default)]
     pub name: String,
 
     /// The version of the project
     #[serde_as(as = "Option<DisplayFromStr>")]
+    #[schemars(with = "Option<String>")]
     pub version: Option<Version>,
 
     /// An optional project description
@@ -26,13 +30,28 @@ pub struct ProjectMetadata {
 
     /// The channels used by the project
     #[serde_as(as = "Vec<super::channel::TomlPrioritizedChannelStrOrMap>")]
+    #[schemars(with = "Vec<super::channel::TomlPrioritizedChannelStrOrMap>")]
     pub channels: Vec<super::channel::PrioritizedChannel>,
 
+    /// How packages that are available in more than one channel are treated by the solver
+    #[serde(default)]
+    pub channel_priority: super::ChannelPriority,
+
     /// The platforms this project supports
     // TODO: This is actually slightly different from the rattler_conda_types::Platform because it
     //     should not include noarch.
+    #[schemars(schema_with = "schema::platforms")]
     pub platforms: PixiSpanned<Vec<Platform>>,
 
+    /// Patterns of packages that are never selected by the solver, in the form `name`,
+    /// `name=version` or `name=version=build`.
+    #[serde(default)]
+    pub exclude: Vec<super::ExcludePattern>,
+
+    /// Packages that were published after this date or timestamp are never selected by the
+    /// solver, e.g. `2024-01-01`.
+    pub exclude_newer: Option<super::ExcludeNewer>,
+
     /// The license as a valid SPDX string (e.g. MIT AND Apache-2.0)
     pub license: Option<String>,
 
@@ -50,4 +69,9 @@ pub struct ProjectMetadata {
 
     /// URL of the project documentation
     pub documentation: Option<Url>,
+
+    /// Dotenv files (relative to the project root) to load into the environment of tasks and
+    /// shells. Files that do not exist are skipped.
+    #[serde(default)]
+    pub env_file: Vec<PathBuf>,
 }
@@ -2,28 +2,46 @@ mod activation;
 mod channel;
 mod environment;
 mod error;
+mod exclude;
+mod exclude_newer;
 mod feature;
+mod format;
+mod license_policy;
+mod lint;
 mod metadata;
+mod pyproject;
 mod python;
 mod system_requirements;
 mod target;
 mod validation;
+mod workspace;
 
 use crate::project::manifest::channel::PrioritizedChannel;
 use crate::project::manifest::environment::TomlEnvironmentMapOrSeq;
-use crate::{consts, project::SpecType, task::Task, utils::spanned::PixiSpanned};
+use crate::{
+    consts,
+    project::{metadata::ManifestVariables, SpecType},
+    task::Task,
+    utils::{schema, spanned::PixiSpanned},
+};
 use ::serde::{Deserialize, Deserializer};
 pub use activation::Activation;
+pub use channel::ChannelPriority;
 pub use environment::{Environment, EnvironmentName};
+pub use exclude::ExcludePattern;
+pub use exclude_newer::ExcludeNewer;
 pub use feature::{Feature, FeatureName};
 use indexmap::{Equivalent, IndexMap};
 use itertools::Itertools;
+pub use license_policy::{parse_license_expression, LicensePolicy, LicenseStatus};
+pub use lint::{Lint, LintContext, LintKind};
 pub use metadata::ProjectMetadata;
-use miette::{miette, Diagnostic, IntoDiagnostic, LabeledSpan, NamedSource};
+use miette::{miette, Diagnostic, IntoDiagnostic, LabeledSpan, NamedSource, WrapErr};
 pub use python::PyPiRequirement;
 use rattler_conda_types::{
     Channel, ChannelConfig, MatchSpec, NamelessMatchSpec, PackageName, Platform, Version,
 };
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde_with::{serde_as, DisplayFromStr, Map, PickFirst};
 use std::hash::Hash;
 use std::{
@@ -35,6 +53,7 @@ pub use system_requirements::{LibCFamilyAndVersion, LibCSystemRequirement, Syste
 pub use target::{Target, TargetSelector, Targets};
 use thiserror::Error;
 use toml_edit::{value, Array, Document, Item, Table, TomlError, Value};
+pub use workspace::Workspace;
 
 /// Errors that can occur when getting a feature.
 #[derive(Debug, Clone, Error, Diagnostic)]
@@ -43,6 +62,61 @@ pub enum GetFeatureError {
     FeatureDoesNotExist(FeatureName),
 }
 
+/// The kind of file that holds the project manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestKind {
+    /// A `pixi.toml` file.
+    Pixi,
+
+    /// A `pyproject.toml` file with a `[tool.pixi]` table.
+    Pyproject,
+}
+
+impl ManifestKind {
+    /// Determines the kind of manifest from the file name of `path`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.file_name()?.to_str()? {
+            consts::PROJECT_MANIFEST => Some(Self::Pixi),
+            consts::PYPROJECT_MANIFEST => Some(Self::Pyproject),
+            _ => None,
+        }
+    }
+
+    /// Returns the file name of this kind of manifest.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Pixi => consts::PROJECT_MANIFEST,
+            Self::Pyproject => consts::PYPROJECT_MANIFEST,
+        }
+    }
+}
+
+/// Returns the path of the project manifest in `dir`, if any. A `pixi.toml` takes precedence over
+/// a `pyproject.toml`, which is only a project manifest if it contains a `[tool.pixi]` table.
+pub fn find_manifest(dir: &Path) -> Option<PathBuf> {
+    let pixi_toml = dir.join(consts::PROJECT_MANIFEST);
+    if pixi_toml.is_file() {
+        return Some(pixi_toml);
+    }
+
+    let pyproject_toml = dir.join(consts::PYPROJECT_MANIFEST);
+    (pyproject_toml.is_file() && pyproject::has_pixi_table(&pyproject_toml))
+        .then_some(pyproject_toml)
+}
+
+/// Converts an error that occurred while parsing a toml file into a report that points at the
+/// location of the error in the source.
+fn toml_error_report(error: TomlError, name: &str, contents: String) -> miette::Report {
+    match error.span() {
+        Some(span) => miette::miette!(
+            labels = vec![LabeledSpan::at(span, error.message())],
+            "failed to parse project manifest"
+        )
+        .with_source_code(NamedSource::new(name, contents)),
+        None => miette::Report::msg(error.to_string()),
+    }
+}
+
 /// Handles the project's manifest file.
 /// This struct is responsible for reading, parsing, editing, and saving the manifest.
 /// It encapsulates all logic related to the manifest's TOML format and structure.
@@ -54,6 +128,9 @@ pub struct Manifest {
     /// The path to the manifest file
     pub path: PathBuf,
 
+    /// The kind of file the manifest was read from
+    pub kind: ManifestKind,
+
     /// The raw contents of the manifest file
     pub contents: String,
 
@@ -65,35 +142,32 @@ pub struct Manifest {
 }
 
 impl Manifest {
-    /// Create a new manifest from a path
+    /// Create a new manifest from a path. The path either points to a `pixi.toml` or to a
+    /// `pyproject.toml` that contains a `[tool.pixi]` table.
     pub fn from_path(path: impl AsRef<Path>) -> miette::Result<Self> {
         let contents = std::fs::read_to_string(path.as_ref()).into_diagnostic()?;
         let parent = path
             .as_ref()
             .parent()
             .expect("Path should always have a parent");
-        Self::from_str(parent, contents)
+        match ManifestKind::from_path(path.as_ref()) {
+            Some(ManifestKind::Pyproject) => Self::from_pyproject_str(parent, contents),
+            _ => Self::from_str(parent, contents),
+        }
     }
 
     /// Create a new manifest from a string
     pub fn from_str(root: &Path, contents: impl Into<String>) -> miette::Result<Self> {
         let contents = contents.into();
-        let (manifest, document) = match ProjectManifest::from_toml_str(&contents)
+        let (mut manifest, document) = match ProjectManifest::from_toml_str(&contents)
             .and_then(|manifest| contents.parse::<Document>().map(|doc| (manifest, doc)))
         {
             Ok(result) => result,
-            Err(e) => {
-                if let Some(span) = e.span() {
-                    return Err(miette::miette!(
-                        labels = vec![LabeledSpan::at(span, e.message())],
-                        "failed to parse project manifest"
-                    )
-                    .with_source_code(NamedSource::new(consts::PROJECT_MANIFEST, contents)));
-                } else {
-                    return Err(e).into_diagnostic();
-                }
-            }
+            Err(e) => return Err(toml_error_report(e, consts::PROJECT_MANIFEST, contents)),
         };
+        manifest.expand_channel_variables(
+            &ManifestVariables::new(root).with_source(consts::PROJECT_MANIFEST, &contents),
+        )?;
 
         // Validate the contents of the manifest
         manifest.validate(
@@ -101,6 +175,51 @@ impl Manifest {
             root,
         )?;
 
+        Ok(Self::from_parts(
+            root,
+            ManifestKind::Pixi,
+            contents,
+            document,
+            manifest,
+        ))
+    }
+
+    /// Create a new manifest from the contents of a `pyproject.toml` file. The `[tool.pixi]` table
+    /// is combined with the standard `[project]` table, see [`pyproject::PyProjectToml`].
+    pub fn from_pyproject_str(root: &Path, contents: impl Into<String>) -> miette::Result<Self> {
+        let contents = contents.into();
+        let (pyproject, document) = match pyproject::PyProjectToml::from_toml_str(&contents)
+            .and_then(|pyproject| contents.parse::<Document>().map(|doc| (pyproject, doc)))
+        {
+            Ok(result) => result,
+            Err(e) => return Err(toml_error_report(e, consts::PYPROJECT_MANIFEST, contents)),
+        };
+        let mut manifest = pyproject.into_manifest(&contents)?;
+        manifest.expand_channel_variables(
+            &ManifestVariables::new(root).with_source(consts::PYPROJECT_MANIFEST, &contents),
+        )?;
+        manifest.validate(
+            NamedSource::new(consts::PYPROJECT_MANIFEST, contents.to_owned()),
+            root,
+        )?;
+
+        Ok(Self::from_parts(
+            root,
+            ManifestKind::Pyproject,
+            contents,
+            document,
+            manifest,
+        ))
+    }
+
+    /// Constructs a manifest from its parsed parts.
+    fn from_parts(
+        root: &Path,
+        kind: ManifestKind,
+        contents: String,
+        document: Document,
+        manifest: ProjectManifest,
+    ) -> Self {
         // Notify the user that pypi-dependencies are still experimental
         if manifest
             .features
@@ -116,12 +235,55 @@ impl Manifest {
             }
         }
 
-        Ok(Self {
-            path: root.join(consts::PROJECT_MANIFEST),
+        Self {
+            path: root.join(kind.file_name()),
+            kind,
             contents,
             document,
             parsed: manifest,
-        })
+        }
+    }
+
+    /// Returns the table of the document that contains the pixi configuration. For a `pixi.toml`
+    /// this is the whole document, for a `pyproject.toml` this is the `[tool.pixi]` table.
+    fn pixi_table(&self) -> &Table {
+        match self.kind {
+            ManifestKind::Pixi => self.document.as_table(),
+            ManifestKind::Pyproject => {
+                pyproject::pixi_table(&self.document).expect("the [tool.pixi] table should exist")
+            }
+        }
+    }
+
+    /// Returns the mutable table of the document that contains the pixi configuration, see
+    /// [`Self::pixi_table`].
+    fn pixi_table_mut(&mut self) -> &mut Table {
+        match self.kind {
+            ManifestKind::Pixi => self.document.as_table_mut(),
+            ManifestKind::Pyproject => self.document["tool"]["pixi"]
+                .as_table_mut()
+                .expect("the [tool.pixi] table should exist"),
+        }
+    }
+
+    /// Sets a field of the project metadata. In a `pyproject.toml` the field is stored in the
+    /// standard `[project]` table, unless it is overwritten in `[tool.pixi.project]`.
+    fn set_project_field(&mut self, key: &str, item: Item) {
+        let in_pyproject_table = self.kind == ManifestKind::Pyproject
+            && self
+                .document
+                .as_table()
+                .get("project")
+                .map_or(false, Item::is_table)
+            && pyproject::pixi_table(&self.document)
+                .and_then(|pixi| pixi.get("project"))
+                .and_then(|project| project.get(key))
+                .is_none();
+        if in_pyproject_table {
+            self.document["project"][key] = item;
+        } else {
+            self.pixi_table_mut()["project"][key] = item;
+        }
     }
 
     /// Save the manifest to the file and update the contents
@@ -168,7 +330,8 @@ impl Manifest {
         }
 
         // Get the table that contains the tasks.
-        let table = get_or_insert_toml_table(&mut self.document, platform, feature_name, "tasks")?;
+        let table =
+            get_or_insert_toml_table(self.pixi_table_mut(), platform, feature_name, "tasks")?;
 
         // Add the task to the table
         table.insert(name.as_ref(), task.clone().into());
@@ -196,7 +359,7 @@ impl Manifest {
 
         // Get the task table either from the target platform or the default tasks.
         let tasks_table =
-            get_or_insert_toml_table(&mut self.document, platform, feature_name, "tasks")?;
+            get_or_insert_toml_table(self.pixi_table_mut(), platform, feature_name, "tasks")?;
 
         // If it does not exist in toml, consider this ok as we want to remove it anyways
         tasks_table.remove(name.as_ref());
@@ -217,7 +380,7 @@ impl Manifest {
         platforms: impl Iterator<Item = &'a Platform> + Clone,
     ) -> miette::Result<()> {
         // Add to platform table
-        let platform_array = &mut self.document["project"]["platforms"];
+        let platform_array = &mut self.pixi_table_mut()["project"]["platforms"];
         let platform_array = platform_array
             .as_array_mut()
             .expect("platforms should be an array");
@@ -258,7 +421,7 @@ impl Manifest {
         }
 
         // remove the platforms from the toml
-        let platform_array = &mut self.document["project"]["platforms"];
+        let platform_array = &mut self.pixi_table_mut()["project"]["platforms"];
         let platform_array = platform_array
             .as_array_mut()
             .expect("platforms should be an array");
@@ -277,7 +440,7 @@ impl Manifest {
     ) -> miette::Result<()> {
         // Find the table toml table to add the dependency to.
         let dependency_table = get_or_insert_toml_table(
-            &mut self.document,
+            self.pixi_table_mut(),
             platform,
             &FeatureName::Default,
             spec_type.name(),
@@ -309,16 +472,43 @@ impl Manifest {
         requirement: &PyPiRequirement,
         platform: Option<Platform>,
     ) -> miette::Result<()> {
-        // Find the table toml table to add the dependency to.
-        let dependency_table = get_or_insert_toml_table(
-            &mut self.document,
-            platform,
-            &FeatureName::Default,
-            consts::PYPI_DEPENDENCIES,
-        )?;
+        // In a pyproject.toml the pypi dependencies of the project are stored as PEP 508
+        // requirements in the `[project]` table.
+        let requirements = match (self.kind, platform) {
+            (ManifestKind::Pyproject, None) => {
+                pyproject::dependencies_array_mut(&mut self.document)
+            }
+            _ => None,
+        };
 
-        // Add the pypi dependency to the table
-        dependency_table.insert(name.as_str(), (*requirement).clone().into());
+        let mut requirement = requirement.clone();
+        match requirements {
+            Some(requirements) => {
+                requirement = pyproject::insert_requirement(requirements, name, &requirement);
+
+                // Remove the dependency from `[tool.pixi.pypi-dependencies]` because it would
+                // take precedence over the requirement.
+                if let Some(table) = self
+                    .pixi_table_mut()
+                    .get_mut(consts::PYPI_DEPENDENCIES)
+                    .and_then(Item::as_table_like_mut)
+                {
+                    table.remove(name.as_source_str());
+                }
+            }
+            None => {
+                // Find the table toml table to add the dependency to.
+                let dependency_table = get_or_insert_toml_table(
+                    self.pixi_table_mut(),
+                    platform,
+                    &FeatureName::Default,
+                    consts::PYPI_DEPENDENCIES,
+                )?;
+
+                // Add the pypi dependency to the table
+                dependency_table.insert(name.as_str(), requirement.clone().into());
+            }
+        }
 
         // Add the dependency to the manifest as well
         self.default_feature_mut()
@@ -326,7 +516,7 @@ impl Manifest {
             .for_opt_target_or_default_mut(platform.map(TargetSelector::from).as_ref())
             .pypi_dependencies
             .get_or_insert_with(Default::default)
-            .insert(name.clone(), requirement.clone());
+            .insert(name.clone(), requirement);
 
         Ok(())
     }
@@ -339,17 +529,21 @@ impl Manifest {
         platform: Option<Platform>,
         feature_name: &FeatureName,
     ) -> miette::Result<(PackageName, NamelessMatchSpec)> {
-        get_or_insert_toml_table(&mut self.document, platform, feature_name, spec_type.name())?
-            .remove(dep.as_normalized())
-            .ok_or_else(|| {
-                let table_name =
-                    get_nested_toml_table_name(feature_name, platform, spec_type.name());
-                miette::miette!(
-                    "Couldn't find {} in [{}]",
-                    console::style(dep.as_source()).bold(),
-                    console::style(table_name).bold(),
-                )
-            })?;
+        get_or_insert_toml_table(
+            self.pixi_table_mut(),
+            platform,
+            feature_name,
+            spec_type.name(),
+        )?
+        .remove(dep.as_normalized())
+        .ok_or_else(|| {
+            let table_name = get_nested_toml_table_name(feature_name, platform, spec_type.name());
+            miette::miette!(
+                "Couldn't find {} in [{}]",
+                console::style(dep.as_source()).bold(),
+                console::style(table_name).bold(),
+            )
+        })?;
 
         Ok(self
             .parsed
@@ -370,23 +564,31 @@ impl Manifest {
         platform: Option<Platform>,
         feature_name: &FeatureName,
     ) -> miette::Result<(rip::types::PackageName, PyPiRequirement)> {
-        get_or_insert_toml_table(
-            &mut self.document,
+        // In a pyproject.toml the dependency might be defined in the `[project]` table.
+        let removed_requirement = self.kind == ManifestKind::Pyproject
+            && platform.is_none()
+            && pyproject::requirements_array_mut(&mut self.document, feature_name)
+                .map_or(false, |requirements| {
+                    pyproject::remove_requirement(requirements, dep)
+                });
+
+        let removed_item = get_or_insert_toml_table(
+            self.pixi_table_mut(),
             platform,
             feature_name,
             consts::PYPI_DEPENDENCIES,
         )?
-        .remove(dep.as_source_str())
-        .ok_or_else(|| {
+        .remove(dep.as_source_str());
+
+        if removed_item.is_none() && !removed_requirement {
             let table_name =
                 get_nested_toml_table_name(feature_name, platform, consts::PYPI_DEPENDENCIES);
-
-            miette::miette!(
+            miette::bail!(
                 "Couldn't find {} in [{}]",
                 console::style(dep.as_source_str()).bold(),
                 console::style(table_name).bold(),
-            )
-        })?;
+            );
+        }
 
         Ok(self
             .parsed
@@ -416,7 +618,7 @@ impl Manifest {
 
     /// Returns a mutable reference to the channels array.
     fn channels_array_mut(&mut self) -> miette::Result<&mut Array> {
-        let project = &mut self.document["project"];
+        let project = &mut self.pixi_table_mut()["project"];
         if project.is_none() {
             *project = Item::Table(Table::new());
         }
@@ -491,7 +693,7 @@ impl Manifest {
     pub fn set_description(&mut self, description: &String) -> miette::Result<()> {
         // Update in both the manifest and the toml
         self.parsed.project.description = Some(description.to_string());
-        self.document["project"]["description"] = value(description);
+        self.set_project_field("description", value(description));
 
         Ok(())
     }
@@ -500,7 +702,7 @@ impl Manifest {
     pub fn set_version(&mut self, version: &String) -> miette::Result<()> {
         // Update in both the manifest and the toml
         self.parsed.project.version = Some(Version::from_str(version).unwrap());
-        self.document["project"]["version"] = value(version);
+        self.set_project_field("version", value(version));
 
         Ok(())
     }
@@ -534,6 +736,29 @@ impl Manifest {
         self.parsed.features.get(name)
     }
 
+    /// Expands the variables in the paths of local channels, like
+    /// `${PIXI_PROJECT_ROOT}/local-channel`, see [`ManifestVariables`].
+    fn expand_channel_variables(&mut self, variables: &ManifestVariables) -> miette::Result<()> {
+        let feature_channels = self
+            .features
+            .values_mut()
+            .filter_map(|feature| feature.channels.as_mut())
+            .flatten();
+        for prioritized in self.project.channels.iter_mut().chain(feature_channels) {
+            let Some(name) = prioritized.channel.name.as_deref() else {
+                continue;
+            };
+            if !name.contains('$') {
+                continue;
+            }
+            let expanded = variables.expand(name)?;
+            prioritized.channel = Channel::from_str(&expanded, &ChannelConfig::default())
+                .into_diagnostic()
+                .wrap_err_with(|| format!("failed to parse the channel `{expanded}`"))?;
+        }
+        Ok(())
+    }
+
     /// Returns the default environment
     ///
     /// This is the environment that is added implicitly as the environment with only the default
@@ -580,7 +805,7 @@ fn get_nested_toml_table_name(
 /// for a specific platform.
 /// If table not found, its inserted into the document.
 fn get_or_insert_toml_table<'a>(
-    doc: &'a mut Document,
+    table: &'a mut Table,
     platform: Option<Platform>,
     feature: &FeatureName,
     table_name: &str,
@@ -588,7 +813,7 @@ fn get_or_insert_toml_table<'a>(
     let table_name = get_nested_toml_table_name(feature, platform, table_name);
     let parts: Vec<&str> = table_name.split('.').collect();
 
-    let mut current_table = doc.as_table_mut();
+    let mut current_table = table;
     for (i, part) in parts.iter().enumerate() {
         current_table = current_table
             .entry(part)
@@ -619,6 +844,12 @@ pub struct ProjectManifest {
 
     /// All the environments defined in the project.
     pub environments: IndexMap<EnvironmentName, Environment>,
+
+    /// The workspace defined by the project, if any.
+    pub workspace: Option<Workspace>,
+
+    /// The licenses that are allowed for the packages of the project.
+    pub license_policy: LicensePolicy,
 }
 
 impl ProjectManifest {
@@ -658,62 +889,106 @@ impl ProjectManifest {
     }
 }
 
+/// The structure of the project manifest as it is written in the toml file.
+#[serde_as]
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct TomlProjectManifest {
+    /// Information about the project.
+    project: ProjectMetadata,
+
+    /// The system requirements of the default feature.
+    #[serde(default)]
+    system_requirements: SystemRequirements,
+
+    /// Platform specific configuration of the default feature, e.g. `[target.linux-64]`.
+    #[serde(default)]
+    target: IndexMap<PixiSpanned<TargetSelector>, Target>,
+
+    // HACK: If we use `flatten`, unknown keys will point to the wrong location in the file.
+    //  When https://github.com/toml-rs/toml/issues/589 is fixed we should use that
+    //
+    // Instead we currently copy the keys from the Target deserialize implementation which
+    // is really ugly.
+    //
+    // #[serde(flatten)]
+    // default_target: Target,
+    /// The conda packages required to run the project.
+    #[serde(default)]
+    #[serde_as(as = "IndexMap<_, PickFirst<(DisplayFromStr, _)>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    dependencies: IndexMap<PackageName, NamelessMatchSpec>,
+
+    /// The conda packages required on the host when building the project.
+    #[serde(default)]
+    #[serde_as(as = "Option<IndexMap<_, PickFirst<(DisplayFromStr, _)>>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    host_dependencies: Option<IndexMap<PackageName, NamelessMatchSpec>>,
+
+    /// The conda packages required on the build machine when building the project.
+    #[serde(default)]
+    #[serde_as(as = "Option<IndexMap<_, PickFirst<(DisplayFromStr, _)>>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    build_dependencies: Option<IndexMap<PackageName, NamelessMatchSpec>>,
+
+    /// The PyPI packages required to run the project.
+    #[serde(default)]
+    pypi_dependencies: Option<IndexMap<rip::types::PackageName, PyPiRequirement>>,
+
+    /// Constraints on the versions of the conda packages used by the project.
+    #[serde(default)]
+    #[serde_as(as = "IndexMap<_, PickFirst<(DisplayFromStr, _)>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    constraints: IndexMap<PackageName, NamelessMatchSpec>,
+
+    /// Overrides of the requirements on conda packages used by the project.
+    #[serde(default)]
+    #[serde_as(as = "IndexMap<_, PickFirst<(DisplayFromStr, _)>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    overrides: IndexMap<PackageName, NamelessMatchSpec>,
+
+    /// Additional information to activate an environment.
+    #[serde(default)]
+    activation: Option<Activation>,
+
+    /// Target specific tasks to run in the environment
+    #[serde(default)]
+    tasks: HashMap<String, Task>,
+
+    /// The features defined in the project.
+    #[serde(default)]
+    feature: IndexMap<FeatureName, Feature>,
+
+    /// The environments the project can create.
+    #[serde(default)]
+    #[serde_as(as = "Map<_, _>")]
+    #[schemars(with = "IndexMap<String, TomlEnvironmentMapOrSeq>")]
+    environments: Vec<(EnvironmentName, TomlEnvironmentMapOrSeq)>,
+
+    /// The other projects that are part of the workspace of this project.
+    #[serde(default)]
+    workspace: Option<Workspace>,
+
+    /// The licenses that are allowed for the packages of the project.
+    #[serde(default)]
+    license_policy: LicensePolicy,
+}
+
+impl JsonSchema for ProjectManifest {
+    fn schema_name() -> String {
+        String::from("ProjectManifest")
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        TomlProjectManifest::json_schema(gen)
+    }
+}
+
 impl<'de> Deserialize<'de> for ProjectManifest {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        #[serde_as]
-        #[derive(Deserialize)]
-        #[serde(deny_unknown_fields, rename_all = "kebab-case")]
-        pub struct TomlProjectManifest {
-            project: ProjectMetadata,
-            #[serde(default)]
-            system_requirements: SystemRequirements,
-            #[serde(default)]
-            target: IndexMap<PixiSpanned<TargetSelector>, Target>,
-
-            // HACK: If we use `flatten`, unknown keys will point to the wrong location in the file.
-            //  When https://github.com/toml-rs/toml/issues/589 is fixed we should use that
-            //
-            // Instead we currently copy the keys from the Target deserialize implementation which
-            // is really ugly.
-            //
-            // #[serde(flatten)]
-            // default_target: Target,
-            #[serde(default)]
-            #[serde_as(as = "IndexMap<_, PickFirst<(DisplayFromStr, _)>>")]
-            dependencies: IndexMap<PackageName, NamelessMatchSpec>,
-
-            #[serde(default)]
-            #[serde_as(as = "Option<IndexMap<_, PickFirst<(DisplayFromStr, _)>>>")]
-            host_dependencies: Option<IndexMap<PackageName, NamelessMatchSpec>>,
-
-            #[serde(default)]
-            #[serde_as(as = "Option<IndexMap<_, PickFirst<(DisplayFromStr, _)>>>")]
-            build_dependencies: Option<IndexMap<PackageName, NamelessMatchSpec>>,
-
-            #[serde(default)]
-            pypi_dependencies: Option<IndexMap<rip::types::PackageName, PyPiRequirement>>,
-
-            /// Additional information to activate an environment.
-            #[serde(default)]
-            activation: Option<Activation>,
-
-            /// Target specific tasks to run in the environment
-            #[serde(default)]
-            tasks: HashMap<String, Task>,
-
-            /// The features defined in the project.
-            #[serde(default)]
-            feature: IndexMap<FeatureName, Feature>,
-
-            /// The environments the project can create.
-            #[serde(default)]
-            #[serde_as(as = "Map<_, _>")]
-            environments: Vec<(EnvironmentName, TomlEnvironmentMapOrSeq)>,
-        }
-
         let toml_manifest = TomlProjectManifest::deserialize(deserializer)?;
 
         let mut dependencies = HashMap::from_iter([(SpecType::Run, toml_manifest.dependencies)]);
@@ -727,6 +1002,8 @@ impl<'de> Deserialize<'de> for ProjectManifest {
         let default_target = Target {
             dependencies,
             pypi_dependencies: toml_manifest.pypi_dependencies,
+            constraints: toml_manifest.constraints,
+            overrides: toml_manifest.overrides,
             activation: toml_manifest.activation,
             tasks: toml_manifest.tasks,
         };
@@ -739,6 +1016,7 @@ impl<'de> Deserialize<'de> for ProjectManifest {
             // metadata.
             platforms: None,
             channels: None,
+            exclude: Vec::new(),
 
             system_requirements: toml_manifest.system_requirements,
 
@@ -784,6 +1062,8 @@ impl<'de> Deserialize<'de> for ProjectManifest {
             project: toml_manifest.project,
             features,
             environments,
+            workspace: toml_manifest.workspace,
+            license_policy: toml_manifest.license_policy,
         })
     }
 }
@@ -1508,6 +1788,30 @@ feature_target_dep = "*"
         );
     }
 
+    #[test]
+    fn test_channel_variables() {
+        let file_contents = r#"
+            [project]
+            name = "foo"
+            channels = ["${PIXI_PROJECT_ROOT}/local-channel", "conda-forge"]
+            platforms = ["linux-64"]
+        "#;
+
+        let manifest = Manifest::from_str(Path::new("/project"), file_contents).unwrap();
+        assert_eq!(
+            manifest.parsed.project.channels[0].channel,
+            Channel::from_str("/project/local-channel", &ChannelConfig::default()).unwrap()
+        );
+
+        let file_contents = r#"
+            [project]
+            name = "foo"
+            channels = ["${PIXI_PLATFORM}/local-channel"]
+            platforms = ["linux-64"]
+        "#;
+        assert!(Manifest::from_str(Path::new("/project"), file_contents).is_err());
+    }
+
     #[test]
     fn test_remove_channels() {
         // Using known files in the project so the test succeed including the file check.
@@ -1841,22 +2145,26 @@ test = "test initial"
     #[test]
     fn test_get_or_insert_toml_table() {
         let mut manifest = Manifest::from_str(Path::new(""), PROJECT_BOILERPLATE).unwrap();
-        let _ =
-            get_or_insert_toml_table(&mut manifest.document, None, &FeatureName::Default, "tasks");
         let _ = get_or_insert_toml_table(
-            &mut manifest.document,
+            manifest.document.as_table_mut(),
+            None,
+            &FeatureName::Default,
+            "tasks",
+        );
+        let _ = get_or_insert_toml_table(
+            manifest.document.as_table_mut(),
             Some(Platform::Linux64),
             &FeatureName::Default,
             "tasks",
         );
         let _ = get_or_insert_toml_table(
-            &mut manifest.document,
+            manifest.document.as_table_mut(),
             None,
             &FeatureName::Named("test".to_string()),
             "tasks",
         );
         let _ = get_or_insert_toml_table(
-            &mut manifest.document,
+            manifest.document.as_table_mut(),
             Some(Platform::Linux64),
             &FeatureName::Named("test".to_string()),
             "tasks",
@@ -0,0 +1,444 @@
+//! Support for project manifests that are stored in the `[tool.pixi]` table of a `pyproject.toml`.
+//!
+//! The `[tool.pixi]` table has the same structure as a `pixi.toml`. Besides that, the standard
+//! `[project]` table is used: its name, version and description become the project metadata, its
+//! `dependencies` become pypi-dependencies and every group of `optional-dependencies` becomes a
+//! feature with the same name.
+
+use super::{Feature, FeatureName, ProjectManifest, PyPiRequirement};
+use crate::{consts, utils::spanned::PixiSpanned};
+use indexmap::IndexMap;
+use miette::{LabeledSpan, NamedSource};
+use rattler_conda_types::Version;
+use serde::Deserialize;
+use std::{path::Path, str::FromStr};
+use toml_edit::{Array, Document, Item, Table, TomlError, Value};
+
+/// The tables of a `pyproject.toml` that are used by pixi. Everything else is ignored.
+#[derive(Deserialize)]
+pub struct PyProjectToml {
+    project: Option<PyProjectMetadata>,
+    tool: Option<PyProjectTools>,
+}
+
+#[derive(Deserialize)]
+struct PyProjectTools {
+    pixi: Option<ProjectManifest>,
+}
+
+/// The fields of the standard `[project]` table that are used by pixi.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PyProjectMetadata {
+    name: Option<String>,
+    version: Option<PixiSpanned<String>>,
+    description: Option<String>,
+    dependencies: Option<Vec<PixiSpanned<String>>>,
+    #[serde(default)]
+    optional_dependencies: IndexMap<String, Vec<PixiSpanned<String>>>,
+}
+
+/// Returns true if the `pyproject.toml` at `path` contains a `[tool.pixi]` table.
+pub fn has_pixi_table(path: &Path) -> bool {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| contents.parse::<Document>().ok())
+        .map_or(false, |document| pixi_table(&document).is_some())
+}
+
+/// Returns the `[tool.pixi]` table of a `pyproject.toml` document.
+pub fn pixi_table(document: &Document) -> Option<&Table> {
+    document
+        .as_table()
+        .get("tool")?
+        .as_table()?
+        .get("pixi")?
+        .as_table()
+}
+
+impl PyProjectToml {
+    /// Parses the contents of a `pyproject.toml`. The `[tool.pixi]` table is parsed in place, so
+    /// errors point into the `pyproject.toml` itself.
+    pub fn from_toml_str(source: &str) -> Result<Self, TomlError> {
+        toml_edit::de::from_str(source).map_err(TomlError::from)
+    }
+
+    /// Combines the `[tool.pixi]` table with the `[project]` table into a project manifest.
+    /// Values in `[tool.pixi]` take precedence over the values derived from the `[project]` table.
+    ///
+    /// The `contents` of the `pyproject.toml` are used to report errors in the `[project]` table.
+    pub fn into_manifest(self, contents: &str) -> miette::Result<ProjectManifest> {
+        let Some(mut manifest) = self.tool.and_then(|tool| tool.pixi) else {
+            miette::bail!(
+                "the {} does not contain a [tool.pixi] table",
+                consts::PYPROJECT_MANIFEST
+            );
+        };
+        let Some(project) = self.project else {
+            return Ok(manifest);
+        };
+        let report = |span: Option<std::ops::Range<usize>>, message: String| {
+            miette::miette!(
+                labels = vec![LabeledSpan::at(span.unwrap_or_default(), message)],
+                "failed to parse the [project] table"
+            )
+            .with_source_code(NamedSource::new(
+                consts::PYPROJECT_MANIFEST,
+                contents.to_owned(),
+            ))
+        };
+
+        // Copy the project metadata
+        if manifest.project.name.is_empty() {
+            manifest.project.name = project.name.unwrap_or_default();
+        }
+        if manifest.project.version.is_none() {
+            if let Some(version) = project.version {
+                let parsed = Version::from_str(version.get_ref())
+                    .map_err(|e| report(version.span(), e.to_string()))?;
+                manifest.project.version = Some(parsed);
+            }
+        }
+        if manifest.project.description.is_none() {
+            manifest.project.description = project.description;
+        }
+
+        // Add the dependencies of the project as pypi-dependencies of the default feature and
+        // every group of optional dependencies as a feature.
+        let groups = project
+            .dependencies
+            .map(|requirements| (FeatureName::Default, requirements))
+            .into_iter()
+            .chain(
+                project
+                    .optional_dependencies
+                    .into_iter()
+                    .map(|(group, requirements)| (FeatureName::Named(group), requirements)),
+            );
+        for (feature_name, requirements) in groups {
+            let feature = manifest
+                .features
+                .entry(feature_name.clone())
+                .or_insert_with(|| Feature {
+                    name: feature_name,
+                    platforms: None,
+                    channels: None,
+                    exclude: Vec::new(),
+                    system_requirements: Default::default(),
+                    targets: Default::default(),
+                });
+            let pypi_dependencies = feature
+                .targets
+                .default_mut()
+                .pypi_dependencies
+                .get_or_insert_with(Default::default);
+            for requirement in requirements {
+                let Some((name, parsed)) = parse_requirement(requirement.get_ref())
+                    .map_err(|e| report(requirement.span(), e))?
+                else {
+                    continue;
+                };
+                pypi_dependencies.entry(name).or_insert(parsed);
+            }
+        }
+
+        Ok(manifest)
+    }
+}
+
+/// Parses a PEP 508 requirement of the `[project]` table into a pypi-dependency. Requirements
+/// that refer to a url are skipped with a warning because pypi-dependencies can not express them.
+fn parse_requirement(
+    requirement: &str,
+) -> Result<Option<(rip::types::PackageName, PyPiRequirement)>, String> {
+    let parsed = pep508_rs::Requirement::from_str(requirement).map_err(|e| e.to_string())?;
+    if let Some(pep508_rs::VersionOrUrl::Url(_)) = parsed.version_or_url {
+        tracing::warn!(
+            "the requirement `{requirement}` refers to a url, which is not supported. The requirement is ignored"
+        );
+        return Ok(None);
+    }
+    let name = rip::types::PackageName::from_str(&parsed.name).map_err(|e| e.to_string())?;
+    let marker = parsed.marker.clone();
+    Ok(Some((
+        name,
+        PyPiRequirement::from(parsed).with_marker(marker),
+    )))
+}
+
+/// Returns the array of PEP 508 requirements in the `[project]` table that holds the pypi
+/// dependencies of `feature`. These are the `dependencies` for the default feature and the group
+/// of `optional-dependencies` with the same name for other features.
+pub fn requirements_array_mut<'a>(
+    document: &'a mut Document,
+    feature: &FeatureName,
+) -> Option<&'a mut Array> {
+    let project = document.as_table_mut().get_mut("project")?;
+    let requirements = match feature {
+        FeatureName::Default => project.get_mut("dependencies")?,
+        FeatureName::Named(name) => project.get_mut("optional-dependencies")?.get_mut(name)?,
+    };
+    requirements.as_array_mut()
+}
+
+/// Returns the `dependencies` array of the `[project]` table, the array is created if it does not
+/// exist yet. Returns `None` if the document does not have a `[project]` table.
+pub fn dependencies_array_mut(document: &mut Document) -> Option<&mut Array> {
+    document
+        .as_table_mut()
+        .get_mut("project")?
+        .as_table_mut()?
+        .entry("dependencies")
+        .or_insert(Item::Value(Value::Array(Array::new())))
+        .as_array_mut()
+}
+
+/// Returns true if the requirement string `value` is a requirement on the package `name`.
+fn is_requirement_on(value: &Value, name: &rip::types::PackageName) -> bool {
+    value
+        .as_str()
+        .and_then(|requirement| pep508_rs::Requirement::from_str(requirement).ok())
+        .and_then(|requirement| rip::types::PackageName::from_str(&requirement.name).ok())
+        .map_or(false, |package| &package == name)
+}
+
+/// Adds `requirement` to the `requirements`, replacing an existing requirement on the same package.
+/// If the `requirement` has no environment marker, the marker of the existing requirement is kept.
+/// Returns the requirement that was written.
+pub fn insert_requirement(
+    requirements: &mut Array,
+    name: &rip::types::PackageName,
+    requirement: &PyPiRequirement,
+) -> PyPiRequirement {
+    let mut requirement = requirement.clone();
+    match requirements
+        .iter()
+        .position(|value| is_requirement_on(value, name))
+    {
+        Some(idx) => {
+            if requirement.marker.is_none() {
+                let marker = requirements
+                    .get(idx)
+                    .and_then(Value::as_str)
+                    .and_then(|existing| pep508_rs::Requirement::from_str(existing).ok())
+                    .and_then(|existing| existing.marker);
+                requirement = requirement.with_marker(marker);
+            }
+            requirements.replace(idx, requirement.as_pep508(name).to_string());
+        }
+        None => requirements.push(requirement.as_pep508(name).to_string()),
+    }
+    requirement
+}
+
+/// Removes the requirement on the package `name` from the `requirements`. Returns true if a
+/// requirement was removed.
+pub fn remove_requirement(requirements: &mut Array, name: &rip::types::PackageName) -> bool {
+    let len = requirements.len();
+    requirements.retain(|value| !is_requirement_on(value, name));
+    requirements.len() != len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::manifest::Manifest;
+    use miette::Diagnostic;
+
+    const PYPROJECT: &str = r#"
+[project]
+name = "my-package"
+version = "0.1.0"
+description = "A python package"
+dependencies = ["requests>=2.31", "rich", "pywin32; sys_platform == 'win32'"]
+
+[project.optional-dependencies]
+test = ["pytest>=7"]
+
+[tool.pixi.project]
+channels = ["conda-forge"]
+platforms = ["linux-64", "win-64"]
+
+[tool.pixi.dependencies]
+python = ">=3.10"
+
+[tool.pixi.environments]
+test = ["test"]
+"#;
+
+    /// Returns true if the first label of the `report` covers the first occurrence of `text` in
+    /// the `contents`.
+    fn label_covers(report: &miette::Report, contents: &str, text: &str) -> bool {
+        let label = report.labels().unwrap().next().unwrap();
+        let offset = contents.find(text).unwrap();
+        label.offset() <= offset && offset < label.offset() + label.len().max(1)
+    }
+
+    #[test]
+    fn test_from_pyproject_str() {
+        let manifest = Manifest::from_pyproject_str(Path::new(""), PYPROJECT).unwrap();
+        assert_eq!(manifest.parsed.project.name, "my-package");
+        assert_eq!(
+            manifest
+                .parsed
+                .project
+                .version
+                .as_ref()
+                .unwrap()
+                .to_string(),
+            "0.1.0"
+        );
+        assert!(manifest
+            .default_feature()
+            .targets
+            .default()
+            .run_dependencies()
+            .unwrap()
+            .contains_key(&rattler_conda_types::PackageName::new_unchecked("python")));
+
+        let pypi_dependencies = manifest
+            .default_feature()
+            .targets
+            .default()
+            .pypi_dependencies
+            .clone()
+            .unwrap();
+        assert_eq!(
+            pypi_dependencies
+                .keys()
+                .map(|name| name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["requests", "rich", "pywin32"]
+        );
+        assert_eq!(
+            pypi_dependencies
+                .values()
+                .map(|requirement| requirement.marker.as_ref().map(ToString::to_string))
+                .collect::<Vec<_>>(),
+            vec![None, None, Some(String::from("sys_platform == 'win32'"))]
+        );
+
+        let test = manifest.feature("test").unwrap();
+        assert!(test
+            .targets
+            .default()
+            .pypi_dependencies
+            .as_ref()
+            .unwrap()
+            .contains_key(&rip::types::PackageName::from_str("pytest").unwrap()));
+    }
+
+    #[test]
+    fn test_pixi_table_precedence() {
+        let pyproject =
+            format!("{PYPROJECT}\n[tool.pixi.pypi-dependencies]\nrequests = \">=2.0\"\n").replace(
+                "[tool.pixi.project]",
+                "[tool.pixi.project]\nname = \"other\"",
+            );
+        let manifest = Manifest::from_pyproject_str(Path::new(""), pyproject).unwrap();
+        assert_eq!(manifest.parsed.project.name, "other");
+        let requests = rip::types::PackageName::from_str("requests").unwrap();
+        assert_eq!(
+            manifest
+                .default_feature()
+                .targets
+                .default()
+                .pypi_dependencies
+                .as_ref()
+                .unwrap()[&requests]
+                .version
+                .as_ref()
+                .map(ToString::to_string),
+            Some(String::from(">=2.0"))
+        );
+    }
+
+    #[test]
+    fn test_errors_point_into_pyproject() {
+        // An error in the `[tool.pixi]` table
+        let pyproject = PYPROJECT.replace("channels = ", "unknown-key = 1\nchannels = ");
+        let err = Manifest::from_pyproject_str(Path::new(""), pyproject.as_str()).unwrap_err();
+        assert!(label_covers(&err, &pyproject, "unknown-key"));
+
+        // An invalid requirement in the `[project]` table
+        let pyproject = PYPROJECT.replace("\"rich\"", "\"rich>>13\"");
+        let err = Manifest::from_pyproject_str(Path::new(""), pyproject.as_str()).unwrap_err();
+        assert!(label_covers(&err, &pyproject, "rich>>13"));
+
+        // A missing name
+        let pyproject = PYPROJECT.replace("name = \"my-package\"", "");
+        let err = Manifest::from_pyproject_str(Path::new(""), pyproject).unwrap_err();
+        assert!(err.to_string().contains("does not have a name"));
+    }
+
+    #[test]
+    fn test_url_requirements_are_skipped() {
+        let pyproject = PYPROJECT.replace(
+            "\"rich\"",
+            "\"rich @ https://example.com/rich-13.0.0.tar.gz\"",
+        );
+        let manifest = Manifest::from_pyproject_str(Path::new(""), pyproject).unwrap();
+        let pypi_dependencies = manifest
+            .default_feature()
+            .targets
+            .default()
+            .pypi_dependencies
+            .clone()
+            .unwrap();
+        assert!(
+            !pypi_dependencies.contains_key(&rip::types::PackageName::from_str("rich").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_edit_requirements() {
+        let mut manifest = Manifest::from_pyproject_str(Path::new(""), PYPROJECT).unwrap();
+        let requests = rip::types::PackageName::from_str("Requests").unwrap();
+        let requirement = PyPiRequirement::from_str(">=2.32").unwrap();
+
+        // Replaces the existing requirement on the same package
+        let document = &mut manifest.document;
+        let requirements = dependencies_array_mut(document).unwrap();
+        insert_requirement(requirements, &requests, &requirement);
+        assert_eq!(requirements.len(), 3);
+        assert!(requirements
+            .get(0)
+            .and_then(Value::as_str)
+            .unwrap()
+            .ends_with(">=2.32"));
+
+        assert!(remove_requirement(requirements, &requests));
+        assert!(!remove_requirement(requirements, &requests));
+
+        let test = requirements_array_mut(document, &FeatureName::from("test")).unwrap();
+        assert_eq!(test.len(), 1);
+
+        // Keeps the environment marker of the replaced requirement, both in the document and in
+        // the parsed manifest.
+        let pywin32 = rip::types::PackageName::from_str("pywin32").unwrap();
+        manifest
+            .add_pypi_dependency(&pywin32, &PyPiRequirement::from_str(">=306").unwrap(), None)
+            .unwrap();
+        let replaced = manifest.document["project"]["dependencies"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|requirement| requirement.starts_with("pywin32"))
+            .unwrap();
+        assert_eq!(replaced, "pywin32 >=306 ; sys_platform == 'win32'");
+        assert_eq!(
+            manifest
+                .default_feature()
+                .targets
+                .default()
+                .pypi_dependencies
+                .as_ref()
+                .unwrap()[&pywin32]
+                .marker
+                .as_ref()
+                .map(ToString::to_string),
+            Some(String::from("sys_platform == 'win32'"))
+        );
+    }
+}
@@ -1,4 +1,6 @@
+use crate::utils::schema;
 use pep440_rs::VersionSpecifiers;
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde::{
     de,
     de::{Error, MapAccess, Visitor},
@@ -12,6 +14,10 @@ use toml_edit::Item;
 pub struct PyPiRequirement {
     pub(crate) version: Option<pep440_rs::VersionSpecifiers>,
     pub(crate) extras: Option<Vec<String>>,
+
+    /// The environment marker that decides whether the requirement applies, e.g.
+    /// `sys_platform == 'win32'`.
+    pub(crate) marker: Option<pep508_rs::MarkerTree>,
 }
 
 /// The type of parse error that occurred when parsing match spec.
@@ -37,8 +43,8 @@ impl fmt::Display for PyPiRequirement {
 impl From<PyPiRequirement> for Item {
     /// PyPiRequirement to a toml_edit item, to put in the manifest file.
     fn from(val: PyPiRequirement) -> Item {
-        if val.extras.is_some() {
-            // If extras is defined use an inline table
+        if val.extras.is_some() || val.marker.is_some() {
+            // If extras or a marker are defined use an inline table
             let mut table = toml_edit::Table::new().into_inline_table();
 
             // First add the version
@@ -55,10 +61,15 @@ impl From<PyPiRequirement> for Item {
                 );
             }
             // Add extras as an array.
-            table.insert(
-                "extras",
-                toml_edit::Value::Array(toml_edit::Array::from_iter(val.extras.unwrap())),
-            );
+            if let Some(extras) = val.extras {
+                table.insert(
+                    "extras",
+                    toml_edit::Value::Array(toml_edit::Array::from_iter(extras)),
+                );
+            }
+            if let Some(marker) = val.marker {
+                table.insert("marker", marker.to_string().into());
+            }
             Item::Value(toml_edit::Value::InlineTable(table))
         } else {
             // Without extras use the string representation.
@@ -84,6 +95,7 @@ impl FromStr for PyPiRequirement {
             Ok(Self {
                 version: None,
                 extras: None,
+                marker: None,
             })
         } else if s.starts_with(|c: char| c.is_ascii_digit()) {
             Err(ParsePyPiRequirementError::MissingOperator(s.to_string()))
@@ -95,12 +107,14 @@ impl FromStr for PyPiRequirement {
                         .map_err(ParsePyPiRequirementError::Pep440Error)?,
                 ),
                 extras: None,
+                marker: None,
             })
         }
     }
 }
 
-/// Implement from [`pep508_rs::Requirement`] to make the conversion easier.
+/// Implement from [`pep508_rs::Requirement`] to make the conversion easier. The environment marker
+/// is not converted, use [`PyPiRequirement::with_marker`] to keep it.
 impl From<pep508_rs::Requirement> for PyPiRequirement {
     fn from(req: pep508_rs::Requirement) -> Self {
         let version = if let Some(version_or_url) = req.version_or_url {
@@ -114,11 +128,17 @@ impl From<pep508_rs::Requirement> for PyPiRequirement {
         PyPiRequirement {
             version,
             extras: req.extras,
+            marker: None,
         }
     }
 }
 
 impl PyPiRequirement {
+    /// Sets the environment marker of the requirement.
+    pub fn with_marker(self, marker: Option<pep508_rs::MarkerTree>) -> Self {
+        Self { marker, ..self }
+    }
+
     /// Returns the requirements as [`pep508_rs::Requirement`]s.
     pub fn as_pep508(&self, name: &rip::types::PackageName) -> pep508_rs::Requirement {
         pep508_rs::Requirement {
@@ -128,10 +148,38 @@ impl PyPiRequirement {
                 .version
                 .clone()
                 .map(pep508_rs::VersionOrUrl::VersionSpecifier),
-            marker: None,
+            marker: self.marker.clone(),
         }
     }
 }
+impl JsonSchema for PyPiRequirement {
+    fn schema_name() -> String {
+        String::from("PyPiRequirement")
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        /// The table form of a requirement.
+        #[derive(JsonSchema)]
+        #[allow(dead_code)]
+        struct PyPiRequirementTable {
+            /// A PEP 440 version specifier, e.g. `>=1.2`, or `*` for any version.
+            version: Option<String>,
+
+            /// The extras of the package to install.
+            extras: Option<Vec<String>>,
+
+            /// A PEP 508 environment marker, the requirement is only used in the environments
+            /// that match it, e.g. `sys_platform == 'win32'`.
+            marker: Option<String>,
+        }
+
+        schema::any_of([
+            schema::string("A PEP 440 version specifier, e.g. `>=1.2`, or `*` for any version"),
+            gen.subschema_for::<PyPiRequirementTable>(),
+        ])
+    }
+}
+
 impl<'de> Deserialize<'de> for PyPiRequirement {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -159,6 +207,7 @@ impl<'de> Deserialize<'de> for PyPiRequirement {
                 struct RawPyPiRequirement {
                     version: Option<String>,
                     extras: Option<Vec<String>>,
+                    marker: Option<String>,
                 }
                 let raw_requirement =
                     RawPyPiRequirement::deserialize(de::value::MapAccessDeserializer::new(map))?;
@@ -173,9 +222,15 @@ impl<'de> Deserialize<'de> for PyPiRequirement {
                         );
                     }
                 }
+                let marker = raw_requirement
+                    .marker
+                    .map(|marker| pep508_rs::MarkerTree::from_str(&marker))
+                    .transpose()
+                    .map_err(A::Error::custom)?;
                 Ok(PyPiRequirement {
                     version,
                     extras: raw_requirement.extras,
+                    marker,
                 })
             }
         }
@@ -211,7 +266,8 @@ mod tests {
             requirement.first().unwrap().1,
             &PyPiRequirement {
                 version: Some(pep440_rs::VersionSpecifiers::from_str(">=3.12").unwrap()),
-                extras: None
+                extras: None,
+                marker: None
             }
         );
         let requirement: IndexMap<rip::types::PackageName, PyPiRequirement> =
@@ -220,7 +276,8 @@ mod tests {
             requirement.first().unwrap().1,
             &PyPiRequirement {
                 version: Some(pep440_rs::VersionSpecifiers::from_str("==3.12.0").unwrap()),
-                extras: None
+                extras: None,
+                marker: None
             }
         );
 
@@ -230,7 +287,8 @@ mod tests {
             requirement.first().unwrap().1,
             &PyPiRequirement {
                 version: Some(pep440_rs::VersionSpecifiers::from_str("~=2.1.3").unwrap()),
-                extras: None
+                extras: None,
+                marker: None
             }
         );
 
@@ -240,7 +298,8 @@ mod tests {
             requirement.first().unwrap().1,
             &PyPiRequirement {
                 version: None,
-                extras: None
+                extras: None,
+                marker: None
             }
         );
     }
@@ -257,7 +316,8 @@ mod tests {
             requirement.first().unwrap().1,
             &PyPiRequirement {
                 version: Some(pep440_rs::VersionSpecifiers::from_str(">=3.12").unwrap()),
-                extras: Some(vec!("bar".to_string()))
+                extras: Some(vec!("bar".to_string())),
+                marker: None
             }
         );
 
@@ -274,8 +334,36 @@ mod tests {
             requirement.first().unwrap().1,
             &PyPiRequirement {
                 version: Some(pep440_rs::VersionSpecifiers::from_str(">=3.12,<3.13.0").unwrap()),
-                extras: Some(vec!("bar".to_string(), "foo".to_string()))
+                extras: Some(vec!("bar".to_string(), "foo".to_string())),
+                marker: None
             }
         );
     }
+
+    #[test]
+    fn test_marker() {
+        let requirement: IndexMap<rip::types::PackageName, PyPiRequirement> =
+            toml_edit::de::from_str(
+                r#"pywin32 = { version = ">=306", marker = "sys_platform == 'win32'" }"#,
+            )
+            .unwrap();
+        let (name, requirement) = requirement.first().unwrap();
+        assert_eq!(
+            requirement.marker,
+            Some(pep508_rs::MarkerTree::from_str("sys_platform == 'win32'").unwrap())
+        );
+        assert_eq!(
+            requirement.as_pep508(name).to_string(),
+            "pywin32 >=306 ; sys_platform == 'win32'"
+        );
+
+        let item = Item::from(requirement.clone());
+        assert_eq!(item["marker"].as_str(), Some("sys_platform == 'win32'"));
+        assert!(
+            toml_edit::de::from_str::<IndexMap<String, PyPiRequirement>>(
+                "pywin32 = { marker = \"sys_platform ==\" }"
+            )
+            .is_err()
+        );
+    }
 }
@@ -1,6 +1,8 @@
+use crate::utils::schema;
 use miette::Diagnostic;
 use rattler_conda_types::Version;
 use rattler_virtual_packages::{Archspec, Cuda, LibC, Linux, Osx, VirtualPackage};
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde::Deserialize;
 use serde_with::{serde_as, DisplayFromStr};
 use std::str::FromStr;
@@ -10,19 +12,22 @@ const GLIBC_FAMILY: &str = "glibc";
 
 /// Describes the minimal system requirements to be able to run a certain environment.
 #[serde_as]
-#[derive(Debug, Clone, Deserialize, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, Default, PartialEq, Eq, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct SystemRequirements {
     /// Dictates the minimum version of macOS required.
     #[serde_as(as = "Option<DisplayFromStr>")]
+    #[schemars(with = "Option<String>")]
     pub macos: Option<Version>,
 
     /// Dictates the minimum linux version required.
     #[serde_as(as = "Option<DisplayFromStr>")]
+    #[schemars(with = "Option<String>")]
     pub linux: Option<Version>,
 
     /// Dictates the minimum cuda version required.
     #[serde_as(as = "Option<DisplayFromStr>")]
+    #[schemars(with = "Option<String>")]
     pub cuda: Option<Version>,
 
     /// Dictates information about the libc version (and optional family).
@@ -172,6 +177,19 @@ impl<'de> Deserialize<'de> for LibCSystemRequirement {
     }
 }
 
+impl JsonSchema for LibCSystemRequirement {
+    fn schema_name() -> String {
+        String::from("LibCSystemRequirement")
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        schema::any_of([
+            schema::string("The minimum version of glibc"),
+            gen.subschema_for::<LibCFamilyAndVersion>(),
+        ])
+    }
+}
+
 impl LibCSystemRequirement {
     /// Returns the family and version of this libc requirement.
     pub fn family_and_version(&self) -> (&str, &Version) {
@@ -185,7 +203,7 @@ impl LibCSystemRequirement {
 }
 
 #[serde_as]
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct LibCFamilyAndVersion {
     /// The libc family, e.g. glibc
@@ -193,6 +211,7 @@ pub struct LibCFamilyAndVersion {
 
     /// The minimum version of the libc family
     #[serde_as(as = "DisplayFromStr")]
+    #[schemars(with = "String")]
     pub version: Version,
 }
 
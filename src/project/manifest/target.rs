@@ -1,5 +1,5 @@
 use crate::project::manifest::activation::Activation;
-use crate::utils::spanned::PixiSpanned;
+use crate::utils::{schema, spanned::PixiSpanned};
 use crate::{
     project::{manifest::error::SpecIsMissing, manifest::PyPiRequirement, SpecType},
     task::Task,
@@ -8,6 +8,7 @@ use indexmap::map::Entry;
 use indexmap::IndexMap;
 use itertools::Either;
 use rattler_conda_types::{NamelessMatchSpec, PackageName, Platform};
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
 use serde::{Deserialize, Deserializer};
 use serde_with::{serde_as, DisplayFromStr, PickFirst};
 use std::borrow::Cow;
@@ -24,6 +25,14 @@ pub struct Target {
     /// Specific python dependencies
     pub pypi_dependencies: Option<IndexMap<rip::types::PackageName, PyPiRequirement>>,
 
+    /// Constraints on the versions of conda packages. A constraint does not add the package to the
+    /// environment, it only restricts the versions that can be selected if the package is required.
+    pub constraints: IndexMap<PackageName, NamelessMatchSpec>,
+
+    /// Overrides of conda packages. An override replaces every requirement on the package by the
+    /// dependencies of other packages.
+    pub overrides: IndexMap<PackageName, NamelessMatchSpec>,
+
     /// Additional information to activate an environment.
     pub activation: Option<Activation>,
 
@@ -166,40 +175,71 @@ impl<'de> Deserialize<'de> for TargetSelector {
     }
 }
 
+/// The structure of a target (e.g. `[target.linux-64]`) as it is written in the toml file.
+#[serde_as]
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+struct TomlTarget {
+    /// The conda packages required on the platform.
+    #[serde(default)]
+    #[serde_as(as = "IndexMap<_, PickFirst<(DisplayFromStr, _)>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    dependencies: IndexMap<PackageName, NamelessMatchSpec>,
+
+    /// The conda packages required on the host when building the project for the platform.
+    #[serde(default)]
+    #[serde_as(as = "Option<IndexMap<_, PickFirst<(DisplayFromStr, _)>>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    host_dependencies: Option<IndexMap<PackageName, NamelessMatchSpec>>,
+
+    /// The conda packages required on the build machine when building the project for the
+    /// platform.
+    #[serde(default)]
+    #[serde_as(as = "Option<IndexMap<_, PickFirst<(DisplayFromStr, _)>>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    build_dependencies: Option<IndexMap<PackageName, NamelessMatchSpec>>,
+
+    /// The PyPI packages required on the platform.
+    #[serde(default)]
+    pypi_dependencies: Option<IndexMap<rip::types::PackageName, PyPiRequirement>>,
+
+    /// Constraints on the versions of conda packages on the platform.
+    #[serde(default)]
+    #[serde_as(as = "IndexMap<_, PickFirst<(DisplayFromStr, _)>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    constraints: IndexMap<PackageName, NamelessMatchSpec>,
+
+    /// Overrides of the requirements on conda packages on the platform.
+    #[serde(default)]
+    #[serde_as(as = "IndexMap<_, PickFirst<(DisplayFromStr, _)>>")]
+    #[schemars(schema_with = "schema::dependencies")]
+    overrides: IndexMap<PackageName, NamelessMatchSpec>,
+
+    /// Additional information to activate an environment.
+    #[serde(default)]
+    activation: Option<Activation>,
+
+    /// Target specific tasks to run in the environment
+    #[serde(default)]
+    tasks: HashMap<String, Task>,
+}
+
+impl JsonSchema for Target {
+    fn schema_name() -> String {
+        String::from("Target")
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        TomlTarget::json_schema(gen)
+    }
+}
+
 impl<'de> Deserialize<'de> for Target {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        #[serde_as]
-        #[derive(Debug, Clone, Default, Deserialize)]
-        #[serde(rename_all = "kebab-case")]
-        #[serde(deny_unknown_fields)]
-        pub struct TomlTarget {
-            #[serde(default)]
-            #[serde_as(as = "IndexMap<_, PickFirst<(DisplayFromStr, _)>>")]
-            dependencies: IndexMap<PackageName, NamelessMatchSpec>,
-
-            #[serde(default)]
-            #[serde_as(as = "Option<IndexMap<_, PickFirst<(DisplayFromStr, _)>>>")]
-            host_dependencies: Option<IndexMap<PackageName, NamelessMatchSpec>>,
-
-            #[serde(default)]
-            #[serde_as(as = "Option<IndexMap<_, PickFirst<(DisplayFromStr, _)>>>")]
-            build_dependencies: Option<IndexMap<PackageName, NamelessMatchSpec>>,
-
-            #[serde(default)]
-            pypi_dependencies: Option<IndexMap<rip::types::PackageName, PyPiRequirement>>,
-
-            /// Additional information to activate an environment.
-            #[serde(default)]
-            activation: Option<Activation>,
-
-            /// Target specific tasks to run in the environment
-            #[serde(default)]
-            tasks: HashMap<String, Task>,
-        }
-
         let target = TomlTarget::deserialize(deserializer)?;
 
         let mut dependencies = HashMap::from_iter([(SpecType::Run, target.dependencies)]);
@@ -213,6 +253,8 @@ impl<'de> Deserialize<'de> for Target {
         Ok(Self {
             dependencies,
             pypi_dependencies: target.pypi_dependencies,
+            constraints: target.constraints,
+            overrides: target.overrides,
             activation: target.activation,
             tasks: target.tasks,
         })
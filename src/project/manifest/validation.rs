@@ -1,4 +1,6 @@
-use crate::project::manifest::{Environment, FeatureName, SystemRequirements};
+use crate::project::manifest::{
+    parse_license_expression, Environment, FeatureName, SystemRequirements,
+};
 use crate::{
     consts,
     project::manifest::{Feature, ProjectManifest, TargetSelector},
@@ -14,6 +16,13 @@ use std::{
 impl ProjectManifest {
     /// Validate the project manifest.
     pub fn validate(&self, source: NamedSource, root_folder: &Path) -> miette::Result<()> {
+        if self.project.name.is_empty() {
+            return Err(miette::miette!(
+                help = "add a `name` to the [project] table",
+                "the project does not have a name"
+            ));
+        }
+
         // Check if the targets are defined for existing platforms
         for feature in self.features.values() {
             let platforms = feature
@@ -38,14 +47,17 @@ impl ProjectManifest {
 
         // parse the SPDX license expression to make sure that it is a valid expression.
         if let Some(spdx_expr) = &self.project.license {
-            spdx::Expression::parse(spdx_expr)
-                .into_diagnostic()
-                .with_context(|| {
-                    format!(
-                        "failed to parse the SPDX license expression '{}'",
-                        spdx_expr
-                    )
-                })?;
+            parse_license_expression(spdx_expr)?;
+        }
+
+        // The same goes for the expressions of the license policy.
+        for spdx_expr in self
+            .license_policy
+            .allow
+            .iter()
+            .chain(self.license_policy.deny.iter())
+        {
+            parse_license_expression(spdx_expr).context("invalid license policy")?;
         }
 
         let check_file_existence = |x: &Option<PathBuf>| {
@@ -64,6 +76,20 @@ impl ProjectManifest {
         check_file_existence(&self.project.license_file)?;
         check_file_existence(&self.project.readme)?;
 
+        // Make sure the workspace members are valid glob patterns
+        if let Some(workspace) = &self.workspace {
+            for pattern in workspace.members.iter() {
+                glob::Pattern::new(pattern)
+                    .into_diagnostic()
+                    .with_context(|| {
+                        format!(
+                            "the workspace member '{}' is not a valid glob pattern",
+                            pattern
+                        )
+                    })?;
+            }
+        }
+
         // Validate the environments defined in the project
         for (_name, env) in self.environments.iter() {
             if let Err(report) = self.validate_environment(env) {
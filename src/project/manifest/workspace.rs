@@ -0,0 +1,118 @@
+use super::{find_manifest, pyproject, ManifestKind};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use toml_edit::{Document, Item};
+
+/// Describes the `[workspace]` section of the project manifest. A workspace groups multiple pixi
+/// projects in one repository. The members of a workspace share the lock-file and environments of
+/// the project that defines the workspace, the workspace root.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Workspace {
+    /// Glob patterns, relative to the workspace root, that match the directories of the members.
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+impl Workspace {
+    /// Reads only the `[workspace]` table of the manifest at `path`, without parsing the rest of
+    /// the manifest. Returns `None` if the manifest can not be read or does not define a
+    /// workspace.
+    pub fn from_manifest_path(path: &Path) -> Option<Self> {
+        let document = std::fs::read_to_string(path)
+            .ok()?
+            .parse::<Document>()
+            .ok()?;
+        let table = match ManifestKind::from_path(path)? {
+            ManifestKind::Pixi => document.as_table(),
+            ManifestKind::Pyproject => pyproject::pixi_table(&document)?,
+        };
+        let members = match table.get("workspace")?.get("members") {
+            Some(members) => members
+                .as_array()?
+                .iter()
+                .map(|member| member.as_str().map(ToString::to_string))
+                .collect::<Option<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+        Some(Self { members })
+    }
+
+    /// Returns the directories of the members of this workspace. Only directories that contain a
+    /// project manifest (a `pixi.toml` or a `pyproject.toml` with a `[tool.pixi]` table) are
+    /// returned.
+    pub fn member_dirs(&self, root: &Path) -> Result<Vec<PathBuf>, glob::PatternError> {
+        let mut dirs = Vec::new();
+        for pattern in self.members.iter() {
+            let pattern = root.join(pattern);
+            for dir in glob::glob(&pattern.to_string_lossy())?.flatten() {
+                if dir != root && find_manifest(&dir).is_some() {
+                    dirs.push(dir);
+                }
+            }
+        }
+        dirs.sort();
+        dirs.dedup();
+        Ok(dirs)
+    }
+
+    /// Returns true if the directory `dir` is matched by one of the member patterns of the
+    /// workspace located at `root`.
+    pub fn is_member(&self, root: &Path, dir: &Path) -> bool {
+        let Ok(relative_dir) = dir.strip_prefix(root) else {
+            return false;
+        };
+        if relative_dir.as_os_str().is_empty() {
+            return false;
+        }
+
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..glob::MatchOptions::new()
+        };
+        self.members.iter().any(|pattern| {
+            glob::Pattern::new(pattern)
+                .map(|pattern| pattern.matches_path_with(relative_dir, options))
+                .unwrap_or(false)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_member() {
+        let workspace = Workspace {
+            members: vec![String::from("pkgs/*"), String::from("tools/cli")],
+        };
+        let root = Path::new("/repo");
+
+        assert!(workspace.is_member(root, Path::new("/repo/pkgs/foo")));
+        assert!(workspace.is_member(root, Path::new("/repo/tools/cli")));
+        assert!(!workspace.is_member(root, Path::new("/repo/pkgs/foo/bar")));
+        assert!(!workspace.is_member(root, Path::new("/repo/tools")));
+        assert!(!workspace.is_member(root, Path::new("/repo")));
+        assert!(!workspace.is_member(root, Path::new("/other/pkgs/foo")));
+    }
+
+    #[test]
+    fn test_from_manifest_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pixi.toml");
+        std::fs::write(&path, "[project]\nname = \"foo\"\n").unwrap();
+        assert!(Workspace::from_manifest_path(&path).is_none());
+
+        std::fs::write(
+            &path,
+            "[project]\nname = \"foo\"\n\n[workspace]\nmembers = [\"pkgs/*\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            Workspace::from_manifest_path(&path).unwrap().members,
+            vec![String::from("pkgs/*")]
+        );
+    }
+}
@@ -1,10 +1,23 @@
 use crate::Project;
+use indexmap::IndexMap;
 use itertools::Itertools;
-use std::collections::HashMap;
+use miette::{Diagnostic, NamedSource, SourceSpan};
+use rattler_conda_types::Platform;
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use thiserror::Error;
 
 // Setting a base prefix for the pixi package
 const ENV_PREFIX: &str = "PIXI_PACKAGE_";
 
+// The variable that contains the root directory of the project
+const PROJECT_ROOT_VAR: &str = "PIXI_PROJECT_ROOT";
+
 impl Project {
     /// Returns environment variables and their values that should be injected when running a command.
     pub fn get_metadata_env(&self) -> HashMap<String, String> {
@@ -13,6 +26,10 @@ impl Project {
                 format!("{ENV_PREFIX}ROOT"),
                 self.root().to_string_lossy().into_owned(),
             ),
+            (
+                PROJECT_ROOT_VAR.to_string(),
+                self.root().to_string_lossy().into_owned(),
+            ),
             (format!("{ENV_PREFIX}NAME"), self.name().to_string()),
             (
                 format!("{ENV_PREFIX}MANIFEST"),
@@ -33,4 +50,316 @@ impl Project {
             ("PIXI_PROMPT".to_string(), format!("({}) ", self.name())),
         ])
     }
+
+    /// Returns the variables that can be used in the values of the manifest, see
+    /// [`ManifestVariables`].
+    pub fn manifest_variables(&self) -> ManifestVariables {
+        ManifestVariables::new(self.root())
+            .with_source(self.manifest.kind.file_name(), &self.manifest.contents)
+    }
+}
+
+/// The variables that can be referenced in the `cwd` and commands of tasks, in the paths of
+/// activation scripts and in the paths of local channels.
+///
+/// The supported references are `${PIXI_PROJECT_ROOT}`, `${PIXI_ENVIRONMENT_NAME}`,
+/// `${PIXI_PLATFORM}` and `${env:NAME}` which refers to the environment variable `NAME` of the pixi
+/// process. The variables can also be referenced without braces, like `$PIXI_PLATFORM`. Any other
+/// reference is left untouched so it can be expanded by the shell.
+#[derive(Debug, Clone)]
+pub struct ManifestVariables {
+    project_root: PathBuf,
+    environment_name: Option<String>,
+    platform: Option<Platform>,
+
+    /// The name and contents of the manifest, used to point at the value that refers to an
+    /// undefined variable.
+    source: Option<(String, Arc<str>)>,
+}
+
+/// Error that is returned when a manifest value refers to a variable that is not defined.
+#[derive(Debug, Error, Diagnostic)]
+#[error("the variable `{name}` is not defined")]
+#[diagnostic(help(
+    "the variables PIXI_PROJECT_ROOT, PIXI_ENVIRONMENT_NAME, PIXI_PLATFORM and env:<NAME> are supported"
+))]
+pub struct UndefinedVariableError {
+    pub name: String,
+    #[source_code]
+    pub source: NamedSource,
+    #[label("undefined variable")]
+    pub span: SourceSpan,
+}
+
+impl ManifestVariables {
+    /// Constructs the variables of the project at `project_root`. The environment name and
+    /// platform are not defined until they are set with [`Self::with_environment`] and
+    /// [`Self::with_platform`].
+    pub fn new(project_root: impl AsRef<Path>) -> Self {
+        Self {
+            project_root: project_root.as_ref().to_path_buf(),
+            environment_name: None,
+            platform: None,
+            source: None,
+        }
+    }
+
+    /// Defines `PIXI_ENVIRONMENT_NAME`.
+    pub fn with_environment(self, name: impl Into<String>) -> Self {
+        Self {
+            environment_name: Some(name.into()),
+            ..self
+        }
+    }
+
+    /// Defines `PIXI_PLATFORM`.
+    pub fn with_platform(self, platform: Platform) -> Self {
+        Self {
+            platform: Some(platform),
+            ..self
+        }
+    }
+
+    /// Sets the manifest the expanded values are read from. An [`UndefinedVariableError`] points
+    /// at the value in the manifest instead of only showing the value.
+    pub fn with_source(self, name: impl Into<String>, contents: &str) -> Self {
+        Self {
+            source: Some((name.into(), Arc::from(contents))),
+            ..self
+        }
+    }
+
+    /// Returns the value of the variable `name`, or `None` if the variable is not defined. Returns
+    /// `Some(None)` if the name does not refer to a manifest variable.
+    fn lookup(&self, name: &str) -> Option<Option<String>> {
+        if let Some(var) = name.strip_prefix("env:") {
+            return Some(std::env::var(var).ok());
+        }
+        match name {
+            PROJECT_ROOT_VAR => Some(Some(self.project_root.to_string_lossy().into_owned())),
+            "PIXI_ENVIRONMENT_NAME" => Some(self.environment_name.clone()),
+            "PIXI_PLATFORM" => Some(self.platform.map(|platform| platform.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Expands the references to manifest variables in `value`.
+    pub fn expand(&self, value: &str) -> Result<String, UndefinedVariableError> {
+        expand_references(value, |name, span| match self.lookup(name) {
+            Some(Some(expanded)) => Ok(Some(expanded)),
+            Some(None) => Err(self.undefined_variable(name, value, span)),
+            None => Ok(None),
+        })
+    }
+
+    /// Constructs the error for the reference to the undefined variable `name` at `span` in
+    /// `value`. If the value is found in the manifest the error points into the manifest,
+    /// otherwise it points into the value itself.
+    fn undefined_variable(
+        &self,
+        name: &str,
+        value: &str,
+        span: Range<usize>,
+    ) -> UndefinedVariableError {
+        let (source, offset) = match &self.source {
+            Some((source_name, contents)) => match contents.find(value) {
+                Some(offset) => (NamedSource::new(source_name, contents.to_string()), offset),
+                None => (NamedSource::new(source_name, value.to_string()), 0),
+            },
+            None => (NamedSource::new("value", value.to_string()), 0),
+        };
+        UndefinedVariableError {
+            name: name.to_string(),
+            source,
+            span: (offset + span.start..offset + span.end).into(),
+        }
+    }
+}
+
+/// Expands the references to variables in `value`. Both `$NAME` and `${NAME}` are supported. The
+/// name of a reference without braces consists of letters, digits and underscores, the name of a
+/// reference with braces can contain any other character except `}`, like in `${env:NAME}`.
+///
+/// `lookup` is called with the name and the byte range of every reference in `value`. It returns
+/// the value of the variable, `None` to leave the reference untouched or an error.
+fn expand_references<E>(
+    value: &str,
+    mut lookup: impl FnMut(&str, Range<usize>) -> Result<Option<String>, E>,
+) -> Result<String, E> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+    let mut result = String::with_capacity(value.len());
+    let mut offset = 0;
+    while let Some(idx) = value[offset..].find('$') {
+        let start = offset + idx;
+        result.push_str(&value[offset..start]);
+        let after = &value[start + 1..];
+
+        // Determine the name of the variable and the end of the reference.
+        let (name, end) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(len) => (&braced[..len], start + len + 3),
+                None => ("", start + 1),
+            },
+            None => {
+                let len = after.find(|c| !is_name_char(c)).unwrap_or(after.len());
+                (&after[..len], start + len + 1)
+            }
+        };
+
+        let expanded = if name.is_empty() {
+            None
+        } else {
+            lookup(name, start..end)?
+        };
+        match expanded {
+            Some(expanded) => {
+                result.push_str(&expanded);
+                offset = end;
+            }
+            None => {
+                result.push('$');
+                offset = start + 1;
+            }
+        }
+    }
+    result.push_str(&value[offset..]);
+    Ok(result)
+}
+
+/// Expands references to environment variables in `value`, see [`expand_references`] for the
+/// syntax. The value of a variable is looked up with `lookup`. References to variables that are
+/// not defined are left untouched.
+pub fn expand_env_vars(value: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    match expand_references(value, |name, _| Ok::<_, Infallible>(lookup(name))) {
+        Ok(expanded) => expanded,
+        Err(never) => match never {},
+    }
+}
+
+/// Expands the values of the `activation_env` variables of the manifest in order and returns the
+/// expanded variables. A reference in a value is looked up in the variables that were expanded
+/// before it, then in `environment`, then in the `metadata_env` of the project and finally in the
+/// environment of the pixi process.
+pub fn expand_activation_env(
+    activation_env: IndexMap<String, String>,
+    environment: &HashMap<String, String>,
+    metadata_env: &HashMap<String, String>,
+) -> IndexMap<String, String> {
+    let mut expanded = IndexMap::with_capacity(activation_env.len());
+    for (name, value) in activation_env {
+        let value = expand_env_vars(&value, |var| {
+            expanded
+                .get(var)
+                .or_else(|| environment.get(var))
+                .or_else(|| metadata_env.get(var))
+                .cloned()
+                .or_else(|| std::env::var(var).ok())
+        });
+        expanded.insert(name, value);
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_activation_env, expand_env_vars, ManifestVariables};
+    use indexmap::IndexMap;
+    use rattler_conda_types::Platform;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_expand_env_vars() {
+        let lookup = |name: &str| match name {
+            "PIXI_PROJECT_ROOT" => Some(String::from("/project")),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+
+        assert_eq!(
+            expand_env_vars("$PIXI_PROJECT_ROOT/bin", lookup),
+            "/project/bin"
+        );
+        assert_eq!(
+            expand_env_vars("${PIXI_PROJECT_ROOT}bin", lookup),
+            "/projectbin"
+        );
+        assert_eq!(expand_env_vars("a${EMPTY}b", lookup), "ab");
+        assert_eq!(expand_env_vars("$UNKNOWN/bin", lookup), "$UNKNOWN/bin");
+        assert_eq!(expand_env_vars("${UNKNOWN}", lookup), "${UNKNOWN}");
+        assert_eq!(expand_env_vars("costs $5", lookup), "costs $5");
+        assert_eq!(expand_env_vars("trailing $", lookup), "trailing $");
+        assert_eq!(expand_env_vars("${unterminated", lookup), "${unterminated");
+    }
+
+    #[test]
+    fn test_expand_activation_env() {
+        let activation_env = IndexMap::from([
+            (
+                String::from("DATA"),
+                String::from("$PIXI_PROJECT_ROOT/data"),
+            ),
+            (String::from("BIN"), String::from("$CONDA_PREFIX/bin")),
+            (String::from("INPUT"), String::from("${DATA}/input")),
+        ]);
+        let environment = HashMap::from([(String::from("CONDA_PREFIX"), String::from("/env"))]);
+        let metadata_env =
+            HashMap::from([(String::from("PIXI_PROJECT_ROOT"), String::from("/project"))]);
+
+        let expanded = expand_activation_env(activation_env, &environment, &metadata_env);
+        assert_eq!(expanded["DATA"], "/project/data");
+        assert_eq!(expanded["BIN"], "/env/bin");
+        assert_eq!(expanded["INPUT"], "/project/data/input");
+    }
+
+    #[test]
+    fn test_manifest_variables() {
+        let variables = ManifestVariables::new("/project");
+        assert_eq!(
+            variables.expand("${PIXI_PROJECT_ROOT}/channel").unwrap(),
+            "/project/channel"
+        );
+        assert_eq!(
+            variables.expand("echo ${FOO} $HOME").unwrap(),
+            "echo ${FOO} $HOME"
+        );
+
+        let err = variables.expand("out/${PIXI_PLATFORM}").unwrap_err();
+        assert_eq!(err.name, "PIXI_PLATFORM");
+        assert_eq!(err.span, (4, 16).into());
+
+        let variables = variables
+            .with_environment("cuda")
+            .with_platform(Platform::Linux64);
+        assert_eq!(
+            variables
+                .expand("build/${PIXI_ENVIRONMENT_NAME}-${PIXI_PLATFORM}")
+                .unwrap(),
+            "build/cuda-linux-64"
+        );
+
+        let err = variables
+            .expand("${env:PIXI_TEST_UNDEFINED_VARIABLE}")
+            .unwrap_err();
+        assert_eq!(err.name, "env:PIXI_TEST_UNDEFINED_VARIABLE");
+        assert_eq!(err.span, (0, 35).into());
+        assert_eq!(
+            variables.expand("$PIXI_PLATFORM/bin").unwrap(),
+            "linux-64/bin"
+        );
+    }
+
+    #[test]
+    fn test_undefined_variable_source() {
+        let manifest = "[tasks]\nbuild = \"make ${PIXI_PLATFORM}\"\n";
+        let variables = ManifestVariables::new("/project").with_source("pixi.toml", manifest);
+
+        // The error points at the reference in the manifest
+        let err = variables.expand("make ${PIXI_PLATFORM}").unwrap_err();
+        assert_eq!(err.span, (22, 16).into());
+
+        // Values that are not part of the manifest are shown on their own
+        let err = variables.expand("out/${PIXI_PLATFORM}").unwrap_err();
+        assert_eq!(err.span, (4, 16).into());
+    }
 }
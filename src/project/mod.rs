@@ -19,8 +19,8 @@ use std::{
     env,
     ffi::OsStr,
     fmt::{Debug, Display, Formatter},
-    fs,
     path::{Path, PathBuf},
+    str::FromStr,
     sync::Arc,
 };
 
@@ -29,7 +29,10 @@ use crate::{
     consts::{self, PROJECT_MANIFEST},
     task::Task,
 };
-use manifest::{EnvironmentName, Manifest, PyPiRequirement, SystemRequirements};
+use manifest::{
+    find_manifest, EnvironmentName, ExcludeNewer, LicensePolicy, Manifest, ManifestKind,
+    PyPiRequirement, SystemRequirements, Workspace,
+};
 use rip::types::NormalizedPackageName;
 use url::Url;
 
@@ -93,6 +96,10 @@ pub struct Project {
     authenticated_client: AuthenticatedClient,
     /// The manifest for the project
     pub(crate) manifest: Manifest,
+    /// The manifests of the members of the workspace, if this project defines a workspace
+    members: Vec<Manifest>,
+    /// The index of the workspace member from which the project was loaded, if any
+    current_member: Option<usize>,
 }
 
 impl Debug for Project {
@@ -116,6 +123,8 @@ impl Project {
             client,
             authenticated_client,
             manifest,
+            members: Vec::new(),
+            current_member: None,
         }
     }
 
@@ -128,10 +137,20 @@ impl Project {
     /// Discovers the project manifest file in the current directory or any of the parent
     /// directories.
     /// This will also set the current working directory to the project root.
+    ///
+    /// If the discovered project is a member of a workspace, the workspace root is loaded instead.
+    /// The member is remembered so its tasks take precedence over the tasks of the workspace root.
     pub fn discover() -> miette::Result<Self> {
-        let project_toml = match find_project_root() {
-            Some(root) => root.join(PROJECT_MANIFEST),
-            None => miette::bail!("could not find {}", PROJECT_MANIFEST),
+        let project_toml = match find_nearest_project_root()
+            .as_deref()
+            .and_then(find_manifest)
+        {
+            Some(manifest_path) => manifest_path,
+            None => miette::bail!(
+                "could not find {} or {} with a [tool.pixi] table",
+                PROJECT_MANIFEST,
+                consts::PYPROJECT_MANIFEST
+            ),
         };
         Self::load(&project_toml)
     }
@@ -139,39 +158,61 @@ impl Project {
     /// Returns the source code of the project as [`NamedSource`].
     /// Used in error reporting.
     pub fn manifest_named_source(&self) -> NamedSource {
-        NamedSource::new(PROJECT_MANIFEST, self.manifest.contents.clone())
+        NamedSource::new(
+            self.manifest.kind.file_name(),
+            self.manifest.contents.clone(),
+        )
     }
 
     /// Loads a project from manifest file.
     fn load(manifest_path: &Path) -> miette::Result<Self> {
         // Determine the parent directory of the manifest file
         let full_path = dunce::canonicalize(manifest_path).into_diagnostic()?;
-        if full_path.file_name().and_then(OsStr::to_str) != Some(PROJECT_MANIFEST) {
-            miette::bail!("the manifest-path must point to a {PROJECT_MANIFEST} file");
+        if ManifestKind::from_path(&full_path).is_none() {
+            miette::bail!(
+                "the manifest-path must point to a {PROJECT_MANIFEST} or {} file",
+                consts::PYPROJECT_MANIFEST
+            );
         }
 
         let root = full_path
             .parent()
             .ok_or_else(|| miette::miette!("can not find parent of {}", manifest_path.display()))?;
 
+        // If the project is a member of a workspace, the workspace root is the actual project.
+        if let Some(workspace_root) = find_workspace_root(root) {
+            let workspace_manifest =
+                find_manifest(&workspace_root).expect("the workspace root contains a manifest");
+            let mut project = Self::load(&workspace_manifest)?;
+            project.current_member = project
+                .members
+                .iter()
+                .position(|member| member.path.parent() == Some(root));
+            return Ok(project);
+        }
+
         // Load the TOML document
-        let manifest = fs::read_to_string(manifest_path)
-            .into_diagnostic()
-            .and_then(|content| Manifest::from_str(root, content))
-            .wrap_err_with(|| {
-                format!(
-                    "failed to parse {} from {}",
-                    consts::PROJECT_MANIFEST,
-                    root.display()
-                )
-            });
+        let manifest = Manifest::from_path(&full_path).wrap_err_with(|| {
+            format!(
+                "failed to parse {} from {}",
+                full_path
+                    .file_name()
+                    .and_then(OsStr::to_str)
+                    .unwrap_or(PROJECT_MANIFEST),
+                root.display()
+            )
+        })?;
+
+        let members = load_workspace_members(root, &manifest)?;
 
         Ok(Self {
             root: root.to_owned(),
             package_db: Default::default(),
             client: Default::default(),
             authenticated_client: Default::default(),
-            manifest: manifest?,
+            manifest,
+            members,
+            current_member: None,
         })
     }
 
@@ -204,6 +245,45 @@ impl Project {
         &self.root
     }
 
+    /// Returns the paths of the dotenv files to load into the environment of tasks and shells, in
+    /// the order they are defined in the manifest.
+    pub fn env_files(&self) -> Vec<PathBuf> {
+        self.manifest
+            .parsed
+            .project
+            .env_file
+            .iter()
+            .map(|path| self.root.join(path))
+            .collect()
+    }
+
+    /// Returns the manifests of the members of the workspace defined by this project.
+    pub fn workspace_members(&self) -> &[Manifest] {
+        &self.members
+    }
+
+    /// Returns the manifest of the workspace member from which this project was loaded, or `None`
+    /// if the project was not loaded from a workspace member.
+    pub fn current_member(&self) -> Option<&Manifest> {
+        self.current_member.map(|idx| &self.members[idx])
+    }
+
+    /// Returns the directory relative to which the task with the given `name` is executed. Tasks
+    /// defined by the current workspace member run relative to that member, all other tasks run
+    /// relative to the project root.
+    pub fn task_root(&self, name: &str, platform: Option<Platform>) -> &Path {
+        self.current_member()
+            .filter(|member| {
+                member
+                    .default_feature()
+                    .targets
+                    .resolve(platform)
+                    .any(|target| target.tasks.contains_key(name))
+            })
+            .and_then(|member| member.path.parent())
+            .unwrap_or(&self.root)
+    }
+
     /// Returns the pixi directory
     pub fn pixi_dir(&self) -> PathBuf {
         self.root.join(consts::PIXI_DIR)
@@ -248,6 +328,18 @@ impl Project {
         })
     }
 
+    /// Returns the environment with the given `name`, or the default environment if no name is
+    /// given. Returns an error if the name is invalid or if the project does not contain the
+    /// environment.
+    pub fn environment_from_name_or_default(
+        &self,
+        name: Option<&str>,
+    ) -> miette::Result<Environment<'_>> {
+        let name = EnvironmentName::from_str(name.unwrap_or(consts::DEFAULT_ENVIRONMENT_NAME))?;
+        self.environment(&name)
+            .ok_or_else(|| miette::miette!("environment `{name}` not found in project"))
+    }
+
     /// Returns the environments in this project.
     pub fn environments(&self) -> Vec<Environment> {
         self.manifest
@@ -261,6 +353,18 @@ impl Project {
             .collect()
     }
 
+    /// Returns the license policy of the project, the licenses that are allowed for the packages
+    /// of its environments.
+    pub fn license_policy(&self) -> &LicensePolicy {
+        &self.manifest.parsed.license_policy
+    }
+
+    /// Overrides the `exclude-newer` cutoff of the manifest, e.g. with the one given on the
+    /// command line. The manifest file itself is not changed.
+    pub fn set_exclude_newer(&mut self, exclude_newer: ExcludeNewer) {
+        self.manifest.parsed.project.exclude_newer = Some(exclude_newer);
+    }
+
     /// Returns the channels used by this project.
     ///
     /// TODO: Remove this function and use the channels from the default environment instead.
@@ -334,6 +438,7 @@ impl Project {
     /// `[pypi-dependencies]` is specified without any requirements this will return true.
     pub fn has_pypi_dependencies(&self) -> bool {
         self.manifest.has_pypi_dependencies()
+            || self.members.iter().any(Manifest::has_pypi_dependencies)
     }
 
     /// Returns the Python index URLs to use for this project.
@@ -372,14 +477,75 @@ impl Project {
 }
 
 /// Iterates over the current directory and all its parent directories and returns the first
-/// directory path that contains the [`consts::PROJECT_MANIFEST`].
+/// directory path that contains a project manifest, see [`find_manifest`]. If that directory is a
+/// member of a workspace, the root directory of the workspace is returned instead.
 pub fn find_project_root() -> Option<PathBuf> {
+    let project_root = find_nearest_project_root()?;
+    Some(find_workspace_root(&project_root).unwrap_or(project_root))
+}
+
+/// Iterates over the current directory and all its parent directories and returns the first
+/// directory path that contains a project manifest, see [`find_manifest`].
+fn find_nearest_project_root() -> Option<PathBuf> {
     let current_dir = env::current_dir().ok()?;
     std::iter::successors(Some(current_dir.as_path()), |prev| prev.parent())
-        .find(|dir| dir.join(consts::PROJECT_MANIFEST).is_file())
+        .find(|dir| find_manifest(dir).is_some())
         .map(Path::to_path_buf)
 }
 
+/// Iterates over the parent directories of `project_root` and returns the first directory that
+/// contains a project manifest which defines a workspace that `project_root` is a member of. Only
+/// the `[workspace]` table of the manifests is read.
+fn find_workspace_root(project_root: &Path) -> Option<PathBuf> {
+    project_root
+        .ancestors()
+        .skip(1)
+        .find(|dir| {
+            find_manifest(dir)
+                .and_then(|manifest_path| Workspace::from_manifest_path(&manifest_path))
+                .map_or(false, |workspace| workspace.is_member(dir, project_root))
+        })
+        .map(Path::to_path_buf)
+}
+
+/// Loads the manifests of the members of the workspace defined by `manifest`, if any.
+fn load_workspace_members(root: &Path, manifest: &Manifest) -> miette::Result<Vec<Manifest>> {
+    let Some(workspace) = &manifest.parsed.workspace else {
+        return Ok(Vec::new());
+    };
+
+    let member_dirs = workspace.member_dirs(root).into_diagnostic()?;
+    member_dirs
+        .into_iter()
+        .map(|dir| {
+            let member = find_manifest(&dir)
+                .ok_or_else(|| miette::miette!("could not find a manifest in {}", dir.display()))
+                .and_then(Manifest::from_path)
+                .wrap_err_with(|| format!("failed to load workspace member {}", dir.display()))?;
+            if member.parsed.workspace.is_some() {
+                miette::bail!(
+                    "the workspace member {} can not define a workspace itself",
+                    dir.display()
+                );
+            }
+            if let Some(name) = member
+                .parsed
+                .environments
+                .keys()
+                .find(|name| **name != EnvironmentName::Default)
+            {
+                miette::bail!(
+                    help = "define the environment in the workspace root instead, features of members are added to the environments of the workspace that use a feature with the same name",
+                    "the workspace member {} can not define the environment `{}`",
+                    dir.display(),
+                    name
+                );
+            }
+            Ok(member)
+        })
+        .collect()
+}
+
 #[derive(Eq, PartialEq, Hash)]
 pub enum DependencyName {
     Conda(PackageName),
@@ -408,6 +574,7 @@ mod tests {
     use insta::{assert_debug_snapshot, assert_display_snapshot};
     use itertools::Itertools;
     use rattler_virtual_packages::{LibC, VirtualPackage};
+    use std::fs;
     use std::str::FromStr;
 
     const PROJECT_BOILERPLATE: &str = r#"
@@ -586,4 +753,129 @@ mod tests {
             .tasks(Some(Platform::Linux64), &FeatureName::Default)
             .unwrap());
     }
+
+    #[test]
+    fn test_workspace_members() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        fs::write(
+            root.join(PROJECT_MANIFEST),
+            format!(
+                r#"{PROJECT_BOILERPLATE}
+            [workspace]
+            members = ["pkgs/*"]
+
+            [dependencies]
+            python = "3.11.*"
+
+            [tasks]
+            test = "test root"
+            lint = "lint root"
+
+            [feature.test.dependencies]
+            pytest = "*"
+
+            [environments]
+            test = ["test"]
+            "#
+            ),
+        )
+        .unwrap();
+        for member in ["a", "b"] {
+            let member_dir = root.join("pkgs").join(member);
+            fs::create_dir_all(&member_dir).unwrap();
+            fs::write(
+                member_dir.join(PROJECT_MANIFEST),
+                format!(
+                    r#"{PROJECT_BOILERPLATE}
+                [dependencies]
+                {member}-lib = "1.0"
+
+                [tasks]
+                test = "test {member}"
+
+                [feature.test.dependencies]
+                {member}-test-lib = "1.0"
+                "#
+                ),
+            )
+            .unwrap();
+        }
+
+        // Loading a member loads the workspace root instead
+        let project =
+            Project::load_or_else_discover(Some(&root.join("pkgs/a").join(PROJECT_MANIFEST)))
+                .unwrap();
+        assert_eq!(project.root(), root);
+        assert_eq!(
+            project.lock_file_path(),
+            root.join(consts::PROJECT_LOCK_FILE)
+        );
+        assert_eq!(project.workspace_members().len(), 2);
+
+        // The dependencies of all members are part of the environment
+        let dependencies = project.dependencies(None, Some(Platform::Linux64));
+        assert_eq!(
+            dependencies
+                .names()
+                .map(PackageName::as_normalized)
+                .sorted()
+                .collect_vec(),
+            vec!["a-lib", "b-lib", "python"]
+        );
+
+        // Tasks are resolved from the member first and fall back to the root
+        let tasks = project.tasks(Some(Platform::Linux64));
+        assert_eq!(tasks.get("test").unwrap().as_plain().unwrap(), "test a");
+        assert_eq!(tasks.get("lint").unwrap().as_plain().unwrap(), "lint root");
+        assert_eq!(project.task_root("test", None), root.join("pkgs/a"));
+        assert_eq!(project.task_root("lint", None), root);
+
+        // Loading the root directly only uses the tasks of the root
+        let project = Project::load_or_else_discover(Some(&root.join(PROJECT_MANIFEST))).unwrap();
+        assert!(project.current_member().is_none());
+        let tasks = project.tasks(Some(Platform::Linux64));
+        assert_eq!(tasks.get("test").unwrap().as_plain().unwrap(), "test root");
+
+        // The features of members are part of the environments that use a feature with the same
+        // name
+        let test_dependencies = project
+            .environment("test")
+            .unwrap()
+            .dependencies(None, Some(Platform::Linux64));
+        assert_eq!(
+            test_dependencies
+                .names()
+                .map(PackageName::as_normalized)
+                .sorted()
+                .collect_vec(),
+            vec![
+                "a-lib",
+                "a-test-lib",
+                "b-lib",
+                "b-test-lib",
+                "pytest",
+                "python"
+            ]
+        );
+
+        // Members can not define their own environments
+        fs::write(
+            root.join("pkgs/b").join(PROJECT_MANIFEST),
+            format!(
+                r#"{PROJECT_BOILERPLATE}
+            [feature.docs.dependencies]
+            sphinx = "*"
+
+            [environments]
+            docs = ["docs"]
+            "#
+            ),
+        )
+        .unwrap();
+        let err = Project::load_or_else_discover(Some(&root.join(PROJECT_MANIFEST))).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("can not define the environment `docs`"));
+    }
 }
@@ -6,6 +6,22 @@ use std::str::FromStr;
 pub fn determine_marker_environment(
     platform: Platform,
     python_record: &PackageRecord,
+) -> miette::Result<MarkerEnvironment> {
+    // Only cpython is supported
+    if python_record.name.as_normalized() != "python" {
+        miette::bail!(
+            "unsupported python variant {}",
+            python_record.name.as_source()
+        )
+    }
+
+    cpython_marker_environment(platform, &python_record.version)
+}
+
+/// Determine the env markers of a cpython interpreter with the given version on the platform.
+pub fn cpython_marker_environment(
+    platform: Platform,
+    python_version: &VersionWithSource,
 ) -> miette::Result<MarkerEnvironment> {
     // Determine system specific information
     let (sys_platform, platform_system, os_name) = if platform.is_linux() {
@@ -18,17 +34,6 @@ pub fn determine_marker_environment(
         miette::bail!("could not determine python environment markers for {platform}")
     };
 
-    // Determine implementation name
-    let (implementation_name, platform_python_implementation) =
-        if python_record.name.as_normalized() == "python" {
-            ("cpython", "CPython")
-        } else {
-            miette::bail!(
-                "unsupported python variant {}",
-                python_record.name.as_source()
-            )
-        };
-
     let platform_machine = match platform {
         Platform::Linux32 => "i386",
         Platform::Linux64 => "x86_64",
@@ -49,21 +54,20 @@ pub fn determine_marker_environment(
     };
 
     Ok(MarkerEnvironment {
-        implementation_name: String::from(implementation_name),
-        implementation_version: version_to_string_version(&python_record.version),
+        implementation_name: String::from("cpython"),
+        implementation_version: version_to_string_version(python_version),
         os_name: String::from(os_name),
-        platform_python_implementation: String::from(platform_python_implementation),
+        platform_python_implementation: String::from("CPython"),
         platform_system: String::from(platform_system),
-        python_full_version: version_to_string_version(&python_record.version),
-        python_version: python_record
-            .version
+        python_full_version: version_to_string_version(python_version),
+        python_version: python_version
             .version()
             .as_major_minor()
             .and_then(|(major, minor)| StringVersion::from_str(&format!("{major}.{minor}")).ok())
             .ok_or_else(|| {
                 miette::miette!(
                     "could not convert python version {}, to a major minor version",
-                    &python_record.version
+                    python_version
                 )
             })?,
         sys_platform: String::from(sys_platform),
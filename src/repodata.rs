@@ -17,10 +17,20 @@ impl Project {
 }
 
 impl Environment<'_> {
+    /// Fetches the repodata of the channels of the environment, including the channels that
+    /// dependencies are pinned to.
     pub async fn fetch_sparse_repodata(&self) -> miette::Result<Vec<SparseRepoData>> {
-        let channels = self.channels();
+        let mut channels = self.channels().into_iter().cloned().collect_vec();
         let platforms = self.platforms();
-        fetch_sparse_repodata(channels, platforms, self.project().authenticated_client()).await
+        for channel in platforms
+            .iter()
+            .flat_map(|platform| self.pinned_channels(Some(*platform)).into_values())
+        {
+            if !channels.iter().any(|c| c.base_url() == channel.base_url()) {
+                channels.push(channel.as_ref().clone());
+            }
+        }
+        fetch_sparse_repodata(&channels, platforms, self.project().authenticated_client()).await
     }
 }
 
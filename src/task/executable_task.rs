@@ -1,4 +1,8 @@
 use crate::{
+    project::{
+        manifest::EnvironmentName,
+        metadata::{ManifestVariables, UndefinedVariableError},
+    },
     task::{quote_arguments, CmdArgs, Custom, Task},
     Project,
 };
@@ -50,6 +54,9 @@ pub enum TaskExecutionError {
     InvalidWorkingDirectory(#[from] InvalidWorkingDirectory),
     #[error(transparent)]
     FailedToParseShellScript(#[from] FailedToParseShellScript),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UndefinedVariable(#[from] UndefinedVariableError),
 }
 
 /// A task that contains enough information to be able to execute it. The lifetime [`'p`] refers to
@@ -122,41 +129,63 @@ impl<'p> ExecutableTask<'p> {
         }
     }
 
+    /// Returns the variables that can be used in the command and working directory of the task.
+    /// Tasks are run in the default environment.
+    fn variables(&self) -> ManifestVariables {
+        self.project
+            .manifest_variables()
+            .with_environment(EnvironmentName::Default.as_str())
+            .with_platform(self.platform.unwrap_or_else(Platform::current))
+    }
+
     /// Returns a [`SequentialList`] which can be executed by deno task shell. Returns `None` if the
     /// command is not executable like in the case of an alias.
-    pub fn as_deno_script(&self) -> Result<Option<SequentialList>, FailedToParseShellScript> {
+    pub fn as_deno_script(&self) -> Result<Option<SequentialList>, TaskExecutionError> {
         // Convert the task into an executable string
         let Some(task) = self.task.as_single_command() else {
             return Ok(None);
         };
+        let task = self.variables().expand(&task)?;
 
         // Append the command line arguments
         let cli_args = quote_arguments(self.additional_args.iter().map(|arg| arg.as_str()));
         let full_script = format!("{task} {cli_args}");
 
         // Parse the shell command
-        deno_task_shell::parser::parse(full_script.trim())
-            .map_err(|e| FailedToParseShellScript {
+        let script = deno_task_shell::parser::parse(full_script.trim()).map_err(|e| {
+            FailedToParseShellScript {
                 script: full_script,
                 error: e.to_string(),
-            })
-            .map(Some)
+            }
+        })?;
+        Ok(Some(script))
     }
 
     /// Returns the working directory for this task.
-    pub fn working_directory(&self) -> Result<PathBuf, InvalidWorkingDirectory> {
-        Ok(match self.task.working_directory() {
-            Some(cwd) if cwd.is_absolute() => cwd.to_path_buf(),
+    pub fn working_directory(&self) -> Result<PathBuf, TaskExecutionError> {
+        let root = match &self.name {
+            Some(name) => self.project.task_root(name, self.platform),
+            None => self.project.root(),
+        };
+        let cwd = self
+            .task
+            .working_directory()
+            .map(|cwd| self.variables().expand(&cwd.to_string_lossy()))
+            .transpose()?
+            .map(PathBuf::from);
+        Ok(match cwd {
+            Some(cwd) if cwd.is_absolute() => cwd,
             Some(cwd) => {
-                let abs_path = self.project.root().join(cwd);
+                let abs_path = root.join(&cwd);
                 if !abs_path.is_dir() {
                     return Err(InvalidWorkingDirectory {
                         path: cwd.to_string_lossy().to_string(),
-                    });
+                    }
+                    .into());
                 }
                 abs_path
             }
-            None => self.project.root().to_path_buf(),
+            None => root.to_path_buf(),
         })
     }
 
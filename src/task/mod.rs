@@ -1,4 +1,6 @@
+use crate::utils::schema;
 use itertools::Itertools;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_with::{formats::PreferMany, serde_as, OneOrMany};
 use std::borrow::Cow;
@@ -15,7 +17,7 @@ pub use executable_task::{
 pub use traverse::TraversalError;
 
 /// Represents different types of scripts
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum Task {
     Plain(String),
@@ -106,7 +108,7 @@ impl Task {
 
 /// A command script executes a single command from the environment
 #[serde_as]
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct Execute {
     /// A list of arguments, the first argument denotes the command to run. When deserializing both
@@ -116,6 +118,7 @@ pub struct Execute {
     /// A list of commands that should be run before this one
     #[serde(default)]
     #[serde_as(deserialize_as = "OneOrMany<_, PreferMany>")]
+    #[schemars(schema_with = "schema::one_or_many_strings")]
     pub depends_on: Vec<String>,
 
     /// The working directory for the command relative to the root of the project.
@@ -144,7 +147,7 @@ impl From<Custom> for Task {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum CmdArgs {
     Single(String),
@@ -181,12 +184,13 @@ impl CmdArgs {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 #[serde_as]
 pub struct Alias {
     /// A list of commands that should be run before this one
     #[serde_as(deserialize_as = "OneOrMany<_, PreferMany>")]
+    #[schemars(schema_with = "schema::one_or_many_strings")]
     pub depends_on: Vec<String>,
 }
 
@@ -0,0 +1,359 @@
+//! Reading and writing conda `environment.yml` files, which are used to import an existing conda
+//! environment into a pixi project and to export the environments of a project.
+
+use crate::project::manifest::PyPiRequirement;
+use miette::{IntoDiagnostic, LabeledSpan, NamedSource, WrapErr};
+use rattler_conda_types::{MatchSpec, NamelessMatchSpec, PackageName};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter},
+    ops::Range,
+    path::Path,
+    str::FromStr,
+};
+
+/// The contents of a conda `environment.yml` file.
+#[derive(Debug, Clone, Default)]
+pub struct CondaEnvironmentFile {
+    /// The name of the environment.
+    pub name: Option<String>,
+
+    /// The channels to get the conda packages from.
+    pub channels: Vec<String>,
+
+    /// The conda dependencies.
+    pub dependencies: Vec<(PackageName, NamelessMatchSpec)>,
+
+    /// The dependencies that are installed with pip, from the nested `pip:` list.
+    pub pypi_dependencies: Vec<(rip::types::PackageName, PyPiRequirement)>,
+}
+
+/// The raw structure of an `environment.yml`.
+#[derive(Debug, Deserialize, Serialize)]
+struct RawEnvironmentFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default)]
+    channels: Vec<String>,
+    #[serde(default)]
+    dependencies: Vec<RawDependency>,
+}
+
+/// An entry of the `dependencies` list: either a conda match spec or the list of pip requirements.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+enum RawDependency {
+    Conda(String),
+    Pip { pip: Vec<String> },
+}
+
+/// A dependency of an `environment.yml` that could not be imported.
+#[derive(Debug, Clone)]
+pub struct InvalidDependency {
+    /// The line on which the dependency is defined (1-based), if it could be found.
+    pub line: Option<usize>,
+
+    /// The spec as it was written in the file.
+    pub spec: String,
+
+    /// Why the spec could not be imported.
+    pub reason: String,
+
+    span: Option<Range<usize>>,
+}
+
+impl Display for InvalidDependency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {line}: ")?;
+        }
+        write!(f, "cannot import `{}`: {}", self.spec, self.reason)
+    }
+}
+
+impl CondaEnvironmentFile {
+    /// Reads an `environment.yml` from disk.
+    pub fn from_path(path: &Path) -> miette::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        Self::from_str(&name, &contents)
+    }
+
+    /// Parses the contents of an `environment.yml`. `source_name` is used in error messages.
+    ///
+    /// Dependencies that cannot be parsed are not dropped, instead all of them are reported in
+    /// the returned error together with the line they are defined on.
+    pub fn from_str(source_name: &str, contents: &str) -> miette::Result<Self> {
+        let raw: RawEnvironmentFile = serde_yaml::from_str(contents)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("failed to parse {source_name}"))?;
+
+        let mut locator = SpecLocator::new(contents);
+        let mut invalid = Vec::new();
+        let mut environment = CondaEnvironmentFile {
+            name: raw.name,
+            channels: raw.channels,
+            ..Default::default()
+        };
+
+        for dependency in raw.dependencies {
+            match dependency {
+                RawDependency::Conda(spec) => match parse_conda_spec(&spec) {
+                    Ok(dependency) => environment.dependencies.push(dependency),
+                    Err(reason) => invalid.push(locator.invalid(spec, reason)),
+                },
+                RawDependency::Pip { pip } => {
+                    for spec in pip {
+                        match parse_pypi_spec(&spec) {
+                            Ok(dependency) => environment.pypi_dependencies.push(dependency),
+                            Err(reason) => invalid.push(locator.invalid(spec, reason)),
+                        }
+                    }
+                }
+            }
+        }
+
+        if invalid.is_empty() {
+            return Ok(environment);
+        }
+
+        let labels = invalid
+            .iter()
+            .filter_map(|dependency| {
+                dependency
+                    .span
+                    .clone()
+                    .map(|span| LabeledSpan::new_with_span(Some(dependency.reason.clone()), span))
+            })
+            .collect::<Vec<_>>();
+        let lines = invalid
+            .iter()
+            .map(|dependency| format!("  {dependency}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Err(miette::miette!(
+            labels = labels,
+            help = "fix or remove these dependencies and try again",
+            "failed to import {} dependencies from {source_name}:\n{lines}",
+            invalid.len()
+        )
+        .with_source_code(NamedSource::new(source_name, contents.to_string())))
+    }
+
+    /// Returns the contents of the `environment.yml`. The pypi dependencies are written in a nested
+    /// `pip:` list, conda needs `pip` to install them so it is added to the conda dependencies if
+    /// it is missing.
+    pub fn to_yaml_string(&self) -> miette::Result<String> {
+        let mut dependencies = self
+            .dependencies
+            .iter()
+            .map(|(name, spec)| {
+                RawDependency::Conda(
+                    MatchSpec::from_nameless(spec.clone(), Some(name.clone())).to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if !self.pypi_dependencies.is_empty() {
+            if !self
+                .dependencies
+                .iter()
+                .any(|(name, _)| name.as_normalized() == "pip")
+            {
+                dependencies.push(RawDependency::Conda(String::from("pip")));
+            }
+            dependencies.push(RawDependency::Pip {
+                pip: self
+                    .pypi_dependencies
+                    .iter()
+                    .map(|(name, requirement)| requirement.as_pep508(name).to_string())
+                    .collect(),
+            });
+        }
+
+        serde_yaml::to_string(&RawEnvironmentFile {
+            name: self.name.clone(),
+            channels: self.channels.clone(),
+            dependencies,
+        })
+        .into_diagnostic()
+    }
+}
+
+/// Parses a conda dependency like `python=3.11` or `conda-forge::numpy>=1.26`.
+fn parse_conda_spec(spec: &str) -> Result<(PackageName, NamelessMatchSpec), String> {
+    let spec = MatchSpec::from_str(spec).map_err(|err| err.to_string())?;
+    match spec.into_nameless() {
+        (Some(name), spec) => Ok((name, spec)),
+        (None, _) => Err(String::from("the spec does not contain a package name")),
+    }
+}
+
+/// Parses a pip requirement like `requests>=2.31`.
+fn parse_pypi_spec(spec: &str) -> Result<(rip::types::PackageName, PyPiRequirement), String> {
+    if spec.starts_with('-') {
+        return Err(String::from("pip options are not supported"));
+    }
+    let requirement = pep508_rs::Requirement::from_str(spec).map_err(|err| err.to_string())?;
+    if let Some(pep508_rs::VersionOrUrl::Url(_)) = requirement.version_or_url {
+        return Err(String::from("requirements on a url are not supported"));
+    }
+    let name =
+        rip::types::PackageName::from_str(&requirement.name).map_err(|err| err.to_string())?;
+    Ok((name, PyPiRequirement::from(requirement)))
+}
+
+/// Finds the location of the list entries in the source of an `environment.yml`. The YAML parser
+/// does not keep track of locations, so the entries are matched against the lines of the file in
+/// order.
+struct SpecLocator<'a> {
+    contents: &'a str,
+    offset: usize,
+}
+
+impl<'a> SpecLocator<'a> {
+    fn new(contents: &'a str) -> Self {
+        Self {
+            contents,
+            offset: 0,
+        }
+    }
+
+    /// Returns the byte range of the next list entry that contains `spec`.
+    fn find(&mut self, spec: &str) -> Option<Range<usize>> {
+        let mut line_start = self.offset;
+        for line in self.contents[self.offset..].split_inclusive('\n') {
+            let entry = line
+                .trim_start()
+                .strip_prefix('-')
+                .map(|entry| entry.split(" #").next().unwrap_or(entry).trim())
+                .map(|entry| entry.trim_matches(|c| c == '"' || c == '\''));
+            if entry == Some(spec) {
+                let start = line_start + line.find(spec)?;
+                self.offset = line_start + line.len();
+                return Some(start..start + spec.len());
+            }
+            line_start += line.len();
+        }
+        None
+    }
+
+    fn invalid(&mut self, spec: String, reason: String) -> InvalidDependency {
+        let span = self.find(&spec);
+        let line = span
+            .as_ref()
+            .map(|span| self.contents[..span.start].matches('\n').count() + 1);
+        InvalidDependency {
+            line,
+            spec,
+            reason,
+            span,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_environment_file() {
+        let environment = CondaEnvironmentFile::from_str(
+            "environment.yml",
+            r#"
+name: my-env
+channels:
+  - conda-forge
+  - bioconda
+dependencies:
+  - python=3.11
+  - numpy >=1.26 # numerical computing
+  - conda-forge::pandas
+  - pip
+  - pip:
+    - requests>=2.31
+    - "rich[jupyter]"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(environment.name.as_deref(), Some("my-env"));
+        assert_eq!(environment.channels, vec!["conda-forge", "bioconda"]);
+        assert_eq!(
+            environment
+                .dependencies
+                .iter()
+                .map(|(name, _)| name.as_source())
+                .collect::<Vec<_>>(),
+            vec!["python", "numpy", "pandas", "pip"]
+        );
+        assert_eq!(
+            environment
+                .pypi_dependencies
+                .iter()
+                .map(|(name, requirement)| requirement.as_pep508(name).to_string())
+                .collect::<Vec<_>>(),
+            vec!["requests>=2.31", "rich[jupyter]"]
+        );
+    }
+
+    #[test]
+    fn test_environment_file_roundtrip() {
+        let environment = CondaEnvironmentFile {
+            name: Some(String::from("my-env")),
+            channels: vec![String::from("conda-forge")],
+            dependencies: vec![(
+                PackageName::from_str("python").unwrap(),
+                NamelessMatchSpec::from_str(">=3.11").unwrap(),
+            )],
+            pypi_dependencies: vec![(
+                rip::types::PackageName::from_str("requests").unwrap(),
+                PyPiRequirement::from_str(">=2.31").unwrap(),
+            )],
+        };
+
+        let yaml = environment.to_yaml_string().unwrap();
+        let parsed = CondaEnvironmentFile::from_str("environment.yml", &yaml).unwrap();
+        assert_eq!(parsed.name, environment.name);
+        assert_eq!(parsed.channels, environment.channels);
+        assert_eq!(
+            parsed
+                .dependencies
+                .iter()
+                .map(|(name, spec)| format!("{} {}", name.as_source(), spec))
+                .collect::<Vec<_>>(),
+            vec!["python >=3.11", "pip *"]
+        );
+        assert_eq!(parsed.pypi_dependencies.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_dependencies_are_reported() {
+        let err = CondaEnvironmentFile::from_str(
+            "environment.yml",
+            r#"dependencies:
+  - python=3.11
+  - numpy[version=1
+  - pip:
+    - requests>=2.31
+    - -e .
+"#,
+        )
+        .unwrap_err();
+
+        let message = err.to_string();
+        assert!(
+            message.contains("line 3: cannot import `numpy[version=1`"),
+            "{message}"
+        );
+        assert!(
+            message.contains("line 6: cannot import `-e .`"),
+            "{message}"
+        );
+    }
+}
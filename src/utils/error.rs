@@ -0,0 +1,27 @@
+use std::{error::Error, fmt::Write};
+
+/// Formats `err` followed by its sources, each source on a new line that starts with "because".
+pub fn format_error_chain(err: &dyn Error) -> String {
+    let mut report = err.to_string();
+    let mut err = err;
+    while let Some(source) = err.source() {
+        write!(&mut report, "\nbecause {}", source).unwrap();
+        err = source;
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("outer")]
+    struct Outer(#[source] std::io::Error);
+
+    #[test]
+    fn test_format_error_chain() {
+        let err = Outer(std::io::Error::new(std::io::ErrorKind::Other, "inner"));
+        assert_eq!(format_error_chain(&err), "outer\nbecause inner");
+    }
+}
@@ -1 +1,5 @@
+pub mod conda_environment_file;
+pub mod error;
+pub mod requirements_file;
+pub mod schema;
 pub mod spanned;
@@ -0,0 +1,320 @@
+//! Reading pip requirements files (`requirements.txt`) and constraints files, which are used to
+//! import the requirements of an existing python project as pypi-dependencies.
+//!
+//! Only the parts of the format that can be represented in the manifest are read, every line that
+//! can not be represented is returned as a [`SkippedLine`] so it can be reported to the user.
+
+use miette::{IntoDiagnostic, WrapErr};
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// A requirement that was read from a requirements file.
+#[derive(Debug, Clone)]
+pub struct FileRequirement {
+    /// The parsed requirement.
+    pub requirement: pep508_rs::Requirement,
+
+    /// The file the requirement was read from.
+    pub path: PathBuf,
+
+    /// The line on which the requirement starts (1-based).
+    pub line: usize,
+}
+
+/// A line of a requirements file that was not (fully) imported.
+#[derive(Debug, Clone)]
+pub struct SkippedLine {
+    /// The file that contains the line.
+    pub path: PathBuf,
+
+    /// The line number (1-based).
+    pub line: usize,
+
+    /// The contents of the line, without comments.
+    pub contents: String,
+
+    /// Why the line was skipped.
+    pub reason: String,
+}
+
+impl Display for SkippedLine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}: `{}`: {}",
+            self.path.display(),
+            self.line,
+            self.contents,
+            self.reason
+        )
+    }
+}
+
+/// The requirements and constraints read from a set of requirements and constraints files.
+#[derive(Debug, Clone, Default)]
+pub struct RequirementsFiles {
+    /// The requirements, in the order they were found.
+    pub requirements: Vec<FileRequirement>,
+
+    /// The constraints, in the order they were found.
+    pub constraints: Vec<FileRequirement>,
+
+    /// The lines that were not imported.
+    pub skipped: Vec<SkippedLine>,
+
+    /// The files that have been read and whether they were read as requirements or constraints,
+    /// used to ignore files that are included more than once in the same way.
+    visited: HashSet<(FileKind, PathBuf)>,
+}
+
+/// Whether the lines of a file are requirements or constraints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum FileKind {
+    Requirements,
+    Constraints,
+}
+
+impl RequirementsFiles {
+    /// Reads the given requirements and constraints files, including the files they refer to with
+    /// `-r` and `-c`.
+    pub fn from_paths(requirements: &[PathBuf], constraints: &[PathBuf]) -> miette::Result<Self> {
+        let mut files = Self::default();
+        for path in requirements {
+            files.read(path, FileKind::Requirements)?;
+        }
+        for path in constraints {
+            files.read(path, FileKind::Constraints)?;
+        }
+        Ok(files)
+    }
+
+    /// Reads a single file.
+    fn read(&mut self, path: &Path, kind: FileKind) -> miette::Result<()> {
+        let contents = std::fs::read_to_string(path)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+        let canonical_path = dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if !self.visited.insert((kind, canonical_path)) {
+            return Ok(());
+        }
+        self.parse(path, &contents, kind)
+    }
+
+    /// Parses the `contents` of the file at `path`.
+    fn parse(&mut self, path: &Path, contents: &str, kind: FileKind) -> miette::Result<()> {
+        for (line, contents) in logical_lines(contents) {
+            let skip = |reason: &str| SkippedLine {
+                path: path.to_path_buf(),
+                line,
+                contents: contents.clone(),
+                reason: reason.to_string(),
+            };
+
+            // Options that refer to other files
+            if contents.starts_with('-') {
+                let (option, value) = split_option(&contents);
+                let include_kind = match option {
+                    "-r" | "--requirement" => kind,
+                    "-c" | "--constraint" => FileKind::Constraints,
+                    _ => {
+                        self.skipped
+                            .push(skip(&format!("the `{option}` option is not supported")));
+                        continue;
+                    }
+                };
+                let include = path.parent().unwrap_or(Path::new("")).join(value);
+                self.read(&include, include_kind)
+                    .wrap_err_with(|| format!("included from {}:{line}", path.display()))?;
+                continue;
+            }
+
+            // Options that apply to a single requirement, like `--hash`, are ignored
+            let (requirement, options) = match contents.find(" -") {
+                Some(idx) => (contents[..idx].trim(), contents[idx..].trim()),
+                None => (contents.as_str(), ""),
+            };
+            if !options.is_empty() {
+                self.skipped.push(skip(&format!(
+                    "`{options}` is not supported, the requirement is imported without it"
+                )));
+            }
+
+            if requirement.contains("://") || requirement.starts_with(['.', '/']) {
+                self.skipped
+                    .push(skip("requirements on a url or path are not supported"));
+                continue;
+            }
+            let requirement = match pep508_rs::Requirement::from_str(requirement) {
+                Ok(requirement) => requirement,
+                Err(err) => {
+                    let message = err.to_string();
+                    self.skipped.push(skip(
+                        message.lines().next().unwrap_or("invalid requirement"),
+                    ));
+                    continue;
+                }
+            };
+            if let Some(pep508_rs::VersionOrUrl::Url(_)) = requirement.version_or_url {
+                self.skipped
+                    .push(skip("requirements on a url or path are not supported"));
+                continue;
+            }
+
+            let requirement = FileRequirement {
+                requirement,
+                path: path.to_path_buf(),
+                line,
+            };
+            match kind {
+                FileKind::Requirements => self.requirements.push(requirement),
+                FileKind::Constraints => self.constraints.push(requirement),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits the contents of a requirements file into logical lines: comments are removed and lines
+/// that end with a `\` are joined with the next line. Returns the line number on which each logical
+/// line starts together with its contents. Empty lines are skipped.
+fn logical_lines(contents: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    for (idx, line) in contents.lines().enumerate() {
+        let line = strip_comment(line);
+        let (line, continues) = match line.trim_end().strip_suffix('\\') {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+
+        let (_, logical) = current.get_or_insert_with(|| (idx + 1, String::new()));
+        if !logical.is_empty() {
+            logical.push(' ');
+        }
+        logical.push_str(line.trim());
+
+        if !continues {
+            if let Some((start, logical)) = current.take() {
+                if !logical.is_empty() {
+                    lines.push((start, logical));
+                }
+            }
+        }
+    }
+    if let Some((start, logical)) = current {
+        if !logical.is_empty() {
+            lines.push((start, logical));
+        }
+    }
+    lines
+}
+
+/// Removes a comment from a line. A comment starts with a `#` at the start of the line or after
+/// whitespace.
+fn strip_comment(line: &str) -> &str {
+    if line.trim_start().starts_with('#') {
+        return "";
+    }
+    match line.find(" #").or_else(|| line.find("\t#")) {
+        Some(idx) => &line[..idx],
+        None => line,
+    }
+}
+
+/// Splits an option like `-r file.txt`, `-rfile.txt` or `--requirement=file.txt` into the option
+/// and its value.
+fn split_option(line: &str) -> (&str, &str) {
+    if let Some((option, value)) = line.split_once(['=', ' ']) {
+        return (option, value.trim());
+    }
+    if !line.starts_with("--") && line.len() > 2 {
+        return (&line[..2], line[2..].trim());
+    }
+    (line, "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_requirements() {
+        let mut files = RequirementsFiles::default();
+        files
+            .parse(
+                Path::new("requirements.txt"),
+                r#"# The requirements of the project
+requests>=2.31 # http
+rich[jupyter]==13.7.0
+pywin32; sys_platform == "win32"
+numpy>=1.26 \
+    --hash=sha256:58cd2187c01e70e6e26505bca751777aa9f2ee0b7f4300988b709f44e013003f
+--index-url https://example.com/simple
+git+https://github.com/psf/black
+black @ https://github.com/psf/black/archive/main.zip
+"#,
+                FileKind::Requirements,
+            )
+            .unwrap();
+
+        assert_eq!(
+            files
+                .requirements
+                .iter()
+                .map(|requirement| (requirement.requirement.name.as_str(), requirement.line))
+                .collect::<Vec<_>>(),
+            vec![("requests", 2), ("rich", 3), ("pywin32", 4), ("numpy", 5)]
+        );
+        assert!(files.requirements[2].requirement.marker.is_some());
+        assert_eq!(
+            files
+                .skipped
+                .iter()
+                .map(|skipped| skipped.line)
+                .collect::<Vec<_>>(),
+            vec![5, 7, 8, 9]
+        );
+        assert_eq!(
+            files.skipped[1].to_string(),
+            "requirements.txt:7: `--index-url https://example.com/simple`: the `--index-url` option is not supported"
+        );
+    }
+
+    #[test]
+    fn test_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("requirements.txt"),
+            "-r requirements-base.txt\n-c constraints.txt\npytest\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("requirements-base.txt"), "requests\n").unwrap();
+        std::fs::write(dir.path().join("constraints.txt"), "requests<3\n").unwrap();
+
+        let files =
+            RequirementsFiles::from_paths(&[dir.path().join("requirements.txt")], &[]).unwrap();
+        assert_eq!(
+            files
+                .requirements
+                .iter()
+                .map(|requirement| requirement.requirement.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["requests", "pytest"]
+        );
+        assert_eq!(files.constraints.len(), 1);
+        assert!(files.skipped.is_empty());
+
+        // A file that is used both for requirements and constraints is read twice
+        let files = RequirementsFiles::from_paths(
+            &[dir.path().join("constraints.txt")],
+            &[dir.path().join("constraints.txt")],
+        )
+        .unwrap();
+        assert_eq!(files.requirements.len(), 1);
+        assert_eq!(files.constraints.len(), 1);
+    }
+}
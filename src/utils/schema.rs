@@ -0,0 +1,139 @@
+//! Helpers to describe the project manifest as a JSON Schema.
+//!
+//! The schema is derived from the types that are used to deserialize the manifest. Some of those
+//! types are deserialized from a string representation or are defined in other crates, the
+//! functions in this module describe what these values look like in the manifest.
+
+use rattler_conda_types::Platform;
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{
+        ArrayValidation, InstanceType, Metadata, ObjectValidation, RootSchema, Schema,
+        SchemaObject, SubschemaValidation,
+    },
+};
+
+/// Returns a schema that matches a value that matches any of the given `schemas`.
+pub fn any_of(schemas: impl IntoIterator<Item = Schema>) -> Schema {
+    SchemaObject {
+        subschemas: Some(Box::new(SubschemaValidation {
+            any_of: Some(schemas.into_iter().collect()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Returns a schema for a string with the given description.
+pub fn string(description: &str) -> Schema {
+    SchemaObject {
+        metadata: Some(Box::new(Metadata {
+            description: Some(description.to_string()),
+            ..Default::default()
+        })),
+        instance_type: Some(InstanceType::String.into()),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Returns a schema for an array of unique items that match `items`.
+fn unique_array(items: Schema) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::Array.into()),
+        array: Some(Box::new(ArrayValidation {
+            items: Some(items.into()),
+            unique_items: Some(true),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Returns a schema for a table with arbitrary keys and values that match `values`.
+fn table_of(values: Schema) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::Object.into()),
+        object: Some(Box::new(ObjectValidation {
+            additional_properties: Some(Box::new(values)),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// The schema of a list of platforms, e.g. `["linux-64", "osx-arm64"]`.
+pub fn platforms(_gen: &mut SchemaGenerator) -> Schema {
+    let platform = SchemaObject {
+        metadata: Some(Box::new(Metadata {
+            description: Some(String::from("A platform supported by conda")),
+            ..Default::default()
+        })),
+        instance_type: Some(InstanceType::String.into()),
+        enum_values: Some(
+            Platform::all()
+                .filter(|platform| *platform != Platform::NoArch)
+                .map(|platform| platform.as_str().into())
+                .collect(),
+        ),
+        ..Default::default()
+    };
+    unique_array(platform.into())
+}
+
+/// The schema of a table that maps package names to conda match specs. A spec is either written
+/// as a string (e.g. `">=3.8"`) or as a table with the individual fields of the spec.
+pub fn dependencies(gen: &mut SchemaGenerator) -> Schema {
+    let mut properties: schemars::Map<String, Schema> = [
+        "version",
+        "build",
+        "channel",
+        "subdir",
+        "file_name",
+        "md5",
+        "sha256",
+    ]
+    .into_iter()
+    .map(|key| (key.to_string(), gen.subschema_for::<String>()))
+    .collect();
+    properties.insert(
+        String::from("build_number"),
+        any_of([gen.subschema_for::<String>(), gen.subschema_for::<u64>()]),
+    );
+
+    let spec_table = SchemaObject {
+        instance_type: Some(InstanceType::Object.into()),
+        object: Some(Box::new(ObjectValidation {
+            properties,
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+
+    table_of(any_of([
+        string("A conda match spec, e.g. `>=3.8,<4`"),
+        spec_table.into(),
+    ]))
+}
+
+/// The schema of a value that can be written as either a single string or a list of strings.
+pub fn one_or_many_strings(gen: &mut SchemaGenerator) -> Schema {
+    any_of([
+        gen.subschema_for::<String>(),
+        gen.subschema_for::<Vec<String>>(),
+    ])
+}
+
+/// Returns the JSON Schema of the project manifest (`pixi.toml`).
+pub fn project_manifest_schema() -> RootSchema {
+    let mut schema = schemars::schema_for!(crate::project::manifest::ProjectManifest);
+    schema.schema.metadata().title = Some(String::from("Pixi project manifest"));
+    schema.schema.metadata().description = Some(format!(
+        "The schema of the `{}` file that describes a pixi project",
+        crate::consts::PROJECT_MANIFEST
+    ));
+    schema
+}
@@ -61,6 +61,12 @@ impl InitBuilder {
         self.args.channels = Some(vec![]);
         self
     }
+
+    /// Import the channels and dependencies from a conda environment file.
+    pub fn with_import(mut self, path: impl AsRef<Path>) -> Self {
+        self.args.import = Some(path.as_ref().to_path_buf());
+        self
+    }
 }
 
 impl IntoFuture for InitBuilder {
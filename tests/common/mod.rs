@@ -175,6 +175,7 @@ impl PixiControl {
                 path: self.project_path().to_path_buf(),
                 channels: None,
                 platforms: Vec::new(),
+                import: None,
             },
         }
     }
@@ -187,6 +188,7 @@ impl PixiControl {
                 path: self.project_path().to_path_buf(),
                 channels: None,
                 platforms,
+                import: None,
             },
         }
     }
@@ -204,6 +206,8 @@ impl PixiControl {
                 no_lockfile_update: false,
                 platform: Default::default(),
                 pypi: false,
+                requirement: Vec::new(),
+                constraint: Vec::new(),
                 sdist_resolution: Default::default(),
             },
         }
@@ -225,7 +229,7 @@ impl PixiControl {
         args.manifest_path = args.manifest_path.or_else(|| Some(self.manifest_path()));
         let project = self.project()?;
         let task = ExecutableTask::from_cmd_args(&project, args.task, Some(Platform::current()));
-        let task_env = get_task_env(&project, args.lock_file_usage.into()).await?;
+        let task_env = get_task_env(&project, args.lock_file_usage.into(), &args.env_file).await?;
 
         #[derive(Error, Debug, Diagnostic)]
         enum RunError {
@@ -1,7 +1,7 @@
 mod common;
 
 use crate::common::PixiControl;
-use rattler_conda_types::{Channel, ChannelConfig, Version};
+use rattler_conda_types::{Channel, ChannelConfig, PackageName, Version};
 use std::str::FromStr;
 
 #[tokio::test]
@@ -74,3 +74,63 @@ async fn default_channel() {
         [&Channel::from_str("conda-forge", &ChannelConfig::default()).unwrap()]
     )
 }
+
+/// Tests that the name, channels and dependencies of a conda environment file are imported.
+#[tokio::test]
+async fn import_environment_file() {
+    let pixi = PixiControl::new().unwrap();
+    let env_file = pixi.project_path().join("environment.yml");
+    std::fs::write(
+        &env_file,
+        r#"name: imported
+channels:
+  - bioconda
+  - nodefaults
+dependencies:
+  - python=3.11
+  - samtools >=1.19
+  - pip:
+    - requests>=2.31
+"#,
+    )
+    .unwrap();
+
+    pixi.init().with_import(&env_file).await.unwrap();
+    let project = pixi.project().unwrap();
+
+    assert_eq!(project.name(), "imported");
+    assert_eq!(
+        Vec::from_iter(project.channels()),
+        [&Channel::from_str("bioconda", &ChannelConfig::default()).unwrap()]
+    );
+
+    let dependencies = project.dependencies(None, None);
+    assert_eq!(
+        dependencies
+            .names()
+            .map(PackageName::as_source)
+            .collect::<Vec<_>>(),
+        ["python", "samtools"]
+    );
+
+    let pypi_dependencies = project.pypi_dependencies(None);
+    assert!(pypi_dependencies
+        .keys()
+        .any(|name| name.as_str() == "requests"));
+}
+
+/// Tests that dependencies that cannot be imported are reported and no manifest is written.
+#[tokio::test]
+async fn import_invalid_environment_file() {
+    let pixi = PixiControl::new().unwrap();
+    let env_file = pixi.project_path().join("environment.yml");
+    std::fs::write(
+        &env_file,
+        "dependencies:\n  - python=3.11\n  - numpy[version=1\n",
+    )
+    .unwrap();
+
+    let err = pixi.init().with_import(&env_file).await.unwrap_err();
+    assert!(err.to_string().contains("line 3"), "{err}");
+    assert!(!pixi.manifest_path().exists());
+}
@@ -207,3 +207,80 @@ async fn test_cwd() {
         .await
         .is_err());
 }
+
+#[cfg(unix)]
+#[tokio::test]
+#[cfg_attr(not(feature = "slow_integration_tests"), ignore)]
+async fn test_run_picks_task_interactively() {
+    use nix::sys::wait::WaitStatus;
+    use pixi::unix::PtySession;
+
+    let pixi = PixiControl::new().unwrap();
+    pixi.init().await.unwrap();
+    pixi.tasks()
+        .add("create-marker", None, FeatureName::Default)
+        .with_commands(["touch marker"])
+        .execute()
+        .unwrap();
+    pixi.tasks()
+        .add("fail", None, FeatureName::Default)
+        .with_commands(["exit 1"])
+        .execute()
+        .unwrap();
+
+    // Start `pixi run` without a task in a pseudo terminal, filter the list and pick the task.
+    let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_pixi"));
+    command
+        .arg("run")
+        .arg("--manifest-path")
+        .arg(pixi.manifest_path())
+        .current_dir(pixi.project_path());
+    let mut session = PtySession::new(command).unwrap();
+    session.send("marker").unwrap();
+    session.send("\r").unwrap();
+    session.flush().unwrap();
+
+    let status = session.process.wait().unwrap();
+    assert!(matches!(status, WaitStatus::Exited(_, 0)));
+    assert!(pixi.project_path().join("marker").is_file());
+}
+
+#[tokio::test]
+async fn test_env_file() {
+    let pixi = PixiControl::new().unwrap();
+    pixi.init().without_channels().await.unwrap();
+
+    fs::write(
+        pixi.project_path().join(".env"),
+        "GREETING=hello\nTARGET=\"$GREETING world\"\n",
+    )
+    .unwrap();
+
+    pixi.tasks()
+        .add("greet", None, FeatureName::Default)
+        .with_commands(["echo $TARGET"])
+        .execute()
+        .unwrap();
+
+    let result = pixi
+        .run(Args {
+            task: vec!["greet".to_string()],
+            env_file: vec![pixi.project_path().join(".env")],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(result.exit_code, 0);
+    assert_eq!(result.stdout.trim(), "hello world");
+
+    // A missing env file given on the command line is an error
+    assert!(pixi
+        .run(Args {
+            task: vec!["greet".to_string()],
+            env_file: vec![pixi.project_path().join(".env.missing")],
+            ..Default::default()
+        })
+        .await
+        .is_err());
+}